@@ -19,13 +19,19 @@ fn main() {
 
     renderer.directional_light(0).unwrap().set_direction(&vec3(0.0, -1.0, 0.0));
     renderer.directional_light(0).unwrap().set_intensity(1.0);
+    renderer.ambient_light().set_intensity(0.3);
+    renderer.enable_ssao().unwrap();
 
+    // Post processing
+    let mut post_process_chain = effects::PostProcessChain::new(&gl, width, height).unwrap();
     let mut fog_effect = effects::FogEffect::new(&gl).unwrap();
     fog_effect.color = vec3(0.8, 0.8, 0.8);
+    let mut bloom_effect = effects::BloomEffect::new(&gl, width, height, 5).unwrap();
+    let mut tone_mapping_effect = effects::ToneMappingEffect::new(&gl).unwrap();
+    tone_mapping_effect.eye_adaptation = true;
     let mut debug_effect = effects::DebugEffect::new(&gl).unwrap();
 
     // main loop
-    let mut time = 0.0;
     let mut rotating = false;
     window.render_loop(move |frame_input|
     {
@@ -52,7 +58,6 @@ fn main() {
                 }
             }
         }
-        time += frame_input.elapsed_time;
 
         // draw
         // Geometry pass
@@ -61,12 +66,14 @@ fn main() {
             monkey.render(&transformation, &camera);
         }).unwrap();
 
+        // Screen space ambient occlusion
+        renderer.ssao_pass(&camera).unwrap();
+
         // Light pass
-        renderer.light_pass(&camera).unwrap();
+        renderer.hdr_light_pass(&camera).unwrap();
 
-        // Effect
-        fog_effect.apply(time as f32, &camera, renderer.geometry_pass_depth_texture()).unwrap();
-        debug_effect.apply(&camera, renderer.geometry_pass_texture(), renderer.geometry_pass_depth_texture()).unwrap();
+        // Effects
+        post_process_chain.apply(&frame_input, &camera, &renderer, &mut [&mut fog_effect, &mut bloom_effect, &mut tone_mapping_effect, &mut debug_effect]).unwrap();
 
         if let Some(ref path) = screenshot_path {
             #[cfg(target_arch = "x86_64")]
@@ -101,7 +101,7 @@ fn main() {
         };
 
         // Shadow pass
-        renderer.shadow_pass(&|_|{});
+        renderer.shadow_pass(&camera, &|_|{});
 
         // Geometry pass
         renderer.geometry_pass(&||
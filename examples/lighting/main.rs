@@ -105,7 +105,7 @@ fn main() {
         };
 
         // Shadow pass
-        renderer.shadow_pass(&render_scene);
+        renderer.shadow_pass(&camera, &render_scene);
 
         // Geometry pass
         renderer.geometry_pass(&||
@@ -219,7 +219,7 @@ fn main()
         }
 
         // Shadow pass
-        renderer.shadow_pass(&|camera: &Camera| {
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
             model.render(&Mat4::identity(), camera);
         });
 
@@ -16,7 +16,7 @@ fn main() {
 
     let box_mesh = tri_mesh::MeshBuilder::new().unconnected_cube().build().unwrap();
     let mut box_mesh = Mesh::new(&gl, &box_mesh.indices_buffer(), &box_mesh.positions_buffer_f32(), &box_mesh.normals_buffer_f32()).unwrap();
-    box_mesh.texture = Some(texture::Texture2D::new_from_bytes(&gl, include_bytes!("../assets/textures/test_texture.jpg")).unwrap());
+    box_mesh.texture = Some(std::rc::Rc::new(texture::Texture2D::new_from_bytes(&gl, include_bytes!("../assets/textures/test_texture.jpg")).unwrap()));
 
     let texture3d = texture::Texture3D::new_from_bytes(&gl,
                                                        include_bytes!("../assets/textures/skybox_evening/back.jpg"),
@@ -59,7 +59,7 @@ fn main() {
     light.enable_shadows();
 
     // Shadow pass
-    renderer.shadow_pass(&|camera: &Camera| {
+    renderer.shadow_pass(&camera, &|camera: &Camera| {
         let transformation = Mat4::from_translation(vec3(0.0, 2.0, 0.0));
         model.render(&transformation, camera);
         wireframe.render(&transformation, camera);
@@ -35,6 +35,8 @@ impl Gl {
     {
         let gl = Gl { inner: InnerGl::load_with(loadfn) };
         gl.bind_vertex_array(&gl.create_vertex_array().unwrap());
+        // Filter across the faces of cube maps, which is always done in WebGL 2
+        gl.enable(consts::TEXTURE_CUBE_MAP_SEAMLESS);
         gl
     }
 
@@ -518,6 +520,13 @@ impl Gl {
         }
     }
 
+    pub fn clear_bufferuiv(&self, buffer: u32, draw_buffer: i32, values: &[u32])
+    {
+        unsafe {
+            self.inner.ClearBufferuiv(buffer, draw_buffer, values.as_ptr());
+        }
+    }
+
     pub fn enable(&self, cap: u32)
     {
         unsafe {
@@ -631,6 +640,13 @@ impl Gl {
         }
     }
 
+    pub fn generate_mipmap(&self, target: u32)
+    {
+        unsafe {
+            self.inner.GenerateMipmap(target);
+        }
+    }
+
     pub fn tex_parameteri(&self, target: u32, pname: u32, param: i32)
     {
         unsafe {
@@ -26,6 +26,8 @@ pub struct Gl {
 impl Gl {
     pub fn new(webgl_context: InnerGl) -> Gl
     {
+        // Makes floating point textures renderable
+        webgl_context.get_extension("EXT_color_buffer_float").ok();
         Gl {
             inner: std::rc::Rc::new(webgl_context)
         }
@@ -94,7 +96,7 @@ impl Gl {
 
     pub fn compile_shader(&self, source: &str, shader: &Shader) -> Result<(), String>
     {
-        let header = "#version 300 es\nprecision highp float;\nprecision highp int;\nprecision highp sampler2DArray;\n";
+        let header = "#version 300 es\nprecision highp float;\nprecision highp int;\nprecision highp sampler2DArray;\nprecision highp sampler2D;\nprecision highp samplerCube;\n";
         let s: &str = &[header, source].concat();
 
         self.inner.shader_source(shader, s);
@@ -263,6 +265,11 @@ impl Gl {
                                     dst_x0 as i32, dst_y0 as i32, dst_x1 as i32, dst_y1 as i32, mask, filter);
     }
 
+    pub fn clear_bufferuiv(&self, buffer: u32, draw_buffer: i32, values: &[u32])
+    {
+        self.inner.clear_bufferuiv_with_u32_array(buffer, draw_buffer, &mut values.to_vec());
+    }
+
     pub fn draw_buffers(&self, draw_buffers: &[u32])
     {
         use wasm_bindgen::JsCast;
@@ -10,18 +10,23 @@ pub struct Camera {
     z_far: f32,
     view: Mat4,
     projection: Mat4,
+    unjittered_projection: Mat4,
+    jitter: Vec2,
     screen2ray: Mat4,
     matrix_buffer: UniformBuffer,
-    frustrum: [Vec4; 6]
+    frustrum: [Vec4; 6],
+    frustrum_corners: [Vec3; 8]
 }
 
 impl Camera
 {
     fn new(gl: &Gl) -> Camera
     {
-        Camera {matrix_buffer: UniformBuffer::new(gl, &vec![16, 16, 16, 3, 1]).unwrap(), frustrum: [vec4(0.0, 0.0, 0.0, 0.0); 6], fov: degrees(0.0), z_near: 0.0, z_far: 0.0,
+        Camera {matrix_buffer: UniformBuffer::new(gl, &vec![16, 16, 16, 3, 1]).unwrap(), frustrum: [vec4(0.0, 0.0, 0.0, 0.0); 6],
+            frustrum_corners: [vec3(0.0, 0.0, 0.0); 8], fov: degrees(0.0), z_near: 0.0, z_far: 0.0,
             position: vec3(0.0, 0.0, 5.0), target: vec3(0.0, 0.0, 0.0), up: vec3(0.0, 1.0, 0.0),
-            view: Mat4::identity(), projection: Mat4::identity(), screen2ray: Mat4::identity()}
+            view: Mat4::identity(), projection: Mat4::identity(), unjittered_projection: Mat4::identity(), jitter: vec2(0.0, 0.0),
+            screen2ray: Mat4::identity()}
     }
 
     pub fn new_orthographic(gl: &Gl, position: Vec3, target: Vec3, up: Vec3, width: f32, height: f32, depth: f32) -> Camera
@@ -46,10 +51,7 @@ impl Camera
         self.fov = fovy;
         self.z_near = z_near;
         self.z_far = z_far;
-        self.projection = perspective(fovy, aspect, z_near, z_far);
-        self.update_screen2ray();
-        self.update_matrix_buffer();
-        self.update_frustrum();
+        self.set_projection(perspective(fovy, aspect, z_near, z_far));
     }
 
     pub fn set_orthographic_projection(&mut self, width: f32, height: f32, depth: f32)
@@ -57,10 +59,20 @@ impl Camera
         self.fov = degrees(0.0);
         self.z_near = -0.5 * depth;
         self.z_far = 0.5 * depth;
-        self.projection = ortho(-0.5 * width, 0.5 * width, -0.5 * height, 0.5 * height, -0.5 * depth, 0.5 * depth);
-        self.update_screen2ray();
-        self.update_matrix_buffer();
-        self.update_frustrum();
+        self.set_projection(ortho(-0.5 * width, 0.5 * width, -0.5 * height, 0.5 * height, -0.5 * depth, 0.5 * depth));
+    }
+
+    /// The jitter is in normalized device coordinates, where the screen goes from -1 to 1.
+    pub fn set_jitter(&mut self, jitter: Vec2)
+    {
+        self.jitter = jitter;
+        let projection = self.unjittered_projection;
+        self.set_projection(projection);
+    }
+
+    pub fn jitter(&self) -> Vec2
+    {
+        self.jitter
     }
 
     pub fn set_size(&mut self, width: f32, height: f32) {
@@ -110,6 +122,11 @@ impl Camera
         &self.projection
     }
 
+    pub fn get_unjittered_projection(&self) -> &Mat4
+    {
+        &self.unjittered_projection
+    }
+
     pub fn position(&self) -> &Vec3
     {
         &self.position
@@ -125,11 +142,30 @@ impl Camera
         &self.up
     }
 
+    pub fn z_near(&self) -> f32
+    {
+        self.z_near
+    }
+
+    pub fn z_far(&self) -> f32
+    {
+        self.z_far
+    }
+
     pub fn matrix_buffer(&self) -> &UniformBuffer
     {
         &self.matrix_buffer
     }
 
+    fn set_projection(&mut self, projection: Mat4)
+    {
+        self.unjittered_projection = projection;
+        self.projection = Mat4::from_translation(vec3(self.jitter.x, self.jitter.y, 0.0)) * projection;
+        self.update_screen2ray();
+        self.update_matrix_buffer();
+        self.update_frustrum();
+    }
+
     fn update_screen2ray(&mut self)
     {
         let mut v = self.view.clone();
@@ -154,9 +190,15 @@ impl Camera
          vec4(m.x.w - m.x.y, m.y.w - m.y.y,m.z.w - m.z.y, m.w.w - m.w.y),
          vec4(m.x.w + m.x.z,m.y.w + m.y.z,m.z.w + m.z.z, m.w.w + m.w.z),
          vec4(m.x.w - m.x.z,m.y.w - m.y.z,m.z.w - m.z.z, m.w.w - m.w.z)];
+
+        if let Some(inverse) = m.invert() {
+            for i in 0..8 {
+                let corner = inverse * vec4(if i & 1 == 0 {-1.0} else {1.0}, if i & 2 == 0 {-1.0} else {1.0}, if i & 4 == 0 {-1.0} else {1.0}, 1.0);
+                self.frustrum_corners[i] = corner.truncate() / corner.w;
+            }
+        }
     }
 
-    // false if fully outside, true if inside or intersects
     pub fn in_frustrum(&self, min: &Vec3, max: &Vec3) -> bool
     {
         // check box outside/inside of frustum
@@ -173,9 +215,14 @@ impl Camera
             if self.frustrum[i].dot(vec4(max.x, max.y, max.z, 1.0)) < 0.0 {out += 1};
             if out == 8 {return false;}
         }
-        // TODO: Test the frustum corners against the box planes (http://www.iquilezles.org/www/articles/frustumcorrect/frustumcorrect.htm)
 
-        return true;
+        // check frustum outside/inside box (http://www.iquilezles.org/www/articles/frustumcorrect/frustumcorrect.htm)
+        for i in 0..3
+        {
+            if self.frustrum_corners.iter().all(|corner| corner[i] > max[i]) {return false;}
+            if self.frustrum_corners.iter().all(|corner| corner[i] < min[i]) {return false;}
+        }
+        true
     }
 
     pub fn translate(&mut self, change: &Vec3)
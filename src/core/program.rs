@@ -146,6 +146,11 @@ impl Program
         Ok(())
     }
 
+    pub fn has_uniform(&self, name: &str) -> bool
+    {
+        self.uniforms.contains_key(name)
+    }
+
     fn get_uniform_location(&self, name: &str) -> Result<&gl::UniformLocation, Error>
     {
         self.set_used();
@@ -237,6 +242,25 @@ impl Program
         Ok(())
     }
 
+    pub fn use_attribute_vec4_float(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, index: usize) -> Result<(), Error>
+    {
+        self.use_attribute_vec4_float_divisor(buffer, attribute_name, index, 0)?;
+        Ok(())
+    }
+
+    pub fn use_attribute_vec4_float_divisor(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, index: usize, divisor: usize) -> Result<(), Error>
+    {
+        buffer.bind();
+        let offset = buffer.offset_from(index);
+        let loc = self.location(attribute_name)?;
+        self.gl.enable_vertex_attrib_array(loc);
+        self.gl.vertex_attrib_pointer(loc, 4, gl::consts::FLOAT, false, 0, offset as u32);
+        self.gl.vertex_attrib_divisor(loc, divisor as u32);
+        self.gl.unbind_buffer(gl::consts::ARRAY_BUFFER);
+        self.gl.unuse_program();
+        Ok(())
+    }
+
     pub fn draw_arrays(&self, count: u32)
     {
         self.set_used();
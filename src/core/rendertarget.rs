@@ -133,6 +133,25 @@ impl RenderTarget
         Ok(())
     }
 
+    pub fn write_to_depth_cube_map(&self, depth_texture: &Texture3D, face: usize) -> Result<(), Error>
+    {
+        self.gl.bind_framebuffer(gl::consts::DRAW_FRAMEBUFFER, self.id.as_ref());
+        depth_texture.bind_to_depth_target(face);
+        self.gl.viewport(0, 0, depth_texture.width as i32, depth_texture.height as i32);
+        self.gl.check_framebuffer_status().map_err(|message| Error::FailedToCreateFramebuffer {message})?;
+        Ok(())
+    }
+
+    pub fn write_to_color_cube_map(&self, texture: &Texture3D, face: usize, mip_level: usize) -> Result<(), Error>
+    {
+        self.gl.bind_framebuffer(gl::consts::DRAW_FRAMEBUFFER, self.id.as_ref());
+        let size = (texture.width >> mip_level).max(1);
+        self.gl.viewport(0, 0, size as i32, size as i32);
+        texture.bind_to_framebuffer(face, mip_level);
+        self.gl.check_framebuffer_status().map_err(|message| Error::FailedToCreateFramebuffer {message})?;
+        Ok(())
+    }
+
     pub fn write_to_color_array(&self, texture: &Texture2DArray, channel_to_texture_layer_map: &dyn Fn(usize) -> usize) -> Result<(), Error>
     {
         self.gl.bind_framebuffer(gl::consts::DRAW_FRAMEBUFFER, self.id.as_ref());
@@ -192,6 +211,13 @@ impl RenderTarget
         self.gl.clear(gl::consts::COLOR_BUFFER_BIT);
     }
 
+    pub fn clear_color_integer_and_depth(&self, color: &[u32; 4])
+    {
+        self.gl.clear_bufferuiv(gl::consts::COLOR, 0, color);
+        depth_write(&self.gl,true);
+        self.gl.clear(gl::consts::DEPTH_BUFFER_BIT);
+    }
+
     pub fn clear_color_and_depth(&self, color: &Vec4)
     {
         self.gl.clear_color(color.x, color.y, color.z, color.w);
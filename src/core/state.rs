@@ -1,11 +1,12 @@
 use crate::core::Gl;
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlendType {
     None,
     SrcAlphaOneMinusSrcAlpha,
     DstAlphaOneMinusDstAlpha,
-    OneOne
+    OneOne,
+    OneOneMinusSrcAlpha
 }
 
 pub fn blend(gl: &Gl, blend_type: BlendType)
@@ -29,6 +30,10 @@ pub fn blend(gl: &Gl, blend_type: BlendType)
                 BlendType::OneOne => {
                     gl.enable(gl::consts::BLEND);
                     gl.blend_func(gl::consts::ONE, gl::consts::ONE);
+                },
+                BlendType::OneOneMinusSrcAlpha => {
+                    gl.enable(gl::consts::BLEND);
+                    gl.blend_func(gl::consts::ONE, gl::consts::ONE_MINUS_SRC_ALPHA);
                 }
             }
             CURRENT = blend_type;
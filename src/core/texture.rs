@@ -25,6 +25,19 @@ pub trait Texture {
     fn bind(&self, location: u32);
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wrapping {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Nearest,
+    Linear
+}
+
 pub struct Texture2D {
     gl: Gl,
     id: gl::Texture,
@@ -56,7 +69,18 @@ impl Texture2D
         use image::GenericImageView;
         let img = image::load_from_memory(bytes)?;
         let mut texture = Texture2D::new(gl, img.dimensions().0 as usize, img.dimensions().1 as usize)?;
-        texture.fill_with_u8(texture.width, texture.height, &mut img.raw_pixels());
+        texture.fill_with_u8(texture.width, texture.height, &img.to_rgb().into_raw());
+        Ok(texture)
+    }
+
+    #[cfg(feature = "image-io")]
+    pub fn new_from_hdr_bytes(gl: &Gl, bytes: &[u8]) -> Result<Texture2D, Error>
+    {
+        let (width, height, mut data) = decode_hdr(bytes)?;
+        let texture = Texture2D::new(gl, width, height)?;
+        bind(&texture.gl, &texture.id, texture.target);
+        gl.tex_image_2d_with_f32_data(texture.target, 0, gl::consts::RGB16F, width as u32, height as u32, 0,
+                                      gl::consts::RGB, gl::consts::FLOAT, &mut data);
         Ok(texture)
     }
 
@@ -66,7 +90,7 @@ impl Texture2D
         use image::GenericImageView;
         let img = image::open(path)?;
         let mut texture = Texture2D::new(gl, img.dimensions().0 as usize, img.dimensions().1 as usize)?;
-        texture.fill_with_u8(texture.width, texture.height, &mut img.raw_pixels());
+        texture.fill_with_u8(texture.width, texture.height, &img.to_rgb().into_raw());
         Ok(texture)
     }
 
@@ -90,6 +114,68 @@ impl Texture2D
         Ok(texture)
     }
 
+    pub fn new_as_hdr_color_target(gl: &Gl, width: usize, height: usize) -> Result<Texture2D, Error>
+    {
+        let id = generate(gl)?;
+        let texture = Texture2D { gl: gl.clone(), id, target: gl::consts::TEXTURE_2D, width, height };
+
+        bind(&texture.gl, &texture.id, texture.target);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MIN_FILTER, gl::consts::LINEAR as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAG_FILTER, gl::consts::LINEAR as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_S, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_T, gl::consts::CLAMP_TO_EDGE as i32);
+
+        gl.tex_storage_2d(texture.target,
+                        1,
+                        gl::consts::RGBA16F,
+                        width as u32,
+                        height as u32);
+
+        Ok(texture)
+    }
+
+    pub fn new_as_hdr_color_target_with_mip_maps(gl: &Gl, width: usize, height: usize, no_mip_levels: usize) -> Result<Texture2D, Error>
+    {
+        let id = generate(gl)?;
+        let texture = Texture2D { gl: gl.clone(), id, target: gl::consts::TEXTURE_2D, width, height };
+
+        bind(&texture.gl, &texture.id, texture.target);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MIN_FILTER, gl::consts::LINEAR_MIPMAP_LINEAR as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAG_FILTER, gl::consts::LINEAR as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_S, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_T, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAX_LEVEL, no_mip_levels as i32 - 1);
+
+        gl.tex_storage_2d(texture.target,
+                        no_mip_levels as u32,
+                        gl::consts::RGBA16F,
+                        width as u32,
+                        height as u32);
+
+        Ok(texture)
+    }
+
+    /// Must be cleared with `RenderTarget::clear_color_integer_and_depth`.
+    pub fn new_as_integer_color_target(gl: &Gl, width: usize, height: usize) -> Result<Texture2D, Error>
+    {
+        let id = generate(gl)?;
+        let texture = Texture2D { gl: gl.clone(), id, target: gl::consts::TEXTURE_2D, width, height };
+
+        bind(&texture.gl, &texture.id, texture.target);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MIN_FILTER, gl::consts::NEAREST as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAG_FILTER, gl::consts::NEAREST as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_S, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_T, gl::consts::CLAMP_TO_EDGE as i32);
+
+        gl.tex_storage_2d(texture.target,
+                        1,
+                        gl::consts::RGBA32UI,
+                        width as u32,
+                        height as u32);
+
+        Ok(texture)
+    }
+
     pub fn new_as_depth_target(gl: &Gl, width: usize, height: usize) -> Result<Texture2D, Error>
     {
         let id = generate(gl)?;
@@ -143,6 +229,42 @@ impl Texture2D
                                            &mut d);
     }
 
+    pub fn set_wrapping(&self, wrap_s: Wrapping, wrap_t: Wrapping)
+    {
+        bind(&self.gl, &self.id, self.target);
+        self.gl.tex_parameteri(self.target, gl::consts::TEXTURE_WRAP_S, wrapping_to_gl(wrap_s) as i32);
+        self.gl.tex_parameteri(self.target, gl::consts::TEXTURE_WRAP_T, wrapping_to_gl(wrap_t) as i32);
+    }
+
+    /// Mip maps are generated from the current content if a mip map interpolation is given.
+    pub fn set_interpolation(&self, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>)
+    {
+        bind(&self.gl, &self.id, self.target);
+        let min_filter = match (min_filter, mip_map_filter) {
+            (Interpolation::Nearest, None) => gl::consts::NEAREST,
+            (Interpolation::Linear, None) => gl::consts::LINEAR,
+            (Interpolation::Nearest, Some(Interpolation::Nearest)) => gl::consts::NEAREST_MIPMAP_NEAREST,
+            (Interpolation::Linear, Some(Interpolation::Nearest)) => gl::consts::LINEAR_MIPMAP_NEAREST,
+            (Interpolation::Nearest, Some(Interpolation::Linear)) => gl::consts::NEAREST_MIPMAP_LINEAR,
+            (Interpolation::Linear, Some(Interpolation::Linear)) => gl::consts::LINEAR_MIPMAP_LINEAR
+        };
+        let mag_filter = match mag_filter {
+            Interpolation::Nearest => gl::consts::NEAREST,
+            Interpolation::Linear => gl::consts::LINEAR
+        };
+        self.gl.tex_parameteri(self.target, gl::consts::TEXTURE_MIN_FILTER, min_filter as i32);
+        self.gl.tex_parameteri(self.target, gl::consts::TEXTURE_MAG_FILTER, mag_filter as i32);
+        if mip_map_filter.is_some() {
+            self.gl.generate_mipmap(self.target);
+        }
+    }
+
+    pub fn generate_mip_maps(&self)
+    {
+        bind(&self.gl, &self.id, self.target);
+        self.gl.generate_mipmap(self.target);
+    }
+
     pub fn bind_to_framebuffer(&self, channel: usize)
     {
         self.gl.framebuffer_texture_2d(gl::consts::FRAMEBUFFER,
@@ -175,7 +297,9 @@ impl Drop for Texture2D
 pub struct Texture3D {
     gl: Gl,
     id: gl::Texture,
-    target: u32
+    target: u32,
+    pub width: usize,
+    pub height: usize
 }
 
 // TEXTURE 3D
@@ -184,7 +308,7 @@ impl Texture3D
     pub fn new(gl: &Gl) -> Result<Texture3D, Error>
     {
         let id = generate(gl)?;
-        let texture = Texture3D { gl: gl.clone(), id, target: gl::consts::TEXTURE_CUBE_MAP };
+        let texture = Texture3D { gl: gl.clone(), id, target: gl::consts::TEXTURE_CUBE_MAP, width: 0, height: 0 };
 
         bind(&texture.gl, &texture.id, texture.target);
         gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MIN_FILTER, gl::consts::LINEAR as i32);
@@ -196,6 +320,65 @@ impl Texture3D
         Ok(texture)
     }
 
+    pub fn new_as_depth_target(gl: &Gl, size: usize) -> Result<Texture3D, Error>
+    {
+        let id = generate(gl)?;
+        let texture = Texture3D { gl: gl.clone(), id, target: gl::consts::TEXTURE_CUBE_MAP, width: size, height: size };
+
+        bind(&texture.gl, &texture.id, texture.target);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MIN_FILTER, gl::consts::NEAREST as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAG_FILTER, gl::consts::NEAREST as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_S, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_T, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_R, gl::consts::CLAMP_TO_EDGE as i32);
+
+        gl.tex_storage_2d(texture.target,
+                        1,
+                        gl::consts::DEPTH_COMPONENT32F,
+                        size as u32,
+                        size as u32);
+
+        Ok(texture)
+    }
+
+    pub fn new_as_hdr_color_target(gl: &Gl, size: usize, no_mip_levels: usize) -> Result<Texture3D, Error>
+    {
+        let id = generate(gl)?;
+        let texture = Texture3D { gl: gl.clone(), id, target: gl::consts::TEXTURE_CUBE_MAP, width: size, height: size };
+
+        bind(&texture.gl, &texture.id, texture.target);
+        let min_filter = if no_mip_levels > 1 {gl::consts::LINEAR_MIPMAP_LINEAR} else {gl::consts::LINEAR};
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MIN_FILTER, min_filter as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAG_FILTER, gl::consts::LINEAR as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_S, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_T, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_R, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAX_LEVEL, no_mip_levels as i32 - 1);
+
+        gl.tex_storage_2d(texture.target,
+                        no_mip_levels as u32,
+                        gl::consts::RGBA16F,
+                        size as u32,
+                        size as u32);
+
+        Ok(texture)
+    }
+
+    #[cfg(feature = "image-io")]
+    pub fn new_from_hdr_bytes(gl: &Gl, right_bytes: &[u8], left_bytes: &[u8], top_bytes: &[u8], bottom_bytes: &[u8], front_bytes: &[u8], back_bytes: &[u8]) -> Result<Texture3D, Error>
+    {
+        let mut texture = Texture3D::new(gl)?;
+        bind(&texture.gl, &texture.id, texture.target);
+        for (i, bytes) in [right_bytes, left_bytes, top_bytes, bottom_bytes, front_bytes, back_bytes].iter().enumerate() {
+            let (width, height, mut data) = decode_hdr(bytes)?;
+            texture.width = width;
+            texture.height = height;
+            gl.tex_image_2d_with_f32_data(gl::consts::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32, 0, gl::consts::RGB16F,
+                                          width as u32, height as u32, 0, gl::consts::RGB, gl::consts::FLOAT, &mut data);
+        }
+        Ok(texture)
+    }
+
     #[cfg(feature = "image-io")]
     pub fn new_from_bytes(gl: &Gl, back_bytes: &[u8], front_bytes: &[u8], top_bytes: &[u8], left_bytes: &[u8], right_bytes: &[u8]) -> Result<Texture3D, Error>
     {
@@ -232,6 +415,8 @@ impl Texture3D
 
     pub fn fill_with_u8(&mut self, width: usize, height: usize, data: [&mut [u8]; 6])
     {
+        self.width = width;
+        self.height = height;
         bind(&self.gl, &self.id, self.target);
         for i in 0..6 {
             let format = gl::consts::RGB;
@@ -247,6 +432,24 @@ impl Texture3D
                                               data[i]);
         }
     }
+
+    pub fn bind_to_framebuffer(&self, face: usize, mip_level: usize)
+    {
+        self.gl.framebuffer_texture_2d(gl::consts::DRAW_FRAMEBUFFER,
+                       gl::consts::COLOR_ATTACHMENT0, gl::consts::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32, &self.id, mip_level as u32);
+    }
+
+    pub fn bind_to_depth_target(&self, face: usize)
+    {
+        self.gl.framebuffer_texture_2d(gl::consts::DRAW_FRAMEBUFFER,
+                       gl::consts::DEPTH_ATTACHMENT, gl::consts::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32, &self.id, 0);
+    }
+
+    pub fn generate_mip_maps(&self)
+    {
+        bind(&self.gl, &self.id, self.target);
+        self.gl.generate_mipmap(self.target);
+    }
 }
 
 impl Texture for Texture3D
@@ -300,6 +503,28 @@ impl Texture2DArray
         Ok(texture)
     }
 
+    pub fn new_as_hdr_color_targets(gl: &Gl, width: usize, height: usize, depth: usize) -> Result<Texture2DArray, Error>
+    {
+        let id = generate(gl)?;
+        let texture = Texture2DArray { gl: gl.clone(), id, target: gl::consts::TEXTURE_2D_ARRAY, attachment: gl::consts::COLOR_ATTACHMENT0,
+            width, height, depth};
+
+        bind(&texture.gl, &texture.id, texture.target);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MIN_FILTER, gl::consts::NEAREST as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_MAG_FILTER, gl::consts::NEAREST as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_S, gl::consts::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(texture.target, gl::consts::TEXTURE_WRAP_T, gl::consts::CLAMP_TO_EDGE as i32);
+
+        gl.tex_storage_3d(texture.target,
+                        1,
+                        gl::consts::RGBA16F,
+                        width as u32,
+                        height as u32,
+                        depth as u32);
+
+        Ok(texture)
+    }
+
     pub fn new_as_depth_targets(gl: &Gl, width: usize, height: usize, depth: usize) -> Result<Texture2DArray, Error>
     {
         let id = generate(gl)?;
@@ -374,6 +599,28 @@ fn drop(gl: &Gl, id: &gl::Texture)
     gl.delete_texture(id);
 }
 
+fn wrapping_to_gl(wrapping: Wrapping) -> u32
+{
+    match wrapping {
+        Wrapping::Repeat => gl::consts::REPEAT,
+        Wrapping::MirroredRepeat => gl::consts::MIRRORED_REPEAT,
+        Wrapping::ClampToEdge => gl::consts::CLAMP_TO_EDGE
+    }
+}
+
+// Decodes a Radiance HDR image sequentially (the decoder in the image crate otherwise uses threads, which are not available on web)
+#[cfg(feature = "image-io")]
+fn decode_hdr(bytes: &[u8]) -> Result<(usize, usize, Vec<f32>), Error>
+{
+    let decoder = image::hdr::HDRDecoder::new(bytes)?;
+    let metadata = decoder.metadata();
+    let mut data = Vec::with_capacity(metadata.width as usize * metadata.height as usize * 3);
+    for pixel in decoder.into_iter() {
+        data.extend_from_slice(&pixel?.to_hdr().0);
+    }
+    Ok((metadata.width as usize, metadata.height as usize, data))
+}
+
 fn extend_data<T>(data: &[T], desired_length: usize, value: T) -> Vec<T> where T: std::clone::Clone
 {
     let mut result = Vec::new();
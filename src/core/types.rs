@@ -1,5 +1,5 @@
 
-use cgmath::{Vector2, Vector3, Vector4, Matrix2, Matrix3, Matrix4, Point3, Deg, Rad};
+use cgmath::{Vector2, Vector3, Vector4, Matrix2, Matrix3, Matrix4, Point3, Quaternion, Deg, Rad};
 pub use cgmath::prelude::*;
 pub use cgmath::perspective;
 pub use cgmath::ortho;
@@ -11,6 +11,7 @@ pub type Mat2 = Matrix2<f32>;
 pub type Mat3 = Matrix3<f32>;
 pub type Mat4 = Matrix4<f32>;
 pub type Point = Point3<f32>;
+pub type Quat = Quaternion<f32>;
 pub type Degrees = Deg<f32>;
 pub type Radians = Rad<f32>;
 
@@ -0,0 +1,70 @@
+use crate::*;
+use std::cell::Cell;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CullingStats {
+    pub tested: usize,
+    pub culled: usize
+}
+
+impl CullingStats {
+
+    pub fn visible(&self) -> usize
+    {
+        self.tested - self.culled
+    }
+}
+
+pub struct FrustumCulling {
+    stats: Cell<CullingStats>,
+    pub enabled: bool
+}
+
+impl FrustumCulling {
+
+    pub fn new() -> FrustumCulling
+    {
+        FrustumCulling {stats: Cell::new(CullingStats::default()), enabled: true}
+    }
+
+    pub fn is_visible(&self, aabb: &AxisAllignedBoundingBox, transformation: &Mat4, camera: &Camera) -> bool
+    {
+        let visible = if self.enabled {
+            let world_aabb = aabb.transform(transformation);
+            camera.in_frustrum(&world_aabb.min, &world_aabb.max)
+        } else { true };
+        let mut stats = self.stats.get();
+        stats.tested += 1;
+        if !visible {
+            stats.culled += 1;
+        }
+        self.stats.set(stats);
+        visible
+    }
+
+    pub fn render_mesh(&self, mesh: &Mesh, transformation: &Mat4, camera: &Camera) -> bool
+    {
+        let visible = self.is_visible(mesh.axis_aligned_bounding_box(), transformation, camera);
+        if visible {
+            mesh.render(transformation, camera);
+        }
+        visible
+    }
+
+    pub fn stats(&self) -> CullingStats
+    {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&self)
+    {
+        self.stats.set(CullingStats::default());
+    }
+}
+
+impl Default for FrustumCulling {
+    fn default() -> Self
+    {
+        FrustumCulling::new()
+    }
+}
@@ -1,19 +1,85 @@
 pub mod debug;
 pub mod fog;
 pub mod copy;
+pub mod ssao;
+pub mod tone_mapping;
+pub mod bloom;
+pub mod post_process_chain;
+pub mod fxaa;
+pub mod taa;
+pub mod custom;
 
 pub use crate::effects::debug::*;
 pub use crate::effects::fog::*;
 pub use crate::effects::copy::*;
+pub use crate::effects::ssao::*;
+pub use crate::effects::tone_mapping::*;
+pub use crate::effects::bloom::*;
+pub use crate::effects::post_process_chain::*;
+pub use crate::effects::fxaa::*;
+pub use crate::effects::taa::*;
+pub use crate::effects::custom::*;
 
 use crate::*;
 #[derive(Debug)]
 pub enum Error {
     Program(program::Error),
+    Rendertarget(rendertarget::Error),
+    Texture(texture::Error),
+    Buffer(buffer::Error),
+    InvalidSize {message: String}
 }
 
 impl From<program::Error> for Error {
     fn from(other: program::Error) -> Self {
         Error::Program(other)
     }
-}
\ No newline at end of file
+}
+
+impl From<rendertarget::Error> for Error {
+    fn from(other: rendertarget::Error) -> Self {
+        Error::Rendertarget(other)
+    }
+}
+
+impl From<texture::Error> for Error {
+    fn from(other: texture::Error) -> Self {
+        Error::Texture(other)
+    }
+}
+
+impl From<buffer::Error> for Error {
+    fn from(other: buffer::Error) -> Self {
+        Error::Buffer(other)
+    }
+}
+
+pub(crate) struct FullScreen {
+    buffer: VertexBuffer
+}
+
+impl FullScreen {
+
+    pub(crate) fn new(gl: &Gl) -> Result<FullScreen, Error>
+    {
+        let positions = vec![
+            -3.0, -1.0, 0.0,
+            3.0, -1.0, 0.0,
+            0.0, 2.0, 0.0
+        ];
+        let uvs = vec![
+            -1.0, 0.0,
+            2.0, 0.0,
+            0.5, 1.5
+        ];
+        Ok(FullScreen {buffer: VertexBuffer::new_with_two_static_attributes(gl, &positions, &uvs)?})
+    }
+
+    pub(crate) fn draw(&self, program: &program::Program) -> Result<(), Error>
+    {
+        program.use_attribute_vec3_float(&self.buffer, "position", 0)?;
+        program.use_attribute_vec2_float(&self.buffer, "uv_coordinate", 1)?;
+        program.draw_arrays(3);
+        Ok(())
+    }
+}
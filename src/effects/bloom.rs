@@ -0,0 +1,106 @@
+use crate::*;
+
+pub struct BloomEffect {
+    gl: Gl,
+    downsample_program: program::Program,
+    upsample_program: program::Program,
+    rendertarget: rendertarget::RenderTarget,
+    levels: Vec<Texture2D>,
+    full_screen: FullScreen,
+    pub intensity: f32,
+    pub threshold: f32,
+    pub radius: f32
+}
+
+impl BloomEffect {
+
+    pub fn new(gl: &Gl, width: usize, height: usize, no_levels: usize) -> Result<BloomEffect, effects::Error>
+    {
+        let downsample_program = program::Program::from_source(gl,
+                                                               include_str!("shaders/effect.vert"),
+                                                               include_str!("shaders/bloom_downsample.frag"))?;
+        let upsample_program = program::Program::from_source(gl,
+                                                             include_str!("shaders/effect.vert"),
+                                                             include_str!("shaders/bloom_upsample.frag"))?;
+
+        let full_screen = FullScreen::new(gl)?;
+
+        let rendertarget = rendertarget::RenderTarget::new(gl, 1)?;
+        let levels = create_levels(gl, width, height, no_levels)?;
+
+        Ok(BloomEffect {gl: gl.clone(), downsample_program, upsample_program, rendertarget, levels, full_screen,
+            intensity: 0.5, threshold: 1.0, radius: 1.0})
+    }
+
+    /// Changes the bound render target, so it must be called before `apply`.
+    pub fn blur(&self, texture: &Texture2D) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        let program = &self.downsample_program;
+        for (i, level) in self.levels.iter().enumerate() {
+            self.rendertarget.write_to_color(level)?;
+            program.use_texture(if i == 0 {texture} else {&self.levels[i - 1]}, "colorMap")?;
+            program.add_uniform_int("useThreshold", &((i == 0) as i32))?;
+            program.add_uniform_float("threshold", &self.threshold)?;
+            self.full_screen.draw(program)?;
+        }
+
+        state::blend(&self.gl, state::BlendType::OneOne);
+        for i in (1..self.levels.len()).rev() {
+            self.rendertarget.write_to_color(&self.levels[i - 1])?;
+            self.upsample(&self.levels[i], 1.0)?;
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::OneOne);
+
+        // The levels are added together, so the sum is scaled to keep the intensity independent of the number of levels
+        self.upsample(&self.levels[0], self.intensity / self.levels.len() as f32)
+    }
+
+    fn upsample(&self, texture: &Texture2D, intensity: f32) -> Result<(), effects::Error>
+    {
+        let program = &self.upsample_program;
+        program.use_texture(texture, "colorMap")?;
+        program.add_uniform_float("radius", &self.radius)?;
+        program.add_uniform_float("intensity", &intensity)?;
+        self.full_screen.draw(program)?;
+        Ok(())
+    }
+
+}
+
+impl PostProcessEffect for BloomEffect {
+
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>
+    {
+        self.blur(input.color_texture)?;
+        input.copy_to_output()?;
+        self.apply()
+    }
+
+    fn resize(&mut self, width: usize, height: usize) -> Result<(), effects::Error>
+    {
+        self.levels = create_levels(&self.gl, width, height, self.levels.len())?;
+        Ok(())
+    }
+}
+
+fn create_levels(gl: &Gl, width: usize, height: usize, no_levels: usize) -> Result<Vec<Texture2D>, effects::Error>
+{
+    let mut levels = Vec::new();
+    for level in 1..no_levels.max(1) + 1 {
+        levels.push(Texture2D::new_as_hdr_color_target(gl, (width >> level).max(1), (height >> level).max(1))?);
+    }
+    Ok(levels)
+}
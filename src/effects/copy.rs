@@ -7,7 +7,7 @@ enum Type {POSITION = 0, NORMAL = 1, COLOR = 2, DEPTH = 3, NONE = 4}
 pub struct CopyEffect {
     gl: Gl,
     program: program::Program,
-    buffer: VertexBuffer
+    full_screen: FullScreen
 }
 
 impl CopyEffect {
@@ -18,19 +18,9 @@ impl CopyEffect {
                                                     include_str!("shaders/effect.vert"),
                                                     include_str!("shaders/copy.frag"))?;
 
-        let positions = vec![
-            -3.0, -1.0, 0.0,
-            3.0, -1.0, 0.0,
-            0.0, 2.0, 0.0
-        ];
-        let uvs = vec![
-            -1.0, 0.0,
-            2.0, 0.0,
-            0.5, 1.5
-        ];
-        let buffer = VertexBuffer::new_with_two_static_attributes(&gl, &positions, &uvs).unwrap();
-
-        Ok(CopyEffect {gl: gl.clone(), program, buffer})
+        let full_screen = FullScreen::new(gl)?;
+
+        Ok(CopyEffect {gl: gl.clone(), program, full_screen})
     }
 
     pub fn apply(&self, color_texture: &dyn Texture, depth_texture: &dyn Texture) -> Result<(), effects::Error>
@@ -43,9 +33,7 @@ impl CopyEffect {
         self.program.use_texture(color_texture, "colorMap")?;
         self.program.use_texture(depth_texture, "depthMap")?;
 
-        self.program.use_attribute_vec3_float(&self.buffer, "position", 0).unwrap();
-        self.program.use_attribute_vec2_float(&self.buffer, "uv_coordinate", 1).unwrap();
-        self.program.draw_arrays(3);
+        self.full_screen.draw(&self.program)?;
         Ok(())
     }
 
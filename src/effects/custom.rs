@@ -0,0 +1,160 @@
+use crate::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformValue {
+    Int(i32),
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Mat3(Mat3),
+    Mat4(Mat4)
+}
+
+impl From<i32> for UniformValue {
+    fn from(other: i32) -> Self {
+        UniformValue::Int(other)
+    }
+}
+
+impl From<f32> for UniformValue {
+    fn from(other: f32) -> Self {
+        UniformValue::Float(other)
+    }
+}
+
+impl From<Vec2> for UniformValue {
+    fn from(other: Vec2) -> Self {
+        UniformValue::Vec2(other)
+    }
+}
+
+impl From<Vec3> for UniformValue {
+    fn from(other: Vec3) -> Self {
+        UniformValue::Vec3(other)
+    }
+}
+
+impl From<Vec4> for UniformValue {
+    fn from(other: Vec4) -> Self {
+        UniformValue::Vec4(other)
+    }
+}
+
+impl From<Mat3> for UniformValue {
+    fn from(other: Mat3) -> Self {
+        UniformValue::Mat3(other)
+    }
+}
+
+impl From<Mat4> for UniformValue {
+    fn from(other: Mat4) -> Self {
+        UniformValue::Mat4(other)
+    }
+}
+
+///
+/// The source is appended to declarations of these inputs, so it only has to contain the `main` function and the extra uniforms:
+/// - `in vec2 uv`: the screen position from 0 to 1,
+/// - `out vec4 color`: the output color,
+/// - `uniform sampler2D colorMap`: the image the effect is applied to,
+/// - `uniform sampler2DArray gbuffer` and `uniform sampler2DArray depthMap`: the geometry pass of a `DeferredPipeline`,
+/// - `uniform mat4 viewProjectionInverse` and `uniform vec3 eyePosition`: the camera,
+/// - `uniform float time`: the time in seconds,
+/// - `uniform vec2 resolution`: the size of the color texture in pixels.
+/// Line numbers in compile errors are offset by the lines of these declarations.
+///
+pub struct CustomEffect {
+    gl: Gl,
+    program: program::Program,
+    uniforms: HashMap<String, UniformValue>,
+    full_screen: FullScreen,
+    pub blend: state::BlendType
+}
+
+impl CustomEffect {
+
+    pub fn new(gl: &Gl, fragment_shader_source: &str) -> Result<CustomEffect, effects::Error>
+    {
+        let program = program::Program::from_source(gl,
+                                                    include_str!("shaders/effect.vert"),
+                                                    &format!("{}{}", include_str!("shaders/custom_effect.frag"), fragment_shader_source))?;
+        let full_screen = FullScreen::new(gl)?;
+        Ok(CustomEffect {gl: gl.clone(), program, uniforms: HashMap::new(), full_screen, blend: state::BlendType::None})
+    }
+
+    pub fn set_uniform<T: Into<UniformValue>>(&mut self, name: &str, value: T) -> Result<(), effects::Error>
+    {
+        if !self.program.has_uniform(name) {
+            Err(program::Error::FailedToFindUniform {message: format!("The uniform {} is not used by the custom effect", name)})?;
+        }
+        self.uniforms.insert(name.to_owned(), value.into());
+        Ok(())
+    }
+
+    pub fn uniform(&self, name: &str) -> Option<&UniformValue>
+    {
+        self.uniforms.get(name)
+    }
+
+    pub fn apply(&self, camera: &Camera, color_texture: &Texture2D, geometry_texture: &dyn Texture, depth_texture: &dyn Texture, time: f32) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, self.blend);
+
+        let program = &self.program;
+        if program.has_uniform("colorMap") {
+            program.use_texture(color_texture, "colorMap")?;
+        }
+        if program.has_uniform("gbuffer") {
+            program.use_texture(geometry_texture, "gbuffer")?;
+        }
+        if program.has_uniform("depthMap") {
+            program.use_texture(depth_texture, "depthMap")?;
+        }
+        if program.has_uniform("viewProjectionInverse") {
+            program.add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
+        }
+        if program.has_uniform("eyePosition") {
+            program.add_uniform_vec3("eyePosition", camera.position())?;
+        }
+        if program.has_uniform("time") {
+            program.add_uniform_float("time", &(0.001 * time))?;
+        }
+        if program.has_uniform("resolution") {
+            program.add_uniform_vec2("resolution", &vec2(color_texture.width as f32, color_texture.height as f32))?;
+        }
+
+        for (name, value) in self.uniforms.iter() {
+            match value {
+                UniformValue::Int(value) => program.add_uniform_int(name, value)?,
+                UniformValue::Float(value) => program.add_uniform_float(name, value)?,
+                UniformValue::Vec2(value) => program.add_uniform_vec2(name, value)?,
+                UniformValue::Vec3(value) => program.add_uniform_vec3(name, value)?,
+                UniformValue::Vec4(value) => program.add_uniform_vec4(name, value)?,
+                UniformValue::Mat3(value) => program.add_uniform_mat3(name, value)?,
+                UniformValue::Mat4(value) => program.add_uniform_mat4(name, value)?
+            }
+        }
+
+        self.full_screen.draw(&self.program)?;
+        Ok(())
+    }
+
+}
+
+impl PostProcessEffect for CustomEffect {
+
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>
+    {
+        if self.blend == state::BlendType::None {
+            input.bind_output()?;
+        } else {
+            input.copy_to_output()?;
+        }
+        self.apply(input.camera, input.color_texture, input.geometry_texture, input.depth_texture, input.time)
+    }
+}
@@ -1,14 +1,15 @@
 use crate::*;
 use num_derive::FromPrimitive;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive)]
-enum Type {POSITION = 0, NORMAL = 1, COLOR = 2, DEPTH = 3, DIFFUSE = 4, SPECULAR = 5, POWER = 6, NONE = 7}
+enum Type {POSITION = 0, NORMAL = 1, COLOR = 2, DEPTH = 3, DIFFUSE = 4, SPECULAR = 5, POWER = 6, METALLIC = 7, ROUGHNESS = 8, OCCLUSION = 9, EMISSIVE = 10, SSAO = 11, NONE = 12}
 
 pub struct DebugEffect {
     gl: Gl,
     program: program::Program,
     debug_type: Type,
-    buffer: VertexBuffer
+    full_screen: FullScreen
 }
 
 impl DebugEffect {
@@ -19,19 +20,9 @@ impl DebugEffect {
                                                     include_str!("shaders/effect.vert"),
                                                     include_str!("shaders/debug.frag"))?;
 
-        let positions = vec![
-            -3.0, -1.0, 0.0,
-            3.0, -1.0, 0.0,
-            0.0, 2.0, 0.0
-        ];
-        let uvs = vec![
-            -1.0, 0.0,
-            2.0, 0.0,
-            0.5, 1.5
-        ];
-        let buffer = VertexBuffer::new_with_two_static_attributes(&gl, &positions, &uvs).unwrap();
-
-        Ok(DebugEffect {gl: gl.clone(), program, debug_type: Type::NONE, buffer})
+        let full_screen = FullScreen::new(gl)?;
+
+        Ok(DebugEffect {gl: gl.clone(), program, debug_type: Type::NONE, full_screen})
     }
 
     pub fn change_type(&mut self)
@@ -41,6 +32,12 @@ impl DebugEffect {
     }
 
     pub fn apply(&self, camera: &Camera, geometry_texture: &dyn Texture, depth_texture: &dyn Texture) -> Result<(), effects::Error>
+    {
+        self.apply_with_ambient_occlusion(camera, geometry_texture, depth_texture, None)
+    }
+
+    pub fn apply_with_ambient_occlusion(&self, camera: &Camera, geometry_texture: &dyn Texture, depth_texture: &dyn Texture,
+                                        ambient_occlusion_texture: Option<&dyn Texture>) -> Result<(), effects::Error>
     {
         if self.debug_type != Type::NONE {
             state::depth_write(&self.gl,false);
@@ -56,13 +53,26 @@ impl DebugEffect {
             depth_texture.bind(1);
             self.program.add_uniform_int("depthMap", &1)?;
 
+            if let Some(texture) = ambient_occlusion_texture {
+                texture.bind(2);
+            }
+            self.program.add_uniform_int("ambientOcclusionMap", &2)?;
+            self.program.add_uniform_int("hasAmbientOcclusion", &(ambient_occlusion_texture.is_some() as i32))?;
+
             self.program.add_uniform_int("type", &(self.debug_type as i32))?;
 
-            self.program.use_attribute_vec3_float(&self.buffer, "position", 0).unwrap();
-            self.program.use_attribute_vec2_float(&self.buffer, "uv_coordinate", 1).unwrap();
-            self.program.draw_arrays(3);
+            self.full_screen.draw(&self.program)?;
         }
         Ok(())
     }
 
-}
\ No newline at end of file
+}
+
+impl PostProcessEffect for DebugEffect {
+
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>
+    {
+        input.copy_to_output()?;
+        self.apply(input.camera, input.geometry_texture, input.depth_texture)
+    }
+}
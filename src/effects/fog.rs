@@ -6,7 +6,7 @@ pub struct FogEffect {
     pub color: Vec3,
     pub density: f32,
     pub animation: f32,
-    buffer: VertexBuffer
+    full_screen: FullScreen
 }
 
 impl FogEffect {
@@ -17,18 +17,8 @@ impl FogEffect {
                                                     include_str!("shaders/effect.vert"),
                                                     include_str!("shaders/fog.frag"))?;
 
-        let positions = vec![
-            -3.0, -1.0, 0.0,
-            3.0, -1.0, 0.0,
-            0.0, 2.0, 0.0
-        ];
-        let uvs = vec![
-            -1.0, 0.0,
-            2.0, 0.0,
-            0.5, 1.5
-        ];
-        let buffer = VertexBuffer::new_with_two_static_attributes(&gl, &positions, &uvs).unwrap();
-        Ok(FogEffect {gl: gl.clone(), program, color: vec3(0.8, 0.8, 0.8), density: 0.2, animation: 0.1, buffer})
+        let full_screen = FullScreen::new(gl)?;
+        Ok(FogEffect {gl: gl.clone(), program, color: vec3(0.8, 0.8, 0.8), density: 0.2, animation: 0.1, full_screen})
     }
 
     pub fn apply(&self, time: f32, camera: &camera::Camera, depth_texture: &dyn Texture) -> Result<(), effects::Error>
@@ -48,10 +38,17 @@ impl FogEffect {
         self.program.add_uniform_float("time", &(0.001 * time))?;
         self.program.add_uniform_vec3("eyePosition", camera.position())?;
 
-        self.program.use_attribute_vec3_float(&self.buffer, "position", 0).unwrap();
-        self.program.use_attribute_vec2_float(&self.buffer, "uv_coordinate", 1).unwrap();
-        self.program.draw_arrays(3);
+        self.full_screen.draw(&self.program)?;
         Ok(())
     }
 
-}
\ No newline at end of file
+}
+
+impl PostProcessEffect for FogEffect {
+
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>
+    {
+        input.copy_to_output()?;
+        self.apply(input.time, input.camera, input.depth_texture)
+    }
+}
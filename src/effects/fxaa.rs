@@ -0,0 +1,41 @@
+use crate::*;
+
+pub struct FxaaEffect {
+    gl: Gl,
+    program: program::Program,
+    full_screen: FullScreen
+}
+
+impl FxaaEffect {
+
+    pub fn new(gl: &Gl) -> Result<FxaaEffect, effects::Error>
+    {
+        let program = program::Program::from_source(gl,
+                                                    include_str!("shaders/effect.vert"),
+                                                    include_str!("shaders/fxaa.frag"))?;
+        let full_screen = FullScreen::new(gl)?;
+        Ok(FxaaEffect {gl: gl.clone(), program, full_screen})
+    }
+
+    pub fn apply(&self, texture: &Texture2D) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        self.program.use_texture(texture, "colorMap")?;
+        self.full_screen.draw(&self.program)?;
+        Ok(())
+    }
+
+}
+
+impl PostProcessEffect for FxaaEffect {
+
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>
+    {
+        input.bind_output()?;
+        self.apply(input.color_texture)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::*;
+
+pub trait PostProcessEffect {
+
+    /// The whole output must be written, for example with `PostProcessInput::copy_to_output` before blending on top of it.
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>;
+
+    fn resize(&mut self, _width: usize, _height: usize) -> Result<(), effects::Error>
+    {
+        Ok(())
+    }
+}
+
+pub struct PostProcessInput<'a> {
+    pub camera: &'a Camera,
+    pub color_texture: &'a Texture2D,
+    pub geometry_texture: &'a Texture2DArray,
+    pub depth_texture: &'a Texture2DArray,
+    pub elapsed_time: f32,
+    pub time: f32,
+    pub width: usize,
+    pub height: usize,
+    chain: &'a PostProcessChain,
+    output: Option<&'a Texture2D>,
+    bind_target: &'a dyn Fn()
+}
+
+impl<'a> PostProcessInput<'a> {
+
+    pub fn bind_output(&self) -> Result<(), effects::Error>
+    {
+        match self.output {
+            Some(texture) => self.chain.rendertarget.write_to_color(texture)?,
+            None => (self.bind_target)()
+        }
+        Ok(())
+    }
+
+    pub fn copy_to_output(&self) -> Result<(), effects::Error>
+    {
+        self.bind_output()?;
+        self.chain.copy(self.color_texture)
+    }
+}
+
+/// The bind function is called whenever the target must be bound, since effects with several passes bind other targets in between.
+pub struct PostProcessTarget<'a> {
+    pub width: usize,
+    pub height: usize,
+    pub elapsed_time: f64,
+    pub bind: &'a dyn Fn()
+}
+
+pub struct PostProcessChain {
+    gl: Gl,
+    copy_program: program::Program,
+    full_screen: FullScreen,
+    rendertarget: rendertarget::RenderTarget,
+    textures: [Texture2D; 2],
+    width: usize,
+    height: usize,
+    time: f64
+}
+
+impl PostProcessChain {
+
+    pub fn new(gl: &Gl, width: usize, height: usize) -> Result<PostProcessChain, effects::Error>
+    {
+        let copy_program = program::Program::from_source(gl,
+                                                         include_str!("shaders/effect.vert"),
+                                                         include_str!("shaders/copy_color.frag"))?;
+        let full_screen = FullScreen::new(gl)?;
+        let rendertarget = rendertarget::RenderTarget::new(gl, 1)?;
+        let textures = [Texture2D::new_as_hdr_color_target(gl, width, height)?, Texture2D::new_as_hdr_color_target(gl, width, height)?];
+        Ok(PostProcessChain {gl: gl.clone(), copy_program, full_screen, rendertarget, textures, width, height, time: 0.0})
+    }
+
+    /// The effects must be created with the size of the chain, and the pipeline must have the screen size.
+    #[cfg(any(feature = "glutin-window", feature = "canvas", feature = "headless"))]
+    pub fn apply(&mut self, frame_input: &FrameInput, camera: &Camera, pipeline: &DeferredPipeline,
+                 effects: &mut [&mut dyn PostProcessEffect]) -> Result<(), effects::Error>
+    {
+        let (gl, width, height) = (self.gl.clone(), frame_input.screen_width, frame_input.screen_height);
+        let target = PostProcessTarget {width, height, elapsed_time: frame_input.elapsed_time, bind: &|| ScreenRendertarget::write(&gl, width, height)};
+        self.apply_render_to_rendertarget(&target, camera, pipeline, effects)
+    }
+
+    pub fn apply_render_to_rendertarget(&mut self, target: &PostProcessTarget, camera: &Camera, pipeline: &DeferredPipeline,
+                                        effects: &mut [&mut dyn PostProcessEffect]) -> Result<(), effects::Error>
+    {
+        let hdr_texture = pipeline.hdr_texture();
+        if (hdr_texture.width, hdr_texture.height) != (target.width, target.height) {
+            Err(effects::Error::InvalidSize {message: format!("The pipeline has the size {}x{} instead of the target size {}x{}",
+                                                              hdr_texture.width, hdr_texture.height, target.width, target.height)})?;
+        }
+        if target.width != self.width || target.height != self.height {
+            self.resize(target.width, target.height)?;
+            for effect in effects.iter_mut() {
+                effect.resize(self.width, self.height)?;
+            }
+        }
+        self.time += target.elapsed_time;
+
+        if effects.is_empty() {
+            (target.bind)();
+            return self.copy(hdr_texture);
+        }
+        let no_effects = effects.len();
+        for (i, effect) in effects.iter_mut().enumerate() {
+            let input = PostProcessInput {
+                camera,
+                color_texture: if i == 0 { hdr_texture } else { &self.textures[(i - 1) % 2] },
+                geometry_texture: pipeline.geometry_pass_texture(),
+                depth_texture: pipeline.geometry_pass_depth_texture(),
+                elapsed_time: target.elapsed_time as f32,
+                time: self.time as f32,
+                width: self.width,
+                height: self.height,
+                chain: self,
+                output: if i + 1 == no_effects { None } else { Some(&self.textures[i % 2]) },
+                bind_target: target.bind
+            };
+            input.bind_output()?;
+            effect.process(&input)?;
+        }
+        Ok(())
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), effects::Error>
+    {
+        self.textures = [Texture2D::new_as_hdr_color_target(&self.gl, width, height)?, Texture2D::new_as_hdr_color_target(&self.gl, width, height)?];
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    fn copy(&self, texture: &Texture2D) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        self.copy_program.use_texture(texture, "colorMap")?;
+        self.full_screen.draw(&self.copy_program)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+uniform sampler2D colorMap;
+
+uniform bool useThreshold;
+uniform float threshold;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    vec2 texel_size = 1.0 / vec2(textureSize(colorMap, 0));
+    vec3 c = 0.25 * (texture(colorMap, uv + vec2(-1.0, -1.0) * texel_size).rgb + texture(colorMap, uv + vec2(1.0, -1.0) * texel_size).rgb +
+                     texture(colorMap, uv + vec2(-1.0, 1.0) * texel_size).rgb + texture(colorMap, uv + vec2(1.0, 1.0) * texel_size).rgb);
+
+    if(useThreshold)
+    {
+        float brightness = max(c.r, max(c.g, c.b));
+        float knee = 0.5 * threshold;
+        float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+        soft = soft * soft / (4.0 * knee + 0.00001);
+        c *= max(soft, brightness - threshold) / max(brightness, 0.00001);
+    }
+    color = vec4(c, 1.0);
+}
@@ -0,0 +1,19 @@
+uniform sampler2D colorMap;
+
+uniform float radius;
+uniform float intensity;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    vec2 d = radius / vec2(textureSize(colorMap, 0));
+    vec3 c = 4.0 * texture(colorMap, uv).rgb;
+    c += 2.0 * (texture(colorMap, uv + vec2(-d.x, 0.0)).rgb + texture(colorMap, uv + vec2(d.x, 0.0)).rgb +
+                texture(colorMap, uv + vec2(0.0, -d.y)).rgb + texture(colorMap, uv + vec2(0.0, d.y)).rgb);
+    c += texture(colorMap, uv + vec2(-d.x, -d.y)).rgb + texture(colorMap, uv + vec2(d.x, -d.y)).rgb +
+         texture(colorMap, uv + vec2(-d.x, d.y)).rgb + texture(colorMap, uv + vec2(d.x, d.y)).rgb;
+    color = vec4(intensity * c / 16.0, 1.0);
+}
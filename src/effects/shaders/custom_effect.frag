@@ -0,0 +1,13 @@
+uniform sampler2D colorMap;
+uniform sampler2DArray gbuffer;
+uniform sampler2DArray depthMap;
+
+uniform mat4 viewProjectionInverse;
+uniform vec3 eyePosition;
+uniform float time;
+uniform vec2 resolution;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
@@ -1,6 +1,8 @@
 
 uniform sampler2DArray gbuffer;
 uniform sampler2DArray depthMap;
+uniform sampler2D ambientOcclusionMap;
+uniform bool hasAmbientOcclusion;
 
 uniform int type;
 
@@ -67,6 +69,30 @@ void main()
         val /= 32.0;
         color = vec4(val, val, val, 1.);
     }
+    else if(type == 7) // Metallic
+    {
+        float val = texture(gbuffer, vec3(uv, 2)).x;
+        color = vec4(val, val, val, 1.);
+    }
+    else if(type == 8) // Roughness
+    {
+        float val = texture(gbuffer, vec3(uv, 2)).y;
+        color = vec4(val, val, val, 1.);
+    }
+    else if(type == 9) // Ambient occlusion
+    {
+        float val = texture(gbuffer, vec3(uv, 2)).z;
+        color = vec4(val, val, val, 1.);
+    }
+    else if(type == 10) // Emissive
+    {
+        color = vec4(texture(gbuffer, vec3(uv, 3)).xyz, 1.);
+    }
+    else if(type == 11) // Screen space ambient occlusion
+    {
+        float val = hasAmbientOcclusion ? texture(ambientOcclusionMap, uv).r : 0.0;
+        color = vec4(val, val, val, 1.);
+    }
     else {
         color = vec4(0., 0., 0., 0.);
     }
@@ -0,0 +1,17 @@
+uniform sampler2D logLuminanceMap;
+uniform sampler2D adaptedLuminanceMap;
+
+uniform float maxLod;
+uniform float adaptation;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    float average_luminance = exp(textureLod(logLuminanceMap, uv, maxLod).r);
+    float previous_luminance = texture(adaptedLuminanceMap, uv).r;
+    float luminance = mix(previous_luminance, average_luminance, adaptation);
+    color = vec4(luminance, 0.0, 0.0, 1.0);
+}
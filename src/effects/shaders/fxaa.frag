@@ -0,0 +1,43 @@
+uniform sampler2D colorMap;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+const float FXAA_REDUCE_MIN = 1.0 / 128.0;
+const float FXAA_REDUCE_MUL = 1.0 / 8.0;
+const float FXAA_SPAN_MAX = 8.0;
+
+float luma(vec3 rgb)
+{
+    float l = dot(rgb, vec3(0.299, 0.587, 0.114));
+    return l / (1.0 + l);
+}
+
+void main()
+{
+    vec2 texel = 1.0 / vec2(textureSize(colorMap, 0));
+    vec3 rgbNW = texture(colorMap, uv + vec2(-1.0, -1.0) * texel).rgb;
+    vec3 rgbNE = texture(colorMap, uv + vec2(1.0, -1.0) * texel).rgb;
+    vec3 rgbSW = texture(colorMap, uv + vec2(-1.0, 1.0) * texel).rgb;
+    vec3 rgbSE = texture(colorMap, uv + vec2(1.0, 1.0) * texel).rgb;
+    vec4 center = texture(colorMap, uv);
+
+    float lumaNW = luma(rgbNW);
+    float lumaNE = luma(rgbNE);
+    float lumaSW = luma(rgbSW);
+    float lumaSE = luma(rgbSE);
+    float lumaM = luma(center.rgb);
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    vec2 dir = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)), (lumaNW + lumaSW) - (lumaNE + lumaSE));
+    float dirReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * (0.25 * FXAA_REDUCE_MUL), FXAA_REDUCE_MIN);
+    float rcpDirMin = 1.0 / (min(abs(dir.x), abs(dir.y)) + dirReduce);
+    dir = clamp(dir * rcpDirMin, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX)) * texel;
+
+    vec3 rgbA = 0.5 * (texture(colorMap, uv + dir * (1.0 / 3.0 - 0.5)).rgb + texture(colorMap, uv + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgbB = rgbA * 0.5 + 0.25 * (texture(colorMap, uv - dir * 0.5).rgb + texture(colorMap, uv + dir * 0.5).rgb);
+    float lumaB = luma(rgbB);
+    color = vec4(lumaB < lumaMin || lumaB > lumaMax ? rgbA : rgbB, center.a);
+}
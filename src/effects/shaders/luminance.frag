@@ -0,0 +1,12 @@
+uniform sampler2D hdrMap;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    // The logarithm of the luminance, so the average of the mip reduction is the geometric mean which is not dominated by a few bright pixels
+    float luminance = dot(texture(hdrMap, uv).rgb, vec3(0.2126, 0.7152, 0.0722));
+    color = vec4(log(luminance + 0.0001), 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,62 @@
+uniform sampler2DArray gbuffer;
+uniform sampler2DArray depthMap;
+
+uniform mat4 viewProjection;
+uniform mat4 viewProjectionInverse;
+uniform vec3 eyePosition;
+
+uniform float radius;
+uniform int noSamples;
+uniform float bias;
+uniform float intensity;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+const float PI = 3.1415926535897932384626433832795;
+const float GOLDEN_ANGLE = 2.39996323;
+
+vec3 WorldPosFromDepth(float depth, vec2 uv) {
+    vec4 clipSpacePosition = vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec4 position = viewProjectionInverse * clipSpacePosition;
+    return position.xyz / position.w;
+}
+
+void main()
+{
+    float depth = texture(depthMap, vec3(uv, 0)).r;
+    if(depth > 0.99999)
+    {
+        color = vec4(1.0);
+        return;
+    }
+    vec3 position = WorldPosFromDepth(depth, uv);
+    vec3 normal = normalize(texture(gbuffer, vec3(uv, 1)).xyz * 2.0 - 1.0);
+
+    ivec2 pixel = ivec2(gl_FragCoord.xy) % 4;
+    float noise = float(pixel.x * 4 + pixel.y) / 16.0;
+
+    vec3 tangent = normalize(cross(abs(normal.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0), normal));
+    vec3 bitangent = cross(normal, tangent);
+
+    float occlusion = 0.0;
+    for(int i = 0; i < noSamples; i++)
+    {
+        float t = (float(i) + 0.5) / float(noSamples);
+        float angle = 2.0 * PI * noise + float(i) * GOLDEN_ANGLE;
+        vec3 direction = sqrt(t) * (cos(angle) * tangent + sin(angle) * bitangent) + sqrt(1.0 - t) * normal;
+        float scale = fract(float(i) * 0.618034 + noise);
+        vec3 sample_position = position + direction * radius * mix(0.1, 1.0, scale * scale);
+
+        vec4 clip_position = viewProjection * vec4(sample_position, 1.0);
+        vec2 sample_uv = clip_position.xy / clip_position.w * 0.5 + 0.5;
+        vec3 scene_position = WorldPosFromDepth(texture(depthMap, vec3(sample_uv, 0)).r, sample_uv);
+
+        float range_check = smoothstep(0.0, 1.0, radius / max(distance(position, scene_position), 0.0001));
+        bool is_occluded = distance(eyePosition, scene_position) < distance(eyePosition, sample_position) - bias;
+        occlusion += is_occluded ? range_check : 0.0;
+    }
+    float visibility = clamp(1.0 - intensity * occlusion / float(noSamples), 0.0, 1.0);
+    color = vec4(visibility, visibility, visibility, 1.0);
+}
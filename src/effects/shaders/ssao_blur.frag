@@ -0,0 +1,20 @@
+uniform sampler2D occlusionMap;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    vec2 texel_size = 1.0 / vec2(textureSize(occlusionMap, 0));
+    float visibility = 0.0;
+    for(int x = -2; x < 2; x++)
+    {
+        for(int y = -2; y < 2; y++)
+        {
+            visibility += texture(occlusionMap, uv + vec2(float(x), float(y)) * texel_size).r;
+        }
+    }
+    visibility /= 16.0;
+    color = vec4(visibility, visibility, visibility, 1.0);
+}
@@ -0,0 +1,61 @@
+uniform sampler2D colorMap;
+uniform sampler2D historyMap;
+uniform sampler2DArray depthMap;
+
+uniform mat4 viewProjectionInverse;
+uniform mat4 previousViewProjection;
+uniform float blendFactor;
+uniform bool hasHistory;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+vec3 WorldPosFromDepth(float depth, vec2 uv) {
+    vec4 clipSpacePosition = vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec4 position = viewProjectionInverse * clipSpacePosition;
+    return position.xyz / position.w;
+}
+
+// Weights a color by the inverse of its luminance, so a few very bright samples do not dominate the blend of high dynamic range colors
+float weight(vec3 rgb)
+{
+    return 1.0 / (1.0 + dot(rgb, vec3(0.2126, 0.7152, 0.0722)));
+}
+
+void main()
+{
+    vec4 current = texture(colorMap, uv);
+    if(!hasHistory)
+    {
+        color = current;
+        return;
+    }
+
+    float depth = texture(depthMap, vec3(uv, 0)).r;
+    vec4 previousPosition = previousViewProjection * vec4(WorldPosFromDepth(depth, uv), 1.0);
+    vec2 previousUv = previousPosition.xy / previousPosition.w * 0.5 + 0.5;
+    if(previousUv.x < 0.0 || previousUv.x > 1.0 || previousUv.y < 0.0 || previousUv.y > 1.0)
+    {
+        color = current;
+        return;
+    }
+
+    vec2 texel = 1.0 / vec2(textureSize(colorMap, 0));
+    vec3 minColor = current.rgb;
+    vec3 maxColor = current.rgb;
+    for(int x = -1; x <= 1; x++)
+    {
+        for(int y = -1; y <= 1; y++)
+        {
+            vec3 neighbour = texture(colorMap, uv + vec2(x, y) * texel).rgb;
+            minColor = min(minColor, neighbour);
+            maxColor = max(maxColor, neighbour);
+        }
+    }
+    vec3 history = clamp(texture(historyMap, previousUv).rgb, minColor, maxColor);
+
+    float currentWeight = blendFactor * weight(current.rgb);
+    float historyWeight = (1.0 - blendFactor) * weight(history);
+    color = vec4((current.rgb * currentWeight + history * historyWeight) / (currentWeight + historyWeight), current.a);
+}
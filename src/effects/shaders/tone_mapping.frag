@@ -0,0 +1,71 @@
+uniform sampler2D hdrMap;
+uniform sampler2D adaptedLuminanceMap;
+
+uniform int toneMappingOperator;
+uniform float exposure;
+uniform bool eyeAdaptation;
+uniform float keyValue;
+uniform float minExposure;
+uniform float maxExposure;
+uniform float gamma;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+vec3 reinhard(vec3 x)
+{
+    return x / (1.0 + x);
+}
+
+// The curve fitted to the ACES filmic reference tone mapping by Krzysztof Narkowicz
+vec3 aces_filmic(vec3 x)
+{
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+// The filmic curve by John Hable used in Uncharted 2
+vec3 uncharted2_curve(vec3 x)
+{
+    float A = 0.15;
+    float B = 0.50;
+    float C = 0.10;
+    float D = 0.20;
+    float E = 0.02;
+    float F = 0.30;
+    return ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F;
+}
+
+vec3 uncharted2(vec3 x)
+{
+    float white_point = 11.2;
+    float exposure_bias = 2.0;
+    return uncharted2_curve(exposure_bias * x) / uncharted2_curve(vec3(white_point));
+}
+
+void main()
+{
+    vec3 hdr_color = texture(hdrMap, uv).rgb;
+
+    float total_exposure = exposure;
+    if(eyeAdaptation)
+    {
+        float adapted_luminance = texture(adaptedLuminanceMap, vec2(0.5, 0.5)).r;
+        total_exposure *= clamp(keyValue / max(adapted_luminance, 0.0001), minExposure, maxExposure);
+    }
+    hdr_color *= total_exposure;
+
+    vec3 ldr_color;
+    if(toneMappingOperator == 0)
+    {
+        ldr_color = reinhard(hdr_color);
+    }
+    else if(toneMappingOperator == 1)
+    {
+        ldr_color = aces_filmic(hdr_color);
+    }
+    else {
+        ldr_color = uncharted2(hdr_color);
+    }
+    color = vec4(pow(ldr_color, vec3(1.0 / gamma)), 1.0);
+}
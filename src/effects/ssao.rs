@@ -0,0 +1,69 @@
+use crate::*;
+
+pub struct SsaoEffect {
+    gl: Gl,
+    ssao_program: program::Program,
+    blur_program: program::Program,
+    rendertarget: rendertarget::RenderTarget,
+    noisy_texture: Texture2D,
+    occlusion_texture: Texture2D,
+    full_screen: FullScreen,
+    pub radius: f32,
+    pub no_samples: u32,
+    pub bias: f32,
+    pub intensity: f32
+}
+
+impl SsaoEffect {
+
+    pub fn new(gl: &Gl, width: usize, height: usize) -> Result<SsaoEffect, effects::Error>
+    {
+        let ssao_program = program::Program::from_source(gl,
+                                                         include_str!("shaders/effect.vert"),
+                                                         include_str!("shaders/ssao.frag"))?;
+        let blur_program = program::Program::from_source(gl,
+                                                         include_str!("shaders/effect.vert"),
+                                                         include_str!("shaders/ssao_blur.frag"))?;
+
+        let full_screen = FullScreen::new(gl)?;
+
+        let rendertarget = rendertarget::RenderTarget::new(gl, 1)?;
+        let noisy_texture = Texture2D::new_as_color_target(gl, width, height)?;
+        let occlusion_texture = Texture2D::new_as_color_target(gl, width, height)?;
+
+        Ok(SsaoEffect {gl: gl.clone(), ssao_program, blur_program, rendertarget, noisy_texture, occlusion_texture, full_screen,
+            radius: 0.5, no_samples: 16, bias: 0.025, intensity: 1.0})
+    }
+
+    pub fn apply(&self, camera: &Camera, geometry_texture: &dyn Texture, depth_texture: &dyn Texture) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        self.rendertarget.write_to_color(&self.noisy_texture)?;
+        let view_projection = camera.get_projection() * camera.get_view();
+        self.ssao_program.use_texture(geometry_texture, "gbuffer")?;
+        self.ssao_program.use_texture(depth_texture, "depthMap")?;
+        self.ssao_program.add_uniform_mat4("viewProjection", &view_projection)?;
+        self.ssao_program.add_uniform_mat4("viewProjectionInverse", &view_projection.invert().unwrap())?;
+        self.ssao_program.add_uniform_vec3("eyePosition", camera.position())?;
+        self.ssao_program.add_uniform_float("radius", &self.radius)?;
+        self.ssao_program.add_uniform_int("noSamples", &(self.no_samples.max(1) as i32))?;
+        self.ssao_program.add_uniform_float("bias", &self.bias)?;
+        self.ssao_program.add_uniform_float("intensity", &self.intensity)?;
+        self.full_screen.draw(&self.ssao_program)?;
+
+        self.rendertarget.write_to_color(&self.occlusion_texture)?;
+        self.blur_program.use_texture(&self.noisy_texture, "occlusionMap")?;
+        self.full_screen.draw(&self.blur_program)?;
+        Ok(())
+    }
+
+    pub fn occlusion_texture(&self) -> &Texture2D
+    {
+        &self.occlusion_texture
+    }
+
+}
@@ -0,0 +1,101 @@
+use crate::*;
+
+/// The camera must be jittered by a different sub-pixel offset each frame, see `DeferredPipeline::jitter_camera`.
+pub struct TaaEffect {
+    gl: Gl,
+    resolve_program: program::Program,
+    copy_program: program::Program,
+    rendertarget: rendertarget::RenderTarget,
+    history_textures: [Texture2D; 2],
+    current_history: usize,
+    previous_view_projection: Option<Mat4>,
+    full_screen: FullScreen,
+    pub blend_factor: f32
+}
+
+impl TaaEffect {
+
+    pub fn new(gl: &Gl, width: usize, height: usize) -> Result<TaaEffect, effects::Error>
+    {
+        let resolve_program = program::Program::from_source(gl,
+                                                            include_str!("shaders/effect.vert"),
+                                                            include_str!("shaders/taa.frag"))?;
+        let copy_program = program::Program::from_source(gl,
+                                                         include_str!("shaders/effect.vert"),
+                                                         include_str!("shaders/copy_color.frag"))?;
+
+        let full_screen = FullScreen::new(gl)?;
+
+        let rendertarget = rendertarget::RenderTarget::new(gl, 1)?;
+        let history_textures = [Texture2D::new_as_hdr_color_target(gl, width, height)?, Texture2D::new_as_hdr_color_target(gl, width, height)?];
+
+        Ok(TaaEffect {gl: gl.clone(), resolve_program, copy_program, rendertarget, history_textures, current_history: 0,
+            previous_view_projection: None, full_screen, blend_factor: 0.1})
+    }
+
+    /// Changes the bound render target, so it must be called before `apply`.
+    pub fn resolve(&mut self, camera: &Camera, color_texture: &Texture2D, depth_texture: &dyn Texture) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        let previous = self.current_history;
+        self.current_history = 1 - previous;
+        let view_projection = camera.get_projection() * camera.get_view();
+        self.rendertarget.write_to_color(&self.history_textures[self.current_history])?;
+        self.resolve_program.use_texture(color_texture, "colorMap")?;
+        self.resolve_program.use_texture(&self.history_textures[previous], "historyMap")?;
+        self.resolve_program.use_texture(depth_texture, "depthMap")?;
+        self.resolve_program.add_uniform_mat4("viewProjectionInverse", &view_projection.invert().unwrap())?;
+        self.resolve_program.add_uniform_mat4("previousViewProjection", &self.previous_view_projection.unwrap_or(view_projection))?;
+        self.resolve_program.add_uniform_float("blendFactor", &self.blend_factor)?;
+        self.resolve_program.add_uniform_int("hasHistory", &(self.previous_view_projection.is_some() as i32))?;
+        self.full_screen.draw(&self.resolve_program)?;
+
+        // The history is reprojected without the jitter, since the jitter of the previous frame is already part of the history
+        self.previous_view_projection = Some(camera.get_unjittered_projection() * camera.get_view());
+        Ok(())
+    }
+
+    pub fn apply(&self) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        self.copy_program.use_texture(self.output_texture(), "colorMap")?;
+        self.full_screen.draw(&self.copy_program)?;
+        Ok(())
+    }
+
+    pub fn output_texture(&self) -> &Texture2D
+    {
+        &self.history_textures[self.current_history]
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.previous_view_projection = None;
+    }
+
+}
+
+impl PostProcessEffect for TaaEffect {
+
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>
+    {
+        self.resolve(input.camera, input.color_texture, input.depth_texture)?;
+        input.bind_output()?;
+        self.apply()
+    }
+
+    fn resize(&mut self, width: usize, height: usize) -> Result<(), effects::Error>
+    {
+        self.history_textures = [Texture2D::new_as_hdr_color_target(&self.gl, width, height)?, Texture2D::new_as_hdr_color_target(&self.gl, width, height)?];
+        self.reset();
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMappingOperator {
+    Reinhard,
+    AcesFilmic,
+    Uncharted2
+}
+
+const LUMINANCE_SIZE: usize = 256;
+const LUMINANCE_MIP_LEVELS: usize = 9;
+
+pub struct ToneMappingEffect {
+    gl: Gl,
+    program: program::Program,
+    luminance_program: program::Program,
+    eye_adaptation_program: program::Program,
+    rendertarget: rendertarget::RenderTarget,
+    log_luminance_texture: Texture2D,
+    adapted_luminance_textures: [Texture2D; 2],
+    current_adapted_luminance: usize,
+    is_adapted: bool,
+    full_screen: FullScreen,
+    pub operator: ToneMappingOperator,
+    pub exposure: f32,
+    pub eye_adaptation: bool,
+    pub adaptation_speed: f32,
+    pub key_value: f32,
+    pub min_exposure: f32,
+    pub max_exposure: f32,
+    pub gamma: f32
+}
+
+impl ToneMappingEffect {
+
+    pub fn new(gl: &Gl) -> Result<ToneMappingEffect, effects::Error>
+    {
+        let program = program::Program::from_source(gl,
+                                                    include_str!("shaders/effect.vert"),
+                                                    include_str!("shaders/tone_mapping.frag"))?;
+        let luminance_program = program::Program::from_source(gl,
+                                                              include_str!("shaders/effect.vert"),
+                                                              include_str!("shaders/luminance.frag"))?;
+        let eye_adaptation_program = program::Program::from_source(gl,
+                                                                   include_str!("shaders/effect.vert"),
+                                                                   include_str!("shaders/eye_adaptation.frag"))?;
+
+        let full_screen = FullScreen::new(gl)?;
+
+        let rendertarget = rendertarget::RenderTarget::new(gl, 1)?;
+        let log_luminance_texture = Texture2D::new_as_hdr_color_target_with_mip_maps(gl, LUMINANCE_SIZE, LUMINANCE_SIZE, LUMINANCE_MIP_LEVELS)?;
+        let adapted_luminance_textures = [Texture2D::new_as_hdr_color_target(gl, 1, 1)?, Texture2D::new_as_hdr_color_target(gl, 1, 1)?];
+
+        Ok(ToneMappingEffect {gl: gl.clone(), program, luminance_program, eye_adaptation_program, rendertarget, log_luminance_texture,
+            adapted_luminance_textures, current_adapted_luminance: 0, is_adapted: false, full_screen,
+            operator: ToneMappingOperator::AcesFilmic, exposure: 1.0, eye_adaptation: false, adaptation_speed: 1.0,
+            key_value: 0.18, min_exposure: 0.1, max_exposure: 10.0, gamma: 2.2})
+    }
+
+    /// Changes the bound render target, so it must be called before `apply`. The elapsed time is in milliseconds.
+    pub fn update_eye_adaptation(&mut self, hdr_texture: &Texture2D, elapsed_time: f32) -> Result<(), effects::Error>
+    {
+        if !self.eye_adaptation {
+            self.is_adapted = false;
+            return Ok(());
+        }
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        self.rendertarget.write_to_color(&self.log_luminance_texture)?;
+        self.luminance_program.use_texture(hdr_texture, "hdrMap")?;
+        self.full_screen.draw(&self.luminance_program)?;
+        self.log_luminance_texture.generate_mip_maps();
+
+        let adaptation = if self.is_adapted { 1.0 - (-0.001 * elapsed_time * self.adaptation_speed).exp() } else { 1.0 };
+        let previous = self.current_adapted_luminance;
+        self.current_adapted_luminance = 1 - previous;
+        self.rendertarget.write_to_color(&self.adapted_luminance_textures[self.current_adapted_luminance])?;
+        self.eye_adaptation_program.use_texture(&self.log_luminance_texture, "logLuminanceMap")?;
+        self.eye_adaptation_program.use_texture(&self.adapted_luminance_textures[previous], "adaptedLuminanceMap")?;
+        self.eye_adaptation_program.add_uniform_float("maxLod", &((LUMINANCE_MIP_LEVELS - 1) as f32))?;
+        self.eye_adaptation_program.add_uniform_float("adaptation", &adaptation)?;
+        self.full_screen.draw(&self.eye_adaptation_program)?;
+        self.is_adapted = true;
+        Ok(())
+    }
+
+    pub fn apply(&self, hdr_texture: &Texture2D) -> Result<(), effects::Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        let operator = match self.operator {
+            ToneMappingOperator::Reinhard => 0,
+            ToneMappingOperator::AcesFilmic => 1,
+            ToneMappingOperator::Uncharted2 => 2
+        };
+        self.program.use_texture(hdr_texture, "hdrMap")?;
+        self.program.use_texture(&self.adapted_luminance_textures[self.current_adapted_luminance], "adaptedLuminanceMap")?;
+        self.program.add_uniform_int("toneMappingOperator", &operator)?;
+        self.program.add_uniform_float("exposure", &self.exposure)?;
+        self.program.add_uniform_int("eyeAdaptation", &((self.eye_adaptation && self.is_adapted) as i32))?;
+        self.program.add_uniform_float("keyValue", &self.key_value)?;
+        self.program.add_uniform_float("minExposure", &self.min_exposure)?;
+        self.program.add_uniform_float("maxExposure", &self.max_exposure)?;
+        self.program.add_uniform_float("gamma", &self.gamma)?;
+        self.full_screen.draw(&self.program)?;
+        Ok(())
+    }
+
+}
+
+impl PostProcessEffect for ToneMappingEffect {
+
+    fn process(&mut self, input: &PostProcessInput) -> Result<(), effects::Error>
+    {
+        self.update_eye_adaptation(input.color_texture, input.elapsed_time)?;
+        input.bind_output()?;
+        self.apply(input.color_texture)
+    }
+}
@@ -0,0 +1,158 @@
+use crate::*;
+
+#[derive(Debug)]
+pub enum Error {
+    Program(program::Error),
+    Texture(texture::Error),
+    Rendertarget(rendertarget::Error),
+    Buffer(buffer::Error)
+}
+
+impl From<program::Error> for Error {
+    fn from(other: program::Error) -> Self {
+        Error::Program(other)
+    }
+}
+
+impl From<texture::Error> for Error {
+    fn from(other: texture::Error) -> Self {
+        Error::Texture(other)
+    }
+}
+
+impl From<rendertarget::Error> for Error {
+    fn from(other: rendertarget::Error) -> Self {
+        Error::Rendertarget(other)
+    }
+}
+
+impl From<buffer::Error> for Error {
+    fn from(other: buffer::Error) -> Self {
+        Error::Buffer(other)
+    }
+}
+
+const ENVIRONMENT_MAP_SIZE: usize = 256;
+const IRRADIANCE_MAP_SIZE: usize = 32;
+const PREFILTERED_MAP_SIZE: usize = 128;
+const PREFILTERED_MAP_MIP_LEVELS: usize = 5;
+const BRDF_LUT_SIZE: usize = 256;
+
+pub struct EnvironmentLight {
+    irradiance_map: Texture3D,
+    prefiltered_map: Texture3D,
+    brdf_lut: Texture2D,
+    intensity: f32
+}
+
+impl EnvironmentLight {
+
+    pub fn new(gl: &Gl, environment_map: &Texture3D) -> Result<EnvironmentLight, Error>
+    {
+        Self::new_from_source(gl, include_str!("shaders/copy_cube_map.frag"), environment_map, "environmentMap")
+    }
+
+    pub fn new_from_equirectangular(gl: &Gl, environment_map: &Texture2D) -> Result<EnvironmentLight, Error>
+    {
+        Self::new_from_source(gl, include_str!("shaders/equirectangular.frag"), environment_map, "equirectangularMap")
+    }
+
+    fn new_from_source(gl: &Gl, fragment_shader_source: &str, source: &dyn Texture, source_name: &str) -> Result<EnvironmentLight, Error>
+    {
+        state::depth_write(gl, false);
+        state::depth_test(gl, state::DepthTestType::None);
+        state::cull(gl, state::CullType::None);
+        state::blend(gl, state::BlendType::None);
+
+        let rendertarget = RenderTarget::new(gl, 1)?;
+        let full_screen = full_screen_buffer(gl)?;
+
+        let no_mip_levels = (ENVIRONMENT_MAP_SIZE as f32).log2() as usize + 1;
+        let environment_map = Texture3D::new_as_hdr_color_target(gl, ENVIRONMENT_MAP_SIZE, no_mip_levels)?;
+        let program = cube_map_program(gl, fragment_shader_source)?;
+        program.use_texture(source, source_name)?;
+        render_cube_map(&rendertarget, &full_screen, &program, &environment_map, 0)?;
+        environment_map.generate_mip_maps();
+
+        let irradiance_map = Texture3D::new_as_hdr_color_target(gl, IRRADIANCE_MAP_SIZE, 1)?;
+        let program = cube_map_program(gl, include_str!("shaders/irradiance.frag"))?;
+        program.use_texture(&environment_map, "environmentMap")?;
+        render_cube_map(&rendertarget, &full_screen, &program, &irradiance_map, 0)?;
+
+        let prefiltered_map = Texture3D::new_as_hdr_color_target(gl, PREFILTERED_MAP_SIZE, PREFILTERED_MAP_MIP_LEVELS)?;
+        let program = cube_map_program(gl, &format!("{}{}", include_str!("shaders/importance_sampling.frag"), include_str!("shaders/prefilter.frag")))?;
+        program.use_texture(&environment_map, "environmentMap")?;
+        for mip_level in 0..PREFILTERED_MAP_MIP_LEVELS {
+            program.add_uniform_float("roughness", &(mip_level as f32 / (PREFILTERED_MAP_MIP_LEVELS - 1) as f32))?;
+            render_cube_map(&rendertarget, &full_screen, &program, &prefiltered_map, mip_level)?;
+        }
+
+        let brdf_lut = Texture2D::new_as_hdr_color_target(gl, BRDF_LUT_SIZE, BRDF_LUT_SIZE)?;
+        let program = program::Program::from_source(gl, include_str!("shaders/cube_map_face.vert"),
+                                                    &format!("{}{}", include_str!("shaders/importance_sampling.frag"), include_str!("shaders/brdf_lut.frag")))?;
+        rendertarget.write_to_color(&brdf_lut)?;
+        program.add_uniform_float("size", &(BRDF_LUT_SIZE as f32))?;
+        program.use_attribute_vec3_float(&full_screen, "position", 0)?;
+        program.draw_arrays(3);
+
+        Ok(EnvironmentLight { irradiance_map, prefiltered_map, brdf_lut, intensity: 1.0 })
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32)
+    {
+        self.intensity = intensity;
+    }
+
+    pub(crate) fn irradiance_map(&self) -> &Texture3D
+    {
+        &self.irradiance_map
+    }
+
+    pub(crate) fn prefiltered_map(&self) -> &Texture3D
+    {
+        &self.prefiltered_map
+    }
+
+    pub(crate) fn max_prefiltered_lod(&self) -> f32
+    {
+        (PREFILTERED_MAP_MIP_LEVELS - 1) as f32
+    }
+
+    pub(crate) fn brdf_lut(&self) -> &Texture2D
+    {
+        &self.brdf_lut
+    }
+}
+
+fn cube_map_program(gl: &Gl, fragment_shader_source: &str) -> Result<program::Program, Error>
+{
+    Ok(program::Program::from_source(gl, include_str!("shaders/cube_map_face.vert"),
+                                     &format!("{}{}", include_str!("shaders/cube_map_face.frag"), fragment_shader_source))?)
+}
+
+fn full_screen_buffer(gl: &Gl) -> Result<VertexBuffer, Error>
+{
+    let positions = vec![
+        -3.0, -1.0, 0.0,
+        3.0, -1.0, 0.0,
+        0.0, 2.0, 0.0
+    ];
+    Ok(VertexBuffer::new_with_one_static_attribute(gl, &positions)?)
+}
+
+fn render_cube_map(rendertarget: &RenderTarget, full_screen: &VertexBuffer, program: &program::Program, cube_map: &Texture3D, mip_level: usize) -> Result<(), Error>
+{
+    program.add_uniform_float("faceSize", &((cube_map.width >> mip_level).max(1) as f32))?;
+    for face in 0..6 {
+        rendertarget.write_to_color_cube_map(cube_map, face, mip_level)?;
+        program.add_uniform_int("face", &(face as i32))?;
+        program.use_attribute_vec3_float(full_screen, "position", 0)?;
+        program.draw_arrays(3);
+    }
+    Ok(())
+}
@@ -7,20 +7,36 @@ pub mod renderer;
 #[cfg(not(feature = "no-renderer"))]
 pub mod light;
 #[cfg(not(feature = "no-renderer"))]
+pub mod environment;
+#[cfg(not(feature = "no-renderer"))]
 pub mod objects;
 #[cfg(not(feature = "no-renderer"))]
 pub mod effects;
+#[cfg(not(feature = "no-renderer"))]
+pub mod scene;
+#[cfg(not(feature = "no-renderer"))]
+pub mod culling;
+#[cfg(not(feature = "no-renderer"))]
+pub mod picking;
 
 #[cfg(not(feature = "no-renderer"))]
 pub use crate::renderer::*;
 #[cfg(not(feature = "no-renderer"))]
 pub use crate::light::*;
 #[cfg(not(feature = "no-renderer"))]
+pub use crate::environment::*;
+#[cfg(not(feature = "no-renderer"))]
 pub use crate::objects::*;
 #[cfg(not(feature = "no-renderer"))]
 pub use crate::effects::*;
+#[cfg(not(feature = "no-renderer"))]
+pub use crate::scene::*;
+#[cfg(not(feature = "no-renderer"))]
+pub use crate::culling::*;
+#[cfg(not(feature = "no-renderer"))]
+pub use crate::picking::*;
 
-#[cfg(any(feature = "glutin-window", feature = "canvas"))]
+#[cfg(any(feature = "glutin-window", feature = "canvas", feature = "headless"))]
 pub mod window;
-#[cfg(any(feature = "glutin-window", feature = "canvas"))]
+#[cfg(any(feature = "glutin-window", feature = "canvas", feature = "headless"))]
 pub use window::*;
\ No newline at end of file
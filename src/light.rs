@@ -4,7 +4,8 @@ use crate::*;
 #[derive(Debug)]
 pub enum Error {
     Buffer(buffer::Error),
-    Rendertarget(rendertarget::Error)
+    Rendertarget(rendertarget::Error),
+    InvalidShadowCascades {message: String}
 }
 
 impl From<buffer::Error> for Error {
@@ -19,8 +20,6 @@ impl From<rendertarget::Error> for Error {
     }
 }
 
-pub const MAX_NO_LIGHTS: usize = 4;
-
 pub struct AmbientLight
 {
     color: Vec3,
@@ -55,63 +54,127 @@ impl AmbientLight
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadowFilter {
+    Hard,
+    Pcf {kernel_size: usize},
+    /// The `light_size` is the width of the light in world units, or the tangent of its angular width for directional lights.
+    Pcss {light_size: f32, kernel_size: usize}
+}
+
+/// The biases are given in shadow map texels at the shadowed surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    pub resolution: usize,
+    pub depth_bias: f32,
+    pub normal_offset_bias: f32,
+    pub near: f32,
+    pub far: f32,
+    pub filter: ShadowFilter
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self
+    {
+        ShadowSettings {
+            resolution: 1024,
+            depth_bias: 1.5,
+            normal_offset_bias: 1.5,
+            near: 0.1,
+            far: 200.0,
+            filter: ShadowFilter::Pcf {kernel_size: 3}
+        }
+    }
+}
+
+pub const MAX_NO_CASCADES: usize = 8;
+
+/// Must match MAX_KERNEL_SIZE in shadow.frag.
+pub const MAX_SHADOW_KERNEL_SIZE: usize = 33;
+
 pub struct DirectionalLight {
     gl: Gl,
     light_buffer: UniformBuffer,
-    shadow_texture: Texture2DArray,
-    shadow_rendertarget: RenderTarget,
-    shadow_cameras: [Option<Camera>; MAX_NO_LIGHTS],
-    index: usize
+    shadow_texture: Option<Texture2DArray>,
+    shadow_cameras: Vec<Camera>,
+    shadow_settings: ShadowSettings,
+    cascades: Option<Cascades>,
+    shadow_box: (Vec3, f32, f32)
+}
+
+enum Cascades {
+    Automatic {no_cascades: usize, max_distance: f32},
+    Splits(Vec<f32>)
 }
 
 impl DirectionalLight {
 
     pub(crate) fn new(gl: &Gl) -> Result<DirectionalLight, Error>
     {
-        let uniform_sizes: Vec<u32> = [3u32, 1, 3, 1, 16].iter().cloned().cycle().take(5*MAX_NO_LIGHTS).collect();
-
-        let shadow_texture = Texture2DArray::new_as_depth_targets(gl, 1024, 1024, MAX_NO_LIGHTS).unwrap();
-        let mut lights = DirectionalLight {
+        let mut uniform_sizes = vec![3u32, 1, 3, 1, 4, 4];
+        uniform_sizes.extend(vec![16; MAX_NO_CASCADES]);
+        uniform_sizes.push(8);
+        let mut light = DirectionalLight {
             gl: gl.clone(),
-            shadow_texture,
-            shadow_rendertarget: RenderTarget::new(gl, 0)?,
             light_buffer: UniformBuffer::new(gl, &uniform_sizes)?,
-            shadow_cameras: [None, None, None, None],
-            index: 0};
-
-        for light_id in 0..MAX_NO_LIGHTS {
-            let light = lights.light_at(light_id);
-            light.set_intensity(0.0);
-            light.set_color(&vec3(1.0, 1.0, 1.0));
-            light.set_direction(&vec3(0.0, -1.0, 0.0));
-            light.disable_shadows();
-        }
-        Ok(lights)
+            shadow_texture: None,
+            shadow_cameras: Vec::new(),
+            shadow_settings: ShadowSettings::default(),
+            cascades: None,
+            shadow_box: (vec3(0.0, 0.0, 0.0), 4.0, 20.0)};
+
+        light.set_intensity(0.0);
+        light.set_color(&vec3(1.0, 1.0, 1.0));
+        light.set_direction(&vec3(0.0, -1.0, 0.0));
+        light.disable_shadows();
+        Ok(light)
     }
 
     pub fn set_color(&mut self, color: &Vec3)
     {
-        self.light_buffer.update(self.index_at(0), &color.to_slice()).unwrap();
+        self.light_buffer.update(0, &color.to_slice()).unwrap();
     }
 
     pub fn set_intensity(&mut self, intensity: f32)
     {
-        self.light_buffer.update(self.index_at(1), &[intensity]).unwrap();
+        self.light_buffer.update(1, &[intensity]).unwrap();
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.light_buffer.get(1).unwrap()[0]
     }
 
     pub fn set_direction(&mut self, direction: &Vec3)
     {
-        self.light_buffer.update(self.index_at(2), &direction.to_slice()).unwrap();
-        self.update_shadows(vec3(0.0, 0.0, 0.0), 4.0, 20.0);
+        self.light_buffer.update(2, &direction.to_slice()).unwrap();
+        if self.is_shadows_enabled() && self.cascades.is_none() {
+            let (target, size, depth) = self.shadow_box;
+            self.update_shadows(target, size, depth);
+        }
     }
 
     pub fn direction(&self) -> Vec3 {
-        let d = self.light_buffer.get(self.index_at(2)).unwrap();
+        let d = self.light_buffer.get(2).unwrap();
         vec3(d[0], d[1], d[2])
     }
 
     pub fn is_shadows_enabled(&self) -> bool {
-        self.shadow_cameras[self.index].is_some()
+        !self.shadow_cameras.is_empty()
+    }
+
+    pub fn shadow_settings(&self) -> &ShadowSettings
+    {
+        &self.shadow_settings
+    }
+
+    pub fn set_shadow_settings(&mut self, settings: ShadowSettings)
+    {
+        self.shadow_settings = settings;
+        if self.is_shadows_enabled() {
+            self.update_shadow_texture();
+        }
+        self.update_shadow_parameters();
     }
 
     pub fn enable_shadows(&mut self)
@@ -123,278 +186,567 @@ impl DirectionalLight {
         let direction = self.direction();
         let up = compute_up_direction(direction);
 
-        if let Some(ref mut camera) = self.shadow_cameras[self.index]
+        self.shadow_box = (target, size, depth);
+        self.cascades = None;
+        self.shadow_cameras.truncate(1);
+        if let Some(camera) = self.shadow_cameras.first_mut()
         {
             camera.set_view(target - direction, target, up);
             camera.set_orthographic_projection(size, size, depth);
         }
         else {
             let camera = Camera::new_orthographic(&self.gl, target - direction, target, up, size, size, depth);
-            self.shadow_cameras[self.index] = Some(camera);
+            self.shadow_cameras.push(camera);
         }
+        self.update_shadow_texture();
+        self.update_shadow_matrices(&[f32::MAX]);
+    }
 
-        let camera = self.shadow_cameras[self.index].as_ref().unwrap();
-        self.light_buffer.update(self.index_at(4), &shadow_matrix(camera).to_slice()).unwrap();
+    pub fn set_shadow_cascades(&mut self, no_cascades: usize, max_distance: f32) -> Result<(), Error>
+    {
+        if no_cascades == 0 || no_cascades > MAX_NO_CASCADES {
+            Err(Error::InvalidShadowCascades {message: format!("The number of cascades {} is not between 1 and {}", no_cascades, MAX_NO_CASCADES)})?;
+        }
+        if !(max_distance > 0.0 && max_distance.is_finite()) {
+            Err(Error::InvalidShadowCascades {message: format!("The maximum distance {} of the cascades is not positive", max_distance)})?;
+        }
+        self.cascades = Some(Cascades::Automatic {no_cascades, max_distance});
+        self.update_cascade_count(no_cascades);
+        Ok(())
+    }
+
+    pub fn set_shadow_cascade_splits(&mut self, splits: &[f32]) -> Result<(), Error>
+    {
+        validate_cascade_splits(splits)?;
+        self.update_cascade_count(splits.len());
+        self.cascades = Some(Cascades::Splits(splits.to_vec()));
+        Ok(())
     }
 
     pub fn disable_shadows(&mut self)
     {
-        self.shadow_cameras[self.index] = None;
-        self.light_buffer.update(self.index_at(4), &Mat4::from_value(0.0).to_slice()).unwrap();
+        self.shadow_cameras.clear();
+        self.shadow_texture = None;
+        self.cascades = None;
+        self.light_buffer.update(3, &[0.0]).unwrap();
+        self.update_shadow_parameters();
     }
 
-    pub(crate) fn shadow_pass<F>(&self, render_scene: &F)
-        where F: Fn(&Camera)
+    fn update_cascade_count(&mut self, no_cascades: usize)
     {
-        for light_id in 0..MAX_NO_LIGHTS {
-            if let Some(ref camera) = self.shadow_cameras[light_id]
-            {
-                self.shadow_rendertarget.write_to_depth_array(&self.shadow_texture, light_id).unwrap();
-                self.shadow_rendertarget.clear_depth();
-                render_scene(camera);
-            }
+        let direction = self.direction();
+        self.shadow_cameras.truncate(no_cascades);
+        while self.shadow_cameras.len() < no_cascades {
+            self.shadow_cameras.push(Camera::new_orthographic(&self.gl, -direction, vec3(0.0, 0.0, 0.0), compute_up_direction(direction), 1.0, 1.0, 1.0));
         }
+        self.update_shadow_texture();
     }
 
-    pub(crate) fn shadow_maps(&self) -> &Texture2DArray
+    fn update_shadow_texture(&mut self)
     {
-        &self.shadow_texture
+        let resolution = self.shadow_settings.resolution;
+        if self.shadow_texture.as_ref().map(|texture| texture.depth != self.shadow_cameras.len() || texture.width != resolution).unwrap_or(true) {
+            self.shadow_texture = Some(Texture2DArray::new_as_depth_targets(&self.gl, resolution, resolution, self.shadow_cameras.len()).unwrap());
+        }
+        self.update_shadow_parameters();
     }
 
-    pub(crate) fn buffer(&self) -> &UniformBuffer
+    fn update_shadow_parameters(&mut self)
     {
-        &self.light_buffer
+        let parameters = shadow_parameters(&self.shadow_settings, self.is_shadows_enabled());
+        self.light_buffer.update(6 + MAX_NO_CASCADES, &parameters).unwrap();
+    }
+
+    fn update_shadow_matrices(&mut self, splits: &[f32])
+    {
+        self.light_buffer.update(3, &[self.shadow_cameras.len() as f32]).unwrap();
+        let mut split_data = [0.0; 8];
+        for (i, split) in splits.iter().enumerate() {
+            split_data[i] = *split;
+        }
+        self.light_buffer.update(4, &split_data[0..4]).unwrap();
+        self.light_buffer.update(5, &split_data[4..8]).unwrap();
+        for (i, camera) in self.shadow_cameras.iter().enumerate() {
+            self.light_buffer.update(6 + i, &shadow_matrix(camera).to_slice()).unwrap();
+        }
+    }
+
+    fn update_cascades(&mut self, camera: &Camera)
+    {
+        let z_near = camera.z_near().max(0.0);
+        let splits = match self.cascades {
+            Some(ref cascades) => cascade_splits(cascades, z_near, camera.z_far()),
+            None => return
+        };
+
+        let view_direction = (*camera.target() - *camera.position()).normalize();
+        let inverse = (camera.get_projection() * camera.get_view()).invert().unwrap();
+        let unproject = |x: f32, y: f32, z: f32| { let p = inverse * vec4(x, y, z, 1.0); p.truncate() / p.w };
+        let corners: Vec<(Vec3, Vec3)> = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].iter()
+            .map(|(x, y)| (unproject(*x, *y, -1.0), unproject(*x, *y, 1.0))).collect();
+        let depth_of = |p: Vec3| (p - *camera.position()).dot(view_direction);
+        let point_at = |corner: &(Vec3, Vec3), depth: f32| {
+            let (near_depth, far_depth) = (depth_of(corner.0), depth_of(corner.1));
+            corner.0 + (corner.1 - corner.0) * ((depth - near_depth) / (far_depth - near_depth))
+        };
+
+        let direction = self.direction().normalize();
+        let up = compute_up_direction(direction);
+        let max_distance = *splits.last().unwrap();
+        let mut start = z_near;
+        for (camera_index, end) in splits.iter().cloned().enumerate() {
+            let points: Vec<Vec3> = corners.iter().flat_map(|corner| vec![point_at(corner, start), point_at(corner, end)]).collect();
+
+            // A bounding sphere keeps the size of the shadow camera constant when the camera rotates
+            let center = points.iter().fold(vec3(0.0, 0.0, 0.0), |sum, p| sum + p) / points.len() as f32;
+            let radius = points.iter().map(|p| (p - center).magnitude()).fold(0.0, f32::max);
+
+            // Move the center in whole texels to avoid flickering shadow edges when the camera moves
+            let texel_size = 2.0 * radius / self.shadow_settings.resolution as f32;
+            let light_view = Mat4::look_at_dir(Point::new(0.0, 0.0, 0.0), direction, up);
+            let light_center = (light_view * center.extend(1.0)).truncate();
+            let snapped = vec3((light_center.x / texel_size).floor() * texel_size, (light_center.y / texel_size).floor() * texel_size, light_center.z);
+            let center = (light_view.invert().unwrap() * snapped.extend(1.0)).truncate();
+
+            let depth = 2.0 * (radius + max_distance);
+            let shadow_camera = &mut self.shadow_cameras[camera_index];
+            shadow_camera.set_view(center - direction, center, up);
+            shadow_camera.set_orthographic_projection(2.0 * radius, 2.0 * radius, depth);
+            start = end;
+        }
+        self.update_shadow_matrices(&splits);
     }
 
-    pub(crate) fn light_at(&mut self, index: usize) -> &mut Self
+    pub(crate) fn shadow_pass<F>(&mut self, camera: &Camera, shadow_rendertarget: &RenderTarget, render_scene: &F)
+        where F: Fn(&Camera)
     {
-        self.index = index;
-        self
+        self.update_cascades(camera);
+        if let Some(ref texture) = self.shadow_texture
+        {
+            for (layer, camera) in self.shadow_cameras.iter().enumerate() {
+                shadow_rendertarget.write_to_depth_array(texture, layer).unwrap();
+                shadow_rendertarget.clear_depth();
+                render_scene(camera);
+            }
+        }
     }
 
-    fn index_at(&self, index: usize) -> usize
+    pub(crate) fn shadow_maps(&self) -> Option<&Texture2DArray>
     {
-        self.index * 5 + index
+        self.shadow_texture.as_ref()
+    }
+
+    pub(crate) fn buffer(&self) -> &UniformBuffer
+    {
+        &self.light_buffer
     }
 }
 
 pub struct PointLight {
+    gl: Gl,
     light_buffer: UniformBuffer,
-    index: usize
+    shadow_texture: Option<Texture3D>,
+    shadow_cameras: Vec<Camera>,
+    shadow_settings: ShadowSettings
 }
 
 impl PointLight {
 
     pub(crate) fn new(gl: &Gl) -> Result<PointLight, Error>
     {
-        let uniform_sizes: Vec<u32> = [3u32, 1, 1, 1, 1, 1, 3, 1].iter().cloned().cycle().take(8*MAX_NO_LIGHTS).collect();
-        let mut lights = PointLight {
-            light_buffer: UniformBuffer::new(gl, &uniform_sizes)?,
-            index: 0};
-
-        for light_id in 0..MAX_NO_LIGHTS {
-            let light = lights.light_at(light_id);
-            light.set_intensity(0.0);
-            light.set_color(&vec3(1.0, 1.0, 1.0));
-            light.set_position(&vec3(0.0, 0.0, 0.0));
-            light.set_attenuation(0.5, 0.05, 0.005);
-        }
-        Ok(lights)
+        let mut light = PointLight {
+            gl: gl.clone(),
+            light_buffer: UniformBuffer::new(gl, &[3u32, 1, 1, 1, 1, 1, 3, 1, 8])?,
+            shadow_texture: None,
+            shadow_cameras: Vec::new(),
+            shadow_settings: ShadowSettings {resolution: 512, ..ShadowSettings::default()}};
+
+        light.set_intensity(0.0);
+        light.set_color(&vec3(1.0, 1.0, 1.0));
+        light.set_position(&vec3(0.0, 0.0, 0.0));
+        light.set_attenuation(0.5, 0.05, 0.005);
+        light.disable_shadows();
+        Ok(light)
     }
 
     pub fn set_color(&mut self, color: &Vec3)
     {
-        self.light_buffer.update(self.index_at(0), &color.to_slice()).unwrap();
+        self.light_buffer.update(0, &color.to_slice()).unwrap();
     }
 
     pub fn set_intensity(&mut self, intensity: f32)
     {
-        self.light_buffer.update(self.index_at(1), &[intensity]).unwrap();
+        self.light_buffer.update(1, &[intensity]).unwrap();
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.light_buffer.get(1).unwrap()[0]
     }
 
     pub fn set_attenuation(&mut self, constant: f32, linear: f32, exponential: f32)
     {
-        self.light_buffer.update(self.index_at(2), &[constant]).unwrap();
-        self.light_buffer.update(self.index_at(3), &[linear]).unwrap();
-        self.light_buffer.update(self.index_at(4), &[exponential]).unwrap();
+        self.light_buffer.update(2, &[constant]).unwrap();
+        self.light_buffer.update(3, &[linear]).unwrap();
+        self.light_buffer.update(4, &[exponential]).unwrap();
     }
 
     pub fn set_position(&mut self, position: &Vec3)
     {
-        self.light_buffer.update(self.index_at(6), &position.to_slice()).unwrap();
+        self.light_buffer.update(6, &position.to_slice()).unwrap();
+        self.update_shadow_cameras();
     }
 
-    pub(crate) fn buffer(&self) -> &UniformBuffer
+    pub fn position(&self) -> Vec3
     {
-        &self.light_buffer
+        let p = self.light_buffer.get(6).unwrap();
+        vec3(p[0], p[1], p[2])
+    }
+
+    /// Also covers the specular highlight of the smoothest physical material at the largest exposure of eye adaptation.
+    pub fn range(&self) -> Option<f32>
+    {
+        light_range(&self.light_buffer)
+    }
+
+    pub fn is_shadows_enabled(&self) -> bool {
+        !self.shadow_cameras.is_empty()
+    }
+
+    pub fn shadow_settings(&self) -> &ShadowSettings
+    {
+        &self.shadow_settings
+    }
+
+    pub fn set_shadow_settings(&mut self, settings: ShadowSettings)
+    {
+        self.shadow_settings = settings;
+        if self.is_shadows_enabled() {
+            self.enable_shadows();
+        }
+        else {
+            self.disable_shadows();
+        }
+    }
+
+    pub fn enable_shadows(&mut self)
+    {
+        let (near, far) = (self.shadow_settings.near, self.shadow_settings.far);
+        if self.shadow_cameras.is_empty() {
+            let position = self.position();
+            for (direction, up) in cube_map_directions().iter() {
+                self.shadow_cameras.push(Camera::new_perspective(&self.gl, position, position + direction, *up,
+                                                                 degrees(90.0), 1.0, near, far));
+            }
+        }
+        for camera in self.shadow_cameras.iter_mut() {
+            camera.set_perspective_projection(degrees(90.0), 1.0, near, far);
+        }
+        let resolution = self.shadow_settings.resolution;
+        if self.shadow_texture.as_ref().map(|texture| texture.width != resolution).unwrap_or(true) {
+            self.shadow_texture = Some(Texture3D::new_as_depth_target(&self.gl, resolution).unwrap());
+        }
+        self.light_buffer.update(8, &shadow_parameters(&self.shadow_settings, true)).unwrap();
     }
 
-    pub(crate) fn light_at(&mut self, index: usize) -> &mut Self
+    pub fn disable_shadows(&mut self)
     {
-        self.index = index;
-        self
+        self.shadow_cameras.clear();
+        self.shadow_texture = None;
+        self.light_buffer.update(8, &shadow_parameters(&self.shadow_settings, false)).unwrap();
     }
 
-    fn index_at(&self, index: usize) -> usize
+    fn update_shadow_cameras(&mut self)
     {
-        self.index * 8 + index
+        let position = self.position();
+        for (camera, (direction, up)) in self.shadow_cameras.iter_mut().zip(cube_map_directions().iter()) {
+            camera.set_view(position, position + direction, *up);
+        }
+    }
+
+    pub(crate) fn shadow_pass<F>(&self, shadow_rendertarget: &RenderTarget, render_scene: &F)
+        where F: Fn(&Camera)
+    {
+        if let Some(ref texture) = self.shadow_texture
+        {
+            for (face, camera) in self.shadow_cameras.iter().enumerate() {
+                shadow_rendertarget.write_to_depth_cube_map(texture, face).unwrap();
+                shadow_rendertarget.clear_depth();
+                render_scene(camera);
+            }
+        }
     }
+
+    pub(crate) fn shadow_map(&self) -> Option<&Texture3D>
+    {
+        self.shadow_texture.as_ref()
+    }
+
+    pub(crate) fn buffer(&self) -> &UniformBuffer
+    {
+        &self.light_buffer
+    }
+}
+
+fn cube_map_directions() -> [(Vec3, Vec3); 6]
+{
+    [(vec3(1.0, 0.0, 0.0), vec3(0.0, -1.0, 0.0)),
+     (vec3(-1.0, 0.0, 0.0), vec3(0.0, -1.0, 0.0)),
+     (vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0)),
+     (vec3(0.0, -1.0, 0.0), vec3(0.0, 0.0, -1.0)),
+     (vec3(0.0, 0.0, 1.0), vec3(0.0, -1.0, 0.0)),
+     (vec3(0.0, 0.0, -1.0), vec3(0.0, -1.0, 0.0))]
 }
 
 pub struct SpotLight {
     gl: Gl,
     light_buffer: UniformBuffer,
-    shadow_texture: Texture2DArray,
-    shadow_rendertarget: RenderTarget,
-    shadow_cameras: [Option<Camera>; MAX_NO_LIGHTS],
-    index: usize
+    shadow_texture: Option<Texture2D>,
+    shadow_camera: Option<Camera>,
+    shadow_settings: ShadowSettings
 }
 
 impl SpotLight {
 
     pub(crate) fn new(gl: &Gl) -> Result<SpotLight, Error>
     {
-        let uniform_sizes: Vec<u32> = [3u32, 1, 1, 1, 1, 1, 3, 1, 3, 1, 16].iter().cloned().cycle().take(11*MAX_NO_LIGHTS).collect();
-        let shadow_texture = Texture2DArray::new_as_depth_targets(gl, 1024, 1024, MAX_NO_LIGHTS).unwrap();
-        let mut lights = SpotLight {
+        let mut light = SpotLight {
             gl: gl.clone(),
-            shadow_texture,
-            shadow_rendertarget: RenderTarget::new(gl, 0)?,
-            light_buffer: UniformBuffer::new(gl, &uniform_sizes)?,
-            shadow_cameras: [None, None, None, None],
-            index: 0};
-
-        for light_id in 0..MAX_NO_LIGHTS {
-            let light = lights.light_at(light_id);
-            light.set_intensity(0.0);
-            light.set_color(&vec3(1.0, 1.0, 1.0));
-            light.set_cutoff(0.1 * std::f32::consts::PI);
-            light.set_direction(&vec3(0.0, -1.0, 0.0));
-            light.set_position(&vec3(0.0, 0.0, 0.0));
-            light.set_attenuation(0.5, 0.05, 0.005);
-            light.disable_shadows();
-        }
-        Ok(lights)
+            light_buffer: UniformBuffer::new(gl, &[3u32, 1, 1, 1, 1, 1, 3, 1, 3, 1, 16, 8])?,
+            shadow_texture: None,
+            shadow_camera: None,
+            shadow_settings: ShadowSettings::default()};
+
+        light.set_intensity(0.0);
+        light.set_color(&vec3(1.0, 1.0, 1.0));
+        light.set_cutoff(0.1 * std::f32::consts::PI);
+        light.set_direction(&vec3(0.0, -1.0, 0.0));
+        light.set_position(&vec3(0.0, 0.0, 0.0));
+        light.set_attenuation(0.5, 0.05, 0.005);
+        light.disable_shadows();
+        Ok(light)
     }
 
     pub fn set_color(&mut self, color: &Vec3)
     {
-        self.light_buffer.update(self.index_at(0), &color.to_slice()).unwrap();
+        self.light_buffer.update(0, &color.to_slice()).unwrap();
     }
 
     pub fn set_intensity(&mut self, intensity: f32)
     {
-        self.light_buffer.update(self.index_at(1), &[intensity]).unwrap();
+        self.light_buffer.update(1, &[intensity]).unwrap();
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.light_buffer.get(1).unwrap()[0]
     }
 
     pub fn set_attenuation(&mut self, constant: f32, linear: f32, exponential: f32)
     {
-        self.light_buffer.update(self.index_at(2), &[constant]).unwrap();
-        self.light_buffer.update(self.index_at(3), &[linear]).unwrap();
-        self.light_buffer.update(self.index_at(4), &[exponential]).unwrap();
+        self.light_buffer.update(2, &[constant]).unwrap();
+        self.light_buffer.update(3, &[linear]).unwrap();
+        self.light_buffer.update(4, &[exponential]).unwrap();
     }
 
     pub fn set_position(&mut self, position: &Vec3)
     {
-        self.light_buffer.update(self.index_at(6), &position.to_slice()).unwrap();
+        self.light_buffer.update(6, &position.to_slice()).unwrap();
         self.update_shadow_camera();
     }
 
     pub fn position(&self) -> Vec3
     {
-        let p = self.light_buffer.get(self.index_at(6)).unwrap();
+        let p = self.light_buffer.get(6).unwrap();
         vec3(p[0], p[1], p[2])
     }
 
     pub fn set_cutoff(&mut self, cutoff: f32)
     {
-        self.light_buffer.update(self.index_at(7), &[cutoff]).unwrap();
+        self.light_buffer.update(7, &[cutoff]).unwrap();
         self.update_shadow_camera();
     }
 
+    pub fn cutoff(&self) -> f32
+    {
+        self.light_buffer.get(7).unwrap()[0]
+    }
+
     pub fn set_direction(&mut self, direction: &Vec3)
     {
-        self.light_buffer.update(self.index_at(8), &direction.normalize().to_slice()).unwrap();
+        self.light_buffer.update(8, &direction.normalize().to_slice()).unwrap();
         self.update_shadow_camera();
     }
 
     pub fn direction(&self) -> Vec3
     {
-        let d = self.light_buffer.get(self.index_at(8)).unwrap();
+        let d = self.light_buffer.get(8).unwrap();
         vec3(d[0], d[1], d[2])
     }
 
+    pub fn range(&self) -> Option<f32>
+    {
+        light_range(&self.light_buffer)
+    }
+
+    fn shadow_half_angle(&self) -> Radians
+    {
+        radians(self.cutoff().clamp(-1.0, 1.0).acos().clamp(0.01, 80f32.to_radians()))
+    }
+
     fn update_shadow_camera(&mut self)
     {
         let position = self.position();
         let direction = self.direction();
-        let up = compute_up_direction(direction);
-
-        let depth = 200.0;
-        let cutoff = self.light_buffer.get(self.index_at(7)).unwrap()[0];
-
-        if let Some(ref mut camera) = self.shadow_cameras[self.index]
+        let half_angle = self.shadow_half_angle();
+        let (near, far) = (self.shadow_settings.near, self.shadow_settings.far);
+        if let Some(ref mut camera) = self.shadow_camera
         {
-            camera.set_view(position, position + direction, up);
-            camera.set_perspective_projection(degrees(45.0), 2.0 * cutoff, 0.1, depth);
-        }
-        else {
-            let camera = Camera::new_perspective(&self.gl, position, position + direction, up, degrees(45.0), 2.0 * cutoff, 0.1, depth);
-            self.shadow_cameras[self.index] = Some(camera);
-        }
+            camera.set_view(position, position + direction, compute_up_direction(direction));
+            camera.set_perspective_projection((half_angle * 2.0).into(), 1.0, near, far);
+            let shadow_matrix = shadow_matrix(camera);
+            self.light_buffer.update(10, &shadow_matrix.to_slice()).unwrap();
 
-        let shadow_matrix = shadow_matrix(self.shadow_cameras[self.index].as_ref().unwrap());
-        self.light_buffer.update(self.index_at(10), &shadow_matrix.to_slice()).unwrap();
+            let texel_scale = 2.0 * half_angle.0.tan() / self.shadow_settings.resolution as f32;
+            self.light_buffer.update(9, &[texel_scale]).unwrap();
+        }
     }
 
     pub fn is_shadows_enabled(&self) -> bool {
-        self.shadow_cameras[self.index].is_some()
+        self.shadow_camera.is_some()
+    }
+
+    pub fn shadow_settings(&self) -> &ShadowSettings
+    {
+        &self.shadow_settings
+    }
+
+    pub fn set_shadow_settings(&mut self, settings: ShadowSettings)
+    {
+        self.shadow_settings = settings;
+        if self.is_shadows_enabled() {
+            self.enable_shadows();
+        }
+        else {
+            self.disable_shadows();
+        }
     }
 
     pub fn enable_shadows(&mut self)
     {
+        if self.shadow_camera.is_none() {
+            let position = self.position();
+            let direction = self.direction();
+            self.shadow_camera = Some(Camera::new_perspective(&self.gl, position, position + direction, compute_up_direction(direction),
+                                                              (self.shadow_half_angle() * 2.0).into(), 1.0, self.shadow_settings.near, self.shadow_settings.far));
+        }
+        let resolution = self.shadow_settings.resolution;
+        if self.shadow_texture.as_ref().map(|texture| texture.width != resolution).unwrap_or(true) {
+            self.shadow_texture = Some(Texture2D::new_as_depth_target(&self.gl, resolution, resolution).unwrap());
+        }
+        self.light_buffer.update(11, &shadow_parameters(&self.shadow_settings, true)).unwrap();
         self.update_shadow_camera();
     }
 
     pub fn disable_shadows(&mut self)
     {
-        self.shadow_cameras[self.index] = None;
-        self.light_buffer.update(self.index_at(10), &Mat4::from_value(0.0).to_slice()).unwrap();
+        self.shadow_camera = None;
+        self.shadow_texture = None;
+        self.light_buffer.update(10, &Mat4::from_value(0.0).to_slice()).unwrap();
+        self.light_buffer.update(11, &shadow_parameters(&self.shadow_settings, false)).unwrap();
     }
 
-    pub(crate) fn shadow_pass<F>(&self, render_scene: &F)
+    pub(crate) fn shadow_pass<F>(&self, shadow_rendertarget: &RenderTarget, render_scene: &F)
         where F: Fn(&Camera)
     {
-        for light_id in 0..MAX_NO_LIGHTS {
-            if let Some(ref camera) = self.shadow_cameras[light_id]
-            {
-                self.shadow_rendertarget.write_to_depth_array(&self.shadow_texture, light_id).unwrap();
-                self.shadow_rendertarget.clear_depth();
-                render_scene(camera);
-            }
+        if let (Some(ref camera), Some(ref texture)) = (&self.shadow_camera, &self.shadow_texture)
+        {
+            shadow_rendertarget.write_to_depth(texture).unwrap();
+            shadow_rendertarget.clear_depth();
+            render_scene(camera);
         }
     }
 
-    pub(crate) fn shadow_maps(&self) -> &Texture2DArray
+    pub(crate) fn shadow_map(&self) -> Option<&Texture2D>
     {
-        &self.shadow_texture
+        self.shadow_texture.as_ref()
     }
 
     pub(crate) fn buffer(&self) -> &UniformBuffer
     {
         &self.light_buffer
     }
+}
 
-    pub(crate) fn light_at(&mut self, index: usize) -> &mut Self
-    {
-        self.index = index;
-        self
+// The largest factor a surface reflects the light color times intensity with, which is the specular peak 1/(4 k r^4) of the
+// Cook-Torrance model at the smallest roughness r = 0.05 in light_pass.frag, where k = (r + 1)^2 / 8.
+const MAX_REFLECTANCE: f32 = 290_250.0;
+
+const MAX_EXPOSURE: f32 = 10.0;
+
+// Half of the smallest visible step of an 8-bit color channel
+const VISIBILITY_THRESHOLD: f32 = 1.0 / 510.0;
+
+fn light_range(light_buffer: &UniformBuffer) -> Option<f32>
+{
+    let color = light_buffer.get(0).unwrap().iter().cloned().fold(0.0, f32::max);
+    let intensity = light_buffer.get(1).unwrap()[0];
+    attenuation_range(intensity * color, light_buffer.get(2).unwrap()[0], light_buffer.get(3).unwrap()[0], light_buffer.get(4).unwrap()[0])
+}
+
+fn attenuation_range(radiance: f32, constant: f32, linear: f32, exponential: f32) -> Option<f32>
+{
+    let attenuation = radiance * MAX_REFLECTANCE * MAX_EXPOSURE / VISIBILITY_THRESHOLD;
+    if attenuation <= constant.max(1.0) {
+        return Some(0.0);
+    }
+    let range = if exponential > 0.0 {
+        let c = constant - attenuation;
+        (-linear + (linear * linear - 4.0 * exponential * c).sqrt()) / (2.0 * exponential)
+    }
+    else if linear > 0.0 {
+        (attenuation - constant) / linear
     }
+    else {
+        return None;
+    };
+    Some(range).filter(|range| range.is_finite())
+}
 
-    fn index_at(&self, index: usize) -> usize
-    {
-        self.index * 11 + index
+fn validate_cascade_splits(splits: &[f32]) -> Result<(), Error>
+{
+    if splits.is_empty() || splits.len() > MAX_NO_CASCADES {
+        Err(Error::InvalidShadowCascades {message: format!("The number of cascade splits {} is not between 1 and {}", splits.len(), MAX_NO_CASCADES)})?;
+    }
+    if !splits.iter().all(|split| *split > 0.0 && split.is_finite()) || !splits.windows(2).all(|pair| pair[0] < pair[1]) {
+        Err(Error::InvalidShadowCascades {message: format!("The cascade splits {:?} are not positive and increasing", splits)})?;
     }
+    Ok(())
+}
+
+fn cascade_splits(cascades: &Cascades, z_near: f32, z_far: f32) -> Vec<f32>
+{
+    let splits: Vec<f32> = match *cascades {
+        Cascades::Automatic {no_cascades, max_distance} => (1..=no_cascades).map(|i| {
+            let t = i as f32 / no_cascades as f32;
+            let logarithmic = z_near.max(0.01) * (max_distance / z_near.max(0.01)).powf(t);
+            let uniform = z_near + (max_distance - z_near) * t;
+            0.5 * logarithmic + 0.5 * uniform
+        }).collect(),
+        Cascades::Splits(ref splits) => splits.clone()
+    };
+    splits.iter().map(|split| split.max(z_near).min(z_far.max(z_near))).collect()
+}
+
+fn shadow_parameters(settings: &ShadowSettings, enabled: bool) -> [f32; 8]
+{
+    let (filter_mode, kernel_size, light_size) = match settings.filter {
+        ShadowFilter::Hard => (0.0, 1, 0.0),
+        ShadowFilter::Pcf {kernel_size} => (1.0, kernel_size, 0.0),
+        ShadowFilter::Pcss {light_size, kernel_size} => (2.0, kernel_size, light_size)
+    };
+    [settings.depth_bias, settings.normal_offset_bias, filter_mode, kernel_size.clamp(1, MAX_SHADOW_KERNEL_SIZE) as f32,
+        light_size, settings.near, settings.far, if enabled {1.0} else {0.0}]
 }
 
 fn shadow_matrix(camera: &Camera) -> Mat4
@@ -416,4 +768,52 @@ fn compute_up_direction(direction: Vec3) -> Vec3
     else {
         (vec3(1.0, 0.0, 0.0).cross(direction)).normalize()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadow_kernel_size_is_bounded()
+    {
+        let parameters = |filter| shadow_parameters(&ShadowSettings {filter, ..ShadowSettings::default()}, true);
+        assert_eq!(parameters(ShadowFilter::Pcf {kernel_size: 200})[3], MAX_SHADOW_KERNEL_SIZE as f32);
+        assert_eq!(parameters(ShadowFilter::Pcss {light_size: 0.1, kernel_size: usize::MAX})[3], MAX_SHADOW_KERNEL_SIZE as f32);
+        assert_eq!(parameters(ShadowFilter::Pcf {kernel_size: 0})[3], 1.0);
+        assert_eq!(parameters(ShadowFilter::Pcf {kernel_size: 5})[3], 5.0);
+    }
+
+    #[test]
+    fn brightest_reflection_is_invisible_beyond_the_range()
+    {
+        for &(constant, linear, exponential) in [(0.5, 0.05, 0.005), (1.0, 0.2, 0.0), (0.1, 0.0, 20.0)].iter() {
+            let range = attenuation_range(0.5, constant, linear, exponential).unwrap();
+            let attenuation = constant + linear * range + exponential * range * range;
+            let brightest = 0.5 * MAX_REFLECTANCE * MAX_EXPOSURE / attenuation;
+            assert!((brightest / VISIBILITY_THRESHOLD - 1.0).abs() < 1e-3, "{} at the range {}", brightest, range);
+        }
+        assert_eq!(attenuation_range(0.0, 0.5, 0.05, 0.005), Some(0.0));
+        assert_eq!(attenuation_range(0.5, 1.0, 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn invalid_cascade_splits()
+    {
+        assert!(validate_cascade_splits(&[2.0, 10.0, 50.0]).is_ok());
+        for splits in [&[][..], &[10.0, 2.0], &[2.0, 2.0], &[0.0, 10.0], &[-1.0], &[f32::NAN], &[1.0, f32::INFINITY], &[1.0; MAX_NO_CASCADES + 1]].iter() {
+            assert!(matches!(validate_cascade_splits(splits), Err(Error::InvalidShadowCascades {..})), "{:?}", splits);
+        }
+    }
+
+    #[test]
+    fn cascade_splits_are_increasing_within_the_view()
+    {
+        let splits = cascade_splits(&Cascades::Automatic {no_cascades: 4, max_distance: 100.0}, 0.1, 1000.0);
+        assert_eq!(splits.len(), 4);
+        assert!(splits.windows(2).all(|pair| pair[0] < pair[1]) && splits[0] > 0.1 && (splits[3] - 100.0).abs() < 1e-3);
+        assert!(splits[0] < 25.0);
+
+        assert!(cascade_splits(&Cascades::Automatic {no_cascades: 2, max_distance: 0.5}, 1.0, 100.0).iter().all(|split| *split == 1.0));
+        assert_eq!(cascade_splits(&Cascades::Splits(vec![5.0, 50.0, 500.0]), 0.1, 100.0), vec![5.0, 50.0, 100.0]);
+    }
+}
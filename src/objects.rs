@@ -2,14 +2,30 @@ pub mod shaded_vertices;
 pub mod shaded_edges;
 pub mod wireframe;
 pub mod mesh;
+pub mod instanced_mesh;
+pub mod simplification;
+pub mod lod_mesh;
+#[cfg(feature = "3d-io")]
 pub mod cpu_mesh;
 pub mod skybox;
 pub mod imposter;
+#[cfg(feature = "3d-io")]
+pub mod gltf_scene;
+#[cfg(feature = "3d-io")]
+pub mod obj_model;
 
 pub use crate::objects::shaded_vertices::*;
 pub use crate::objects::shaded_edges::*;
 pub use crate::objects::wireframe::*;
 pub use crate::objects::mesh::*;
+pub use crate::objects::instanced_mesh::*;
+pub use crate::objects::simplification::*;
+pub use crate::objects::lod_mesh::*;
+#[cfg(feature = "3d-io")]
 pub use crate::objects::cpu_mesh::*;
 pub use crate::objects::skybox::*;
-pub use crate::objects::imposter::*;
\ No newline at end of file
+pub use crate::objects::imposter::*;
+#[cfg(feature = "3d-io")]
+pub use crate::objects::gltf_scene::*;
+#[cfg(feature = "3d-io")]
+pub use crate::objects::obj_model::*;
\ No newline at end of file
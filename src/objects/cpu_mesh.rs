@@ -1,32 +1,271 @@
+use crate::*;
 
-#[cfg(feature = "3d-io")]
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(Debug)]
+pub enum Error {
+    Bincode(bincode::Error),
+    Mesh(mesh::Error),
+    WrongMagicNumber {message: String},
+    UnsupportedVersion {message: String},
+    InvalidData {message: String}
+}
+
+impl From<bincode::Error> for Error {
+    fn from(other: bincode::Error) -> Self {
+        Error::Bincode(other)
+    }
+}
+
+impl From<mesh::Error> for Error {
+    fn from(other: mesh::Error) -> Self {
+        Error::Mesh(other)
+    }
+}
+
+const MAGIC_NUMBER: u8 = 61;
+const VERSION: u8 = 3;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct CPUSubmesh {
+    pub first: u32,
+    pub count: u32,
+    pub material: Option<usize>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct CPUMaterial {
+    pub name: String,
+    pub color: [f32; 3],
+    pub diffuse_intensity: f32,
+    pub specular_intensity: f32,
+    pub specular_power: f32,
+    pub texture: Option<String>,
+    pub normal_map: Option<String>
+}
+
+impl Default for CPUMaterial {
+    fn default() -> Self
+    {
+        CPUMaterial { name: String::new(), color: [1.0, 1.0, 1.0], diffuse_intensity: 0.5, specular_intensity: 0.2, specular_power: 6.0, texture: None, normal_map: None }
+    }
+}
+
+/// The `.3d` format is a magic number and a version byte followed by the bincode serialization of the mesh.
+/// All versions are read, but the current version 3 is always written.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct CPUMesh {
-    pub magic_number: u8,
-    pub version: u8,
     pub indices: Vec<u32>,
     pub positions: Vec<f32>,
-    pub normals: Vec<f32>
+    pub normals: Vec<f32>,
+    pub uvs: Option<Vec<f32>>,
+    pub tangents: Option<Vec<f32>>,
+    pub colors: Option<Vec<f32>>,
+    pub submeshes: Vec<CPUSubmesh>,
+    pub materials: Vec<CPUMaterial>
+}
+
+#[derive(serde::Deserialize)]
+struct CPUMeshVersion1 {
+    indices: Vec<u32>,
+    positions: Vec<f32>,
+    normals: Vec<f32>
+}
+
+#[derive(serde::Deserialize)]
+struct CPUMeshVersion2 {
+    indices: Vec<u32>,
+    positions: Vec<f32>,
+    normals: Vec<f32>,
+    uvs: Option<Vec<f32>>
 }
 
-#[cfg(feature = "3d-io")]
 impl CPUMesh {
-    pub fn new(bytes: &[u8]) -> Result<CPUMesh, bincode::Error>
+
+    pub fn new(bytes: &[u8]) -> Result<CPUMesh, Error>
+    {
+        if bytes.len() < 2 {
+            Err(Error::InvalidData {message: "The data is too short to contain a mesh".to_string()})?;
+        }
+        if bytes[0] != MAGIC_NUMBER {
+            Err(Error::WrongMagicNumber {message: format!("Expected the magic number {} but got {}", MAGIC_NUMBER, bytes[0])})?;
+        }
+        let content = &bytes[2..];
+        let truncated = |version: u8| move |_| Error::InvalidData {message: format!("The data of the version {} mesh is truncated or corrupt", version)};
+        let mesh = match bytes[1] {
+            1 => {
+                let decoded: CPUMeshVersion1 = bincode::deserialize(content).map_err(truncated(1))?;
+                CPUMesh { indices: decoded.indices, positions: decoded.positions, normals: decoded.normals, ..Default::default() }
+            },
+            2 => {
+                let decoded: CPUMeshVersion2 = bincode::deserialize(content).map_err(truncated(2))?;
+                CPUMesh { indices: decoded.indices, positions: decoded.positions, normals: decoded.normals, uvs: decoded.uvs, ..Default::default() }
+            },
+            VERSION => bincode::deserialize(content).map_err(truncated(VERSION))?,
+            version => Err(Error::UnsupportedVersion {message: format!("The version {} is not supported, the newest supported version is {}", version, VERSION)})?
+        };
+        mesh.validate()?;
+        Ok(mesh)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error>
+    {
+        self.validate()?;
+        let mut bytes = vec![MAGIC_NUMBER, VERSION];
+        bytes.extend(bincode::serialize(self)?);
+        Ok(bytes)
+    }
+
+    pub fn to_mesh(&self, gl: &Gl) -> Result<Mesh, Error>
+    {
+        self.validate()?;
+        self.mesh_with_indices(gl, &self.indices)
+    }
+
+    pub fn to_meshes(&self, gl: &Gl) -> Result<Vec<Mesh>, Error>
+    {
+        self.validate()?;
+        if self.submeshes.is_empty() {
+            return Ok(vec![self.mesh_with_indices(gl, &self.indices)?]);
+        }
+        let mut meshes = Vec::new();
+        for submesh in self.submeshes.iter() {
+            let first = submesh.first as usize;
+            let mut mesh = self.mesh_with_indices(gl, &self.indices[first..first + submesh.count as usize])?;
+            if let Some(material) = submesh.material.map(|index| &self.materials[index]) {
+                mesh.color = vec3(material.color[0], material.color[1], material.color[2]);
+                mesh.diffuse_intensity = material.diffuse_intensity;
+                mesh.specular_intensity = material.specular_intensity;
+                mesh.specular_power = material.specular_power;
+            }
+            meshes.push(mesh);
+        }
+        Ok(meshes)
+    }
+
+    /// The fractions must be decreasing.
+    pub fn lod_indices(&self, triangle_fractions: &[f32]) -> Vec<Vec<u32>>
+    {
+        let no_triangles = self.indices.len() / 3;
+        let mut levels: Vec<Vec<u32>> = Vec::new();
+        for fraction in triangle_fractions {
+            let previous = levels.last().unwrap_or(&self.indices);
+            let target = (no_triangles as f32 * fraction).round() as usize;
+            levels.push(simplify(previous, &self.positions, target));
+        }
+        levels
+    }
+
+    pub fn to_mesh_with_lods(&self, gl: &Gl, lods: &[(f32, f32)]) -> Result<Mesh, Error>
+    {
+        let mut mesh = self.to_mesh(gl)?;
+        let fractions: Vec<f32> = lods.iter().map(|(fraction, _)| *fraction).collect();
+        for (indices, (_, screen_size)) in self.lod_indices(&fractions).iter().zip(lods.iter()) {
+            mesh.add_lod(indices, *screen_size)?;
+        }
+        Ok(mesh)
+    }
+
+    fn mesh_with_indices(&self, gl: &Gl, indices: &[u32]) -> Result<Mesh, Error>
+    {
+        Ok(match (&self.uvs, &self.tangents) {
+            (Some(ref uvs), Some(ref tangents)) => Mesh::new_with_tangents(gl, indices, &self.positions, &self.normals, uvs, tangents)?,
+            (Some(ref uvs), None) => Mesh::new_with_uvs(gl, indices, &self.positions, &self.normals, uvs)?,
+            _ => Mesh::new(gl, indices, &self.positions, &self.normals)?
+        })
+    }
+
+    fn validate(&self) -> Result<(), Error>
+    {
+        let no_vertices = self.positions.len() / 3;
+        let check_stream = |name: &str, length: usize, components: usize| {
+            if length != no_vertices * components {
+                Err(Error::InvalidData {message: format!("The {} have {} values, but {} are expected for {} vertices", name, length, no_vertices * components, no_vertices)})
+            } else { Ok(()) }
+        };
+        check_stream("positions", self.positions.len(), 3)?;
+        check_stream("normals", self.normals.len(), 3)?;
+        if let Some(ref uvs) = self.uvs { check_stream("uv coordinates", uvs.len(), 2)?; }
+        if let Some(ref tangents) = self.tangents { check_stream("tangents", tangents.len(), 4)?; }
+        if let Some(ref colors) = self.colors { check_stream("colors", colors.len(), 4)?; }
+
+        if self.indices.len() % 3 != 0 {
+            Err(Error::InvalidData {message: format!("The number of indices {} is not a multiple of three", self.indices.len())})?;
+        }
+        if let Some(index) = self.indices.iter().find(|index| **index as usize >= no_vertices) {
+            Err(Error::InvalidData {message: format!("The index {} is out of range for {} vertices", index, no_vertices)})?;
+        }
+        for submesh in self.submeshes.iter() {
+            if submesh.first % 3 != 0 || submesh.count % 3 != 0 || submesh.first as u64 + submesh.count as u64 > self.indices.len() as u64 {
+                Err(Error::InvalidData {message: format!("The submesh from index {} with {} indices does not consist of whole triangles within the {} indices",
+                                                         submesh.first, submesh.count, self.indices.len())})?;
+            }
+            if submesh.material.map(|index| index >= self.materials.len()).unwrap_or(false) {
+                Err(Error::InvalidData {message: format!("A submesh refers to a material which is not among the {} materials", self.materials.len())})?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suzanne_with_submeshes() -> CPUMesh
     {
-        let decoded: CPUMesh = bincode::deserialize(bytes)?;
-        if decoded.magic_number != 61 {
-            Err(bincode::Error::new(bincode::ErrorKind::Custom("Corrupt file!".to_string())))?;
+        let mut cpu_mesh = CPUMesh::new(include_bytes!("../../examples/assets/models/suzanne.3d")).unwrap();
+        let half = (cpu_mesh.indices.len() / 6 * 3) as u32;
+        cpu_mesh.materials = vec![CPUMaterial { name: "red".to_string(), color: [1.0, 0.2, 0.2], ..Default::default() },
+                                  CPUMaterial { name: "blue".to_string(), color: [0.2, 0.4, 1.0], specular_intensity: 0.8, specular_power: 20.0, ..Default::default() }];
+        cpu_mesh.submeshes = vec![CPUSubmesh { first: 0, count: half, material: Some(0) },
+                                  CPUSubmesh { first: half, count: cpu_mesh.indices.len() as u32 - half, material: Some(1) }];
+        cpu_mesh
+    }
+
+    #[test]
+    fn reads_version_1()
+    {
+        let cpu_mesh = CPUMesh::new(include_bytes!("../../examples/assets/models/suzanne.3d")).unwrap();
+        assert!(cpu_mesh.uvs.is_none() && cpu_mesh.submeshes.is_empty() && cpu_mesh.materials.is_empty());
+    }
+
+    #[test]
+    fn round_trip_in_current_version()
+    {
+        let cpu_mesh = suzanne_with_submeshes();
+        let written = cpu_mesh.to_bytes().unwrap();
+        assert_eq!(&written[0..2], &[MAGIC_NUMBER, VERSION]);
+        assert_eq!(CPUMesh::new(&written).unwrap(), cpu_mesh);
+    }
+
+    #[test]
+    fn invalid_files()
+    {
+        assert!(matches!(CPUMesh::new(&[62, 1, 0, 0]), Err(Error::WrongMagicNumber {..})));
+        assert!(matches!(CPUMesh::new(&[61, 99, 0, 0]), Err(Error::UnsupportedVersion {..})));
+        let bytes = include_bytes!("../../examples/assets/models/suzanne.3d");
+        match CPUMesh::new(&bytes[..100]) {
+            Err(Error::InvalidData {message}) => assert!(message.contains("version 1") && message.contains("truncated")),
+            result => panic!("Expected invalid data but got {:?}", result.map(|_| ()))
         }
-        Ok(decoded)
     }
 
-    pub fn to_bytes(&self) -> Result<Vec<u8>, bincode::Error>
+    #[test]
+    fn invalid_meshes()
     {
-        bincode::serialize(self)
+        let cpu_mesh = suzanne_with_submeshes();
+        let mut invalid = cpu_mesh.clone();
+        invalid.uvs = Some(vec![0.0; 4]);
+        assert!(matches!(invalid.to_bytes(), Err(Error::InvalidData {..})));
+        invalid = cpu_mesh.clone();
+        invalid.submeshes[1].material = Some(2);
+        assert!(matches!(invalid.to_bytes(), Err(Error::InvalidData {..})));
     }
 
-    pub fn to_mesh(&self, gl: &crate::Gl) -> Result<crate::Mesh, crate::mesh::Error>
+    #[test]
+    fn submesh_range_does_not_overflow()
     {
-        crate::Mesh::new( &gl, &self.indices, &self.positions, &self.normals)
+        let mut invalid = suzanne_with_submeshes();
+        invalid.submeshes[1] = CPUSubmesh { first: u32::MAX - 2, count: 6, material: None };
+        assert!(matches!(invalid.to_bytes(), Err(Error::InvalidData {..})));
     }
-}
\ No newline at end of file
+}
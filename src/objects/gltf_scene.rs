@@ -0,0 +1,400 @@
+use crate::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub enum Error {
+    GLTF(gltf::Error),
+    Base64(base64::DecodeError),
+    IO(std::io::Error),
+    Texture(texture::Error),
+    Mesh(mesh::Error),
+    Renderer(renderer::Error),
+    MissingResource {message: String}
+}
+
+impl From<gltf::Error> for Error {
+    fn from(other: gltf::Error) -> Self {
+        Error::GLTF(other)
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(other: base64::DecodeError) -> Self {
+        Error::Base64(other)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(other: std::io::Error) -> Self {
+        Error::IO(other)
+    }
+}
+
+impl From<texture::Error> for Error {
+    fn from(other: texture::Error) -> Self {
+        Error::Texture(other)
+    }
+}
+
+impl From<mesh::Error> for Error {
+    fn from(other: mesh::Error) -> Self {
+        Error::Mesh(other)
+    }
+}
+
+impl From<renderer::Error> for Error {
+    fn from(other: renderer::Error) -> Self {
+        Error::Renderer(other)
+    }
+}
+
+pub struct GLTFPrimitive {
+    pub indices: Vec<u32>,
+    pub positions: Vec<f32>,
+    pub normals: Vec<f32>,
+    pub uvs: Option<Vec<f32>>,
+    pub tangents: Option<Vec<f32>>,
+    pub material: Option<usize>
+}
+
+pub struct GLTFMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GLTFPrimitive>
+}
+
+pub struct GLTFMaterial {
+    pub name: Option<String>,
+    pub physical_material: PhysicalMaterial,
+    pub base_color_texture: Option<usize>,
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub occlusion_texture: Option<usize>,
+    pub emissive_texture: Option<usize>
+}
+
+pub struct GLTFNode {
+    pub name: Option<String>,
+    pub transformation: Mat4,
+    pub world_transformation: Mat4,
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+    pub camera: Option<usize>,
+    pub light: Option<usize>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GLTFLightType {
+    Directional,
+    Point,
+    Spot {inner_cone_angle: f32, outer_cone_angle: f32}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GLTFLight {
+    pub name: Option<String>,
+    pub light_type: GLTFLightType,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: Option<f32>,
+    pub position: Vec3,
+    pub direction: Vec3
+}
+
+pub struct GLTFScene {
+    pub meshes: Vec<GLTFMesh>,
+    pub materials: Vec<GLTFMaterial>,
+    pub textures: Vec<Rc<Texture2D>>,
+    pub nodes: Vec<GLTFNode>,
+    pub roots: Vec<usize>,
+    /// If the aspect ratio is not specified in the file it is 1, use `Camera::set_size` to change it.
+    pub cameras: Vec<Camera>,
+    pub lights: Vec<GLTFLight>
+}
+
+impl GLTFScene {
+
+    pub fn new_from_bytes(gl: &Gl, bytes: &[u8]) -> Result<GLTFScene, Error>
+    {
+        Self::new_from_bytes_with_resources(gl, bytes, &HashMap::new())
+    }
+
+    pub fn new_from_bytes_with_resources(gl: &Gl, bytes: &[u8], resources: &HashMap<&str, &[u8]>) -> Result<GLTFScene, Error>
+    {
+        Self::load(gl, bytes, &|uri| resources.get(uri).map(|bytes| bytes.to_vec())
+            .ok_or_else(|| Error::MissingResource {message: format!("The resource {} is not given", uri)}))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_from_file(gl: &Gl, path: &str) -> Result<GLTFScene, Error>
+    {
+        let path = std::path::Path::new(path);
+        let directory = path.parent().unwrap_or_else(|| std::path::Path::new(""));
+        Self::load(gl, &std::fs::read(path)?, &|uri| Ok(std::fs::read(directory.join(uri))?))
+    }
+
+    fn load(gl: &Gl, bytes: &[u8], resolve: &dyn Fn(&str) -> Result<Vec<u8>, Error>) -> Result<GLTFScene, Error>
+    {
+        let gltf = gltf::Gltf::from_slice(bytes)?;
+
+        let mut buffers = Vec::new();
+        for buffer in gltf.buffers() {
+            let data = match buffer.source() {
+                gltf::buffer::Source::Bin => gltf.blob.clone()
+                    .ok_or_else(|| Error::MissingResource {message: "The binary chunk is missing".to_string()})?,
+                gltf::buffer::Source::Uri(uri) => load_uri(uri, resolve)?
+            };
+            buffers.push(data);
+        }
+
+        let mut textures = Vec::new();
+        for texture in gltf.textures() {
+            let bytes = match texture.source().source() {
+                gltf::image::Source::View {view, ..} => {
+                    let buffer = &buffers[view.buffer().index()];
+                    buffer[view.offset()..view.offset() + view.length()].to_vec()
+                },
+                gltf::image::Source::Uri {uri, ..} => load_uri(uri, resolve)?
+            };
+            textures.push(Rc::new(texture_with_sampler(gl, &bytes, &texture.sampler())?));
+        }
+
+        let materials = gltf.materials().map(|material| {
+            let pbr = material.pbr_metallic_roughness();
+            let base_color = pbr.base_color_factor();
+            let emissive = material.emissive_factor();
+            GLTFMaterial {
+                name: material.name().map(|name| name.to_string()),
+                physical_material: PhysicalMaterial {
+                    albedo: vec3(base_color[0], base_color[1], base_color[2]),
+                    metallic: pbr.metallic_factor(),
+                    roughness: pbr.roughness_factor(),
+                    ambient_occlusion: 1.0,
+                    emissive: vec3(emissive[0], emissive[1], emissive[2])
+                },
+                base_color_texture: pbr.base_color_texture().map(|info| info.texture().index()),
+                metallic_roughness_texture: pbr.metallic_roughness_texture().map(|info| info.texture().index()),
+                normal_texture: material.normal_texture().map(|info| info.texture().index()),
+                occlusion_texture: material.occlusion_texture().map(|info| info.texture().index()),
+                emissive_texture: material.emissive_texture().map(|info| info.texture().index())
+            }
+        }).collect();
+
+        let mut meshes = Vec::new();
+        for mesh in gltf.meshes() {
+            let mut primitives = Vec::new();
+            for primitive in mesh.primitives() {
+                if let Some(primitive) = read_primitive(&primitive, &buffers) {
+                    primitives.push(primitive);
+                }
+            }
+            meshes.push(GLTFMesh { name: mesh.name().map(|name| name.to_string()), primitives });
+        }
+
+        let mut parents = vec![None; gltf.nodes().len()];
+        for node in gltf.nodes() {
+            for child in node.children() {
+                parents[child.index()] = Some(node.index());
+            }
+        }
+        let local_transformations: Vec<Mat4> = gltf.nodes().map(|node| Mat4::from(node.transform().matrix())).collect();
+        let world_transformation = |mut index: usize| {
+            let mut transformation = local_transformations[index];
+            while let Some(parent) = parents[index] {
+                transformation = local_transformations[parent] * transformation;
+                index = parent;
+            }
+            transformation
+        };
+
+        let mut nodes = Vec::new();
+        let mut cameras = Vec::new();
+        let mut lights = Vec::new();
+        for node in gltf.nodes() {
+            let world_transformation = world_transformation(node.index());
+            let position = (world_transformation * vec4(0.0, 0.0, 0.0, 1.0)).truncate();
+            let direction = (world_transformation * vec4(0.0, 0.0, -1.0, 0.0)).truncate().normalize();
+            let up = (world_transformation * vec4(0.0, 1.0, 0.0, 0.0)).truncate().normalize();
+
+            let camera = node.camera().map(|camera| {
+                cameras.push(match camera.projection() {
+                    gltf::camera::Projection::Perspective(perspective) =>
+                        Camera::new_perspective(gl, position, position + direction, up, radians(perspective.yfov()).into(),
+                                                perspective.aspect_ratio().unwrap_or(1.0), perspective.znear(), perspective.zfar().unwrap_or(1000.0)),
+                    gltf::camera::Projection::Orthographic(orthographic) =>
+                        Camera::new_orthographic(gl, position, position + direction, up, 2.0 * orthographic.xmag(), 2.0 * orthographic.ymag(),
+                                                 orthographic.zfar())
+                });
+                cameras.len() - 1
+            });
+
+            let light = node.light().map(|light| {
+                let color = light.color();
+                lights.push(GLTFLight {
+                    name: light.name().map(|name| name.to_string()),
+                    light_type: match light.kind() {
+                        gltf::khr_lights_punctual::Kind::Directional => GLTFLightType::Directional,
+                        gltf::khr_lights_punctual::Kind::Point => GLTFLightType::Point,
+                        gltf::khr_lights_punctual::Kind::Spot {inner_cone_angle, outer_cone_angle} => GLTFLightType::Spot {inner_cone_angle, outer_cone_angle}
+                    },
+                    color: vec3(color[0], color[1], color[2]),
+                    intensity: light.intensity(),
+                    range: light.range(),
+                    position,
+                    direction
+                });
+                lights.len() - 1
+            });
+
+            nodes.push(GLTFNode {
+                name: node.name().map(|name| name.to_string()),
+                transformation: local_transformations[node.index()],
+                world_transformation,
+                children: node.children().map(|child| child.index()).collect(),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                camera,
+                light
+            });
+        }
+
+        let roots = match gltf.default_scene().or_else(|| gltf.scenes().next()) {
+            Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+            None => (0..nodes.len()).filter(|index| parents[*index].is_none()).collect()
+        };
+
+        Ok(GLTFScene { meshes, materials, textures, nodes, roots, cameras, lights })
+    }
+
+    pub fn to_meshes(&self, gl: &Gl) -> Result<Vec<(Mesh, Mat4)>, Error>
+    {
+        let mut meshes = Vec::new();
+        let mut stack = self.roots.clone();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if let Some(mesh_index) = node.mesh {
+                for primitive in self.meshes[mesh_index].primitives.iter() {
+                    let mut mesh = match (&primitive.uvs, &primitive.tangents) {
+                        (Some(ref uvs), Some(ref tangents)) => Mesh::new_with_tangents(gl, &primitive.indices, &primitive.positions, &primitive.normals, uvs, tangents)?,
+                        (Some(ref uvs), None) => Mesh::new_with_uvs(gl, &primitive.indices, &primitive.positions, &primitive.normals, uvs)?,
+                        _ => Mesh::new(gl, &primitive.indices, &primitive.positions, &primitive.normals)?
+                    };
+                    let material = primitive.material.map(|material| &self.materials[material]);
+                    mesh.physical_material = Some(material.map(|material| material.physical_material.clone()).unwrap_or_default());
+                    mesh.texture = material.and_then(|material| material.base_color_texture).map(|texture| self.textures[texture].clone());
+                    mesh.normal_map = material.and_then(|material| material.normal_texture).map(|texture| self.textures[texture].clone());
+                    meshes.push((mesh, node.world_transformation));
+                }
+            }
+            stack.extend(&node.children);
+        }
+        Ok(meshes)
+    }
+
+    /// The lights are added after the lights the renderer already has.
+    pub fn add_lights(&self, renderer: &mut DeferredPipeline) -> Result<(), Error>
+    {
+        for light in self.lights.iter() {
+            match light.light_type {
+                GLTFLightType::Directional => {
+                    let index = renderer.no_directional_lights();
+                    let directional_light = renderer.directional_light(index)?;
+                    directional_light.set_color(&light.color);
+                    directional_light.set_intensity(light.intensity);
+                    directional_light.set_direction(&light.direction);
+                },
+                GLTFLightType::Point => {
+                    let index = renderer.no_point_lights();
+                    let point_light = renderer.point_light(index)?;
+                    point_light.set_color(&light.color);
+                    point_light.set_intensity(light.intensity);
+                    point_light.set_attenuation(1.0, 0.0, 1.0);
+                    point_light.set_position(&light.position);
+                },
+                GLTFLightType::Spot {outer_cone_angle, ..} => {
+                    let index = renderer.no_spot_lights();
+                    let spot_light = renderer.spot_light(index)?;
+                    spot_light.set_color(&light.color);
+                    spot_light.set_intensity(light.intensity);
+                    spot_light.set_attenuation(1.0, 0.0, 1.0);
+                    spot_light.set_position(&light.position);
+                    spot_light.set_direction(&light.direction);
+                    spot_light.set_cutoff(outer_cone_angle.cos());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn load_uri(uri: &str, resolve: &dyn Fn(&str) -> Result<Vec<u8>, Error>) -> Result<Vec<u8>, Error>
+{
+    if uri.starts_with("data:") {
+        let start = uri.find(',')
+            .ok_or_else(|| Error::MissingResource {message: "The data URI has no data".to_string()})?;
+        Ok(base64::decode(&uri[start + 1..])?)
+    }
+    else {
+        resolve(uri)
+    }
+}
+
+fn texture_with_sampler(gl: &Gl, bytes: &[u8], sampler: &gltf::texture::Sampler) -> Result<Texture2D, Error>
+{
+    use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+    let wrapping = |mode| match mode {
+        WrappingMode::Repeat => Wrapping::Repeat,
+        WrappingMode::MirroredRepeat => Wrapping::MirroredRepeat,
+        WrappingMode::ClampToEdge => Wrapping::ClampToEdge
+    };
+    let (min_filter, mip_map_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) => (Interpolation::Nearest, None),
+        Some(MinFilter::Linear) => (Interpolation::Linear, None),
+        Some(MinFilter::NearestMipmapNearest) => (Interpolation::Nearest, Some(Interpolation::Nearest)),
+        Some(MinFilter::LinearMipmapNearest) => (Interpolation::Linear, Some(Interpolation::Nearest)),
+        Some(MinFilter::NearestMipmapLinear) => (Interpolation::Nearest, Some(Interpolation::Linear)),
+        Some(MinFilter::LinearMipmapLinear) | None => (Interpolation::Linear, Some(Interpolation::Linear))
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => Interpolation::Nearest,
+        Some(MagFilter::Linear) | None => Interpolation::Linear
+    };
+
+    let texture = Texture2D::new_from_bytes(gl, bytes)?;
+    texture.set_wrapping(wrapping(sampler.wrap_s()), wrapping(sampler.wrap_t()));
+    texture.set_interpolation(min_filter, mag_filter, mip_map_filter);
+    Ok(texture)
+}
+
+fn read_primitive(primitive: &gltf::Primitive, buffers: &[Vec<u8>]) -> Option<GLTFPrimitive>
+{
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data[..]));
+    let positions: Vec<f32> = reader.read_positions()?.flatten().collect();
+    let no_vertices = positions.len() / 3;
+    let vertex_indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..no_vertices as u32).collect()
+    };
+    let indices = match primitive.mode() {
+        gltf::mesh::Mode::Triangles => vertex_indices,
+        gltf::mesh::Mode::TriangleStrip => (0..vertex_indices.len().saturating_sub(2)).flat_map(|i| {
+            if i % 2 == 0 { vec![vertex_indices[i], vertex_indices[i + 1], vertex_indices[i + 2]] }
+            else { vec![vertex_indices[i + 1], vertex_indices[i], vertex_indices[i + 2]] }
+        }).collect(),
+        gltf::mesh::Mode::TriangleFan => (1..vertex_indices.len().saturating_sub(1)).flat_map(|i| {
+            vec![vertex_indices[0], vertex_indices[i], vertex_indices[i + 1]]
+        }).collect(),
+        _ => return None
+    };
+    let normals = match reader.read_normals() {
+        Some(normals) => normals.flatten().collect(),
+        None => compute_normals(&indices, &positions)
+    };
+    Some(GLTFPrimitive {
+        indices,
+        positions,
+        normals,
+        uvs: reader.read_tex_coords(0).map(|uvs| uvs.into_f32().flatten().collect()),
+        tangents: reader.read_tangents().map(|tangents| tangents.flatten().collect()),
+        material: primitive.material().index()
+    })
+}
@@ -23,9 +23,9 @@ impl Imposter {
 
         let texture_width = (max_texture_size as f32 * (width / height).min(1.0)) as usize;
         let texture_height = (max_texture_size as f32 * (height / width).min(1.0)) as usize;
-        let texture = Texture2DArray::new_as_color_targets(gl, texture_width, texture_height, NO_VIEW_ANGLES*2).unwrap();
+        let texture = Texture2DArray::new_as_color_targets(gl, texture_width, texture_height, NO_VIEW_ANGLES*GEOMETRY_PASS_LAYERS).unwrap();
         let depth_texture = Texture2DArray::new_as_depth_targets(gl, texture_width, texture_height, NO_VIEW_ANGLES).unwrap();
-        let rendertarget = RenderTarget::new(gl, 2).unwrap();
+        let rendertarget = RenderTarget::new(gl, GEOMETRY_PASS_LAYERS).unwrap();
 
         for i in 0..NO_VIEW_ANGLES {
             let angle = i as f32 * 2.0 * PI / NO_VIEW_ANGLES as f32;
@@ -0,0 +1,162 @@
+use crate::*;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub enum Error {
+    Program(program::Error),
+    Buffer(buffer::Error),
+    InvalidData {message: String}
+}
+
+impl From<program::Error> for Error {
+    fn from(other: program::Error) -> Self {
+        Error::Program(other)
+    }
+}
+
+impl From<buffer::Error> for Error {
+    fn from(other: buffer::Error) -> Self {
+        Error::Buffer(other)
+    }
+}
+
+pub struct InstancedMesh {
+    position_buffer: VertexBuffer,
+    normal_buffer: VertexBuffer,
+    index_buffer: ElementBuffer,
+    instance_buffer: VertexBuffer,
+    instance_count: u32,
+    program: program::Program,
+    mesh_aabb: AxisAllignedBoundingBox,
+    aabb: AxisAllignedBoundingBox,
+    pub color: Vec3,
+    pub texture: Option<Rc<texture::Texture2D>>,
+    pub diffuse_intensity: f32,
+    pub specular_intensity: f32,
+    pub specular_power: f32,
+    pub physical_material: Option<PhysicalMaterial>
+}
+
+impl InstancedMesh
+{
+    pub fn new(gl: &Gl, indices: &[u32], positions: &[f32], normals: &[f32]) -> Result<Self, Error>
+    {
+        let position_buffer = VertexBuffer::new_with_one_static_attribute(gl, positions)?;
+        let normal_buffer = VertexBuffer::new_with_one_static_attribute(gl, normals)?;
+        let index_buffer = ElementBuffer::new_with(gl, indices)?;
+        let instance_buffer = VertexBuffer::new(gl)?;
+
+        let program = program::Program::from_source(gl,
+                                                    include_str!("shaders/mesh_instanced.vert"),
+                                                    &format!("#define INSTANCED\n{}", include_str!("shaders/shaded.frag")))?;
+
+        let mesh_aabb = compute_aabb(positions);
+        let aabb = AxisAllignedBoundingBox {min: vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY), max: vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)};
+        Ok(InstancedMesh { position_buffer, normal_buffer, index_buffer, instance_buffer, instance_count: 0, program, mesh_aabb, aabb,
+            color: vec3(1.0, 1.0, 1.0), texture: None, diffuse_intensity: 0.5, specular_intensity: 0.2, specular_power: 6.0, physical_material: None })
+    }
+
+    pub fn update_instances(&mut self, transformations: &[Mat4], colors: Option<&[Vec3]>) -> Result<(), Error>
+    {
+        if let Some(colors) = colors {
+            if colors.len() != transformations.len() {
+                Err(Error::InvalidData {message: format!("There are {} colors for {} instances", colors.len(), transformations.len())})?;
+            }
+        }
+        let mut columns: Vec<Vec<f32>> = (0..4).map(|_| Vec::with_capacity(4 * transformations.len())).collect();
+        let mut normal_columns: Vec<Vec<f32>> = (0..3).map(|_| Vec::with_capacity(3 * transformations.len())).collect();
+        let mut aabb = AxisAllignedBoundingBox {min: vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY), max: vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)};
+        for transformation in transformations.iter() {
+            for (i, column) in columns.iter_mut().enumerate() {
+                let c = transformation[i];
+                column.extend_from_slice(&[c.x, c.y, c.z, c.w]);
+            }
+            let normal_matrix = normal_matrix(transformation);
+            for (i, column) in normal_columns.iter_mut().enumerate() {
+                column.extend_from_slice(&normal_matrix[i].to_slice());
+            }
+            aabb = aabb.add(&self.mesh_aabb.transform(transformation));
+        }
+        for column in columns.iter() {
+            self.instance_buffer.add(column);
+        }
+        match colors {
+            Some(colors) => self.instance_buffer.add(&colors.iter().flat_map(|color| color.to_slice().to_vec()).collect::<Vec<f32>>()),
+            None => self.instance_buffer.add(&vec![1.0; 3 * transformations.len()])
+        }
+        for column in normal_columns.iter() {
+            self.instance_buffer.add(column);
+        }
+        self.instance_buffer.send_dynamic_data();
+        self.instance_count = transformations.len() as u32;
+        self.aabb = aabb;
+        Ok(())
+    }
+
+    pub fn instance_count(&self) -> u32
+    {
+        self.instance_count
+    }
+
+    pub fn render(&self, transformation: &Mat4, camera: &camera::Camera)
+    {
+        if self.instance_count == 0 {
+            return;
+        }
+        if let Some(ref material) = self.physical_material
+        {
+            self.program.add_uniform_int("use_physical_material", &1).unwrap();
+            self.program.add_uniform_float("metallic", &material.metallic).unwrap();
+            self.program.add_uniform_float("roughness", &material.roughness).unwrap();
+            self.program.add_uniform_float("occlusion", &material.ambient_occlusion).unwrap();
+            self.program.add_uniform_vec3("emissive", &material.emissive).unwrap();
+        }
+        else {
+            self.program.add_uniform_int("use_physical_material", &0).unwrap();
+            self.program.add_uniform_float("diffuse_intensity", &self.diffuse_intensity).unwrap();
+            self.program.add_uniform_float("specular_intensity", &self.specular_intensity).unwrap();
+            self.program.add_uniform_float("specular_power", &self.specular_power).unwrap();
+        }
+
+        if let Some(ref tex) = self.texture
+        {
+            self.program.add_uniform_int("use_texture", &1).unwrap();
+            self.program.use_texture(tex.as_ref(),"tex").unwrap();
+            self.program.add_uniform_int("use_uvs", &0).unwrap();
+        }
+        else {
+            self.program.add_uniform_int("use_texture", &0).unwrap();
+            let color = self.physical_material.as_ref().map(|material| material.albedo).unwrap_or(self.color);
+            self.program.add_uniform_vec3("meshColor", &color).unwrap();
+        }
+        self.program.add_uniform_int("use_normal_map", &0).unwrap();
+
+        self.program.add_uniform_mat4("modelMatrix", transformation).unwrap();
+        self.program.add_uniform_mat4("normalMatrix", &transformation.invert().unwrap().transpose()).unwrap();
+        self.program.use_uniform_block(camera.matrix_buffer(), "Camera");
+
+        self.program.use_attribute_vec3_float(&self.position_buffer, "position", 0).unwrap();
+        self.program.use_attribute_vec3_float(&self.normal_buffer, "normal", 0).unwrap();
+        self.program.use_attribute_vec4_float_divisor(&self.instance_buffer, "transformation_column0", 0, 1).unwrap();
+        self.program.use_attribute_vec4_float_divisor(&self.instance_buffer, "transformation_column1", 1, 1).unwrap();
+        self.program.use_attribute_vec4_float_divisor(&self.instance_buffer, "transformation_column2", 2, 1).unwrap();
+        self.program.use_attribute_vec4_float_divisor(&self.instance_buffer, "transformation_column3", 3, 1).unwrap();
+        self.program.use_attribute_vec3_float_divisor(&self.instance_buffer, "instance_color", 4, 1).unwrap();
+        self.program.use_attribute_vec3_float_divisor(&self.instance_buffer, "normal_column0", 5, 1).unwrap();
+        self.program.use_attribute_vec3_float_divisor(&self.instance_buffer, "normal_column1", 6, 1).unwrap();
+        self.program.use_attribute_vec3_float_divisor(&self.instance_buffer, "normal_column2", 7, 1).unwrap();
+
+        self.program.draw_elements_instanced(&self.index_buffer, self.instance_count);
+    }
+
+    pub fn axis_aligned_bounding_box(&self) -> &AxisAllignedBoundingBox
+    {
+        &self.aabb
+    }
+}
+
+fn normal_matrix(transformation: &Mat4) -> Mat3
+{
+    let m = Mat3::from_cols(transformation.x.truncate(), transformation.y.truncate(), transformation.z.truncate());
+    m.invert().unwrap_or(m).transpose()
+}
@@ -0,0 +1,117 @@
+use crate::*;
+
+pub struct LodMesh {
+    mesh: Mesh,
+    imposter: Option<(Imposter, f32)>,
+    transformations: Vec<Mat4>,
+    levels: Vec<usize>,
+    no_imposters: usize
+}
+
+impl LodMesh {
+
+    pub fn new(mesh: Mesh) -> LodMesh
+    {
+        LodMesh {mesh, imposter: None, transformations: Vec::new(), levels: Vec::new(), no_imposters: 0}
+    }
+
+    /// The screen size must be smaller than the screen sizes of the levels of the mesh.
+    pub fn set_imposter(&mut self, imposter: Imposter, screen_size: f32)
+    {
+        self.imposter = Some((imposter, screen_size));
+    }
+
+    pub fn mesh(&self) -> &Mesh
+    {
+        &self.mesh
+    }
+
+    pub fn mesh_mut(&mut self) -> &mut Mesh
+    {
+        &mut self.mesh
+    }
+
+    pub fn set_transformations(&mut self, transformations: &[Mat4])
+    {
+        self.transformations = transformations.to_vec();
+        self.levels = vec![0; transformations.len()];
+    }
+
+    pub fn no_lods(&self) -> usize
+    {
+        self.mesh.no_lods() + self.imposter.is_some() as usize
+    }
+
+    pub fn levels(&self) -> &[usize]
+    {
+        &self.levels
+    }
+
+    /// Should be called once per frame with the main camera, before both the shadow pass and the geometry pass.
+    pub fn update(&mut self, camera: &Camera)
+    {
+        let mut screen_sizes = self.mesh.lod_screen_sizes();
+        if let Some((_, screen_size)) = self.imposter {
+            screen_sizes.push(screen_size);
+        }
+        let aabb = *self.mesh.axis_aligned_bounding_box();
+        for (transformation, level) in self.transformations.iter().zip(self.levels.iter_mut()) {
+            *level = select_lod(projected_size(&aabb.transform(transformation), camera), &screen_sizes, *level, self.mesh.lod_hysteresis);
+        }
+
+        let imposter_level = self.mesh.no_lods();
+        if let Some((ref mut imposter, _)) = self.imposter {
+            let mut positions = Vec::new();
+            let mut angles = Vec::new();
+            for (transformation, level) in self.transformations.iter().zip(self.levels.iter()) {
+                if *level == imposter_level {
+                    positions.extend_from_slice(&[transformation.w.x, transformation.w.y, transformation.w.z]);
+                    angles.push(f32::atan2(-transformation.x.z, transformation.x.x));
+                }
+            }
+            imposter.update_positions(&positions, &angles);
+            self.no_imposters = angles.len();
+        }
+    }
+
+    pub fn render(&self, camera: &Camera)
+    {
+        for (transformation, level) in self.transformations.iter().zip(self.levels.iter()) {
+            if *level < self.mesh.no_lods() {
+                self.mesh.render_lod(*level, transformation, camera);
+            }
+        }
+        if let Some((ref imposter, _)) = self.imposter {
+            if self.no_imposters > 0 {
+                imposter.render(camera);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_level_only_beyond_the_hysteresis()
+    {
+        let screen_sizes = [1.2, 0.9, 0.75];
+        assert_eq!(select_lod(1.15, &screen_sizes, 0, 0.1), 0);
+        assert_eq!(select_lod(1.05, &screen_sizes, 0, 0.1), 1);
+        assert_eq!(select_lod(1.25, &screen_sizes, 1, 0.1), 1);
+        assert_eq!(select_lod(1.35, &screen_sizes, 1, 0.1), 0);
+        assert_eq!(select_lod(0.1, &screen_sizes, 0, 0.1), 3);
+        assert_eq!(select_lod(f32::INFINITY, &screen_sizes, 3, 0.1), 0);
+    }
+
+    #[test]
+    fn levels_get_coarser_with_distance()
+    {
+        let screen_sizes = [1.2, 0.9, 0.75, 0.65];
+        let levels: Vec<usize> = (1..12).map(|distance| select_lod(4.0 / distance as f32, &screen_sizes, 0, 0.1)).collect();
+        assert!(levels.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(levels.first(), Some(&0));
+        assert_eq!(levels.last(), Some(&4));
+    }
+}
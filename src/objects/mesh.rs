@@ -1,5 +1,6 @@
 
 use crate::*;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub enum Error {
@@ -19,6 +20,7 @@ impl From<buffer::Error> for Error {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct AxisAllignedBoundingBox {
     pub min: Vec3,
     pub max: Vec3
@@ -31,19 +33,67 @@ impl AxisAllignedBoundingBox {
             max: vec3(f32::max(self.max.x, other.max.x), f32::max(self.max.y, other.max.y), f32::max(self.max.z, other.max.z))
         }
     }
+
+    pub fn transform(&self, transformation: &Mat4) -> AxisAllignedBoundingBox {
+        if self.min.x > self.max.x {
+            return *self;
+        }
+        let mut min = vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for i in 0..8 {
+            let corner = vec3(if i & 1 == 0 {self.min.x} else {self.max.x}, if i & 2 == 0 {self.min.y} else {self.max.y}, if i & 4 == 0 {self.min.z} else {self.max.z});
+            let p = (transformation * corner.extend(1.0)).truncate();
+            min = vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        AxisAllignedBoundingBox {min, max}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicalMaterial {
+    pub albedo: Vec3,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub ambient_occlusion: f32,
+    pub emissive: Vec3
+}
+
+impl Default for PhysicalMaterial {
+    fn default() -> Self
+    {
+        PhysicalMaterial { albedo: vec3(1.0, 1.0, 1.0), metallic: 0.0, roughness: 0.5, ambient_occlusion: 1.0, emissive: vec3(0.0, 0.0, 0.0) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureMapping {
+    UV,
+    Triplanar
 }
 
 pub struct Mesh {
+    gl: Gl,
     position_buffer: VertexBuffer,
     normal_buffer: VertexBuffer,
+    uv_buffer: Option<VertexBuffer>,
+    tangent_buffer: Option<VertexBuffer>,
     index_buffer: ElementBuffer,
     program: program::Program,
     aabb: AxisAllignedBoundingBox,
+    lods: Vec<(ElementBuffer, f32)>,
     pub color: Vec3,
-    pub texture: Option<texture::Texture2D>,
+    pub texture: Option<Rc<texture::Texture2D>>,
+    pub texture_mapping: TextureMapping,
+    /// Only used when the mesh has uv coordinates and tangents.
+    pub normal_map: Option<Rc<texture::Texture2D>>,
     pub diffuse_intensity: f32,
     pub specular_intensity: f32,
-    pub specular_power: f32
+    pub specular_power: f32,
+    /// Only used in the transparent pass.
+    pub opacity: f32,
+    pub physical_material: Option<PhysicalMaterial>,
+    pub lod_hysteresis: f32
 }
 
 impl Mesh
@@ -58,8 +108,23 @@ impl Mesh
                                                     include_str!("shaders/mesh_shaded.vert"),
                                                     include_str!("shaders/shaded.frag"))?;
 
-        Ok(Mesh { index_buffer, position_buffer, normal_buffer, program, aabb: compute_aabb(positions), color: vec3(1.0, 1.0, 1.0), texture: None,
-            diffuse_intensity: 0.5, specular_intensity: 0.2, specular_power: 6.0 })
+        Ok(Mesh { gl: gl.clone(), index_buffer, position_buffer, normal_buffer, uv_buffer: None, tangent_buffer: None, program, aabb: compute_aabb(positions), color: vec3(1.0, 1.0, 1.0),
+            texture: None, texture_mapping: TextureMapping::Triplanar, normal_map: None, diffuse_intensity: 0.5, specular_intensity: 0.2, specular_power: 6.0, opacity: 1.0, physical_material: None,
+            lods: Vec::new(), lod_hysteresis: 0.1 })
+    }
+
+    pub fn new_with_uvs(gl: &Gl, indices: &[u32], positions: &[f32], normals: &[f32], uvs: &[f32]) -> Result<Self, Error>
+    {
+        Self::new_with_tangents(gl, indices, positions, normals, uvs, &compute_tangents(indices, positions, normals, uvs))
+    }
+
+    pub fn new_with_tangents(gl: &Gl, indices: &[u32], positions: &[f32], normals: &[f32], uvs: &[f32], tangents: &[f32]) -> Result<Self, Error>
+    {
+        let mut mesh = Self::new(gl, indices, positions, normals)?;
+        mesh.uv_buffer = Some(VertexBuffer::new_with_one_static_attribute(gl, uvs)?);
+        mesh.tangent_buffer = Some(VertexBuffer::new_with_one_static_attribute(gl, tangents)?);
+        mesh.texture_mapping = TextureMapping::UV;
+        Ok(mesh)
     }
 
     pub fn new_with_computed_normals(gl: &Gl, indices: &[u32], positions: &[f32]) -> Result<Self, Error>
@@ -71,6 +136,7 @@ impl Mesh
     {
         self.position_buffer.add(positions);
         self.position_buffer.send_static_data();
+        self.aabb = compute_aabb(positions);
         Ok(())
     }
 
@@ -81,20 +147,73 @@ impl Mesh
         Ok(())
     }
 
+    pub fn add_lod(&mut self, indices: &[u32], screen_size: f32) -> Result<(), Error>
+    {
+        self.lods.push((ElementBuffer::new_with(&self.gl, indices)?, screen_size));
+        Ok(())
+    }
+
+    pub fn no_lods(&self) -> usize
+    {
+        self.lods.len() + 1
+    }
+
+    pub fn lod_screen_sizes(&self) -> Vec<f32>
+    {
+        self.lods.iter().map(|(_, screen_size)| *screen_size).collect()
+    }
+
+    pub fn screen_size(&self, transformation: &Mat4, camera: &camera::Camera) -> f32
+    {
+        projected_size(&self.aabb.transform(transformation), camera)
+    }
+
+    pub fn select_lod(&self, transformation: &Mat4, camera: &camera::Camera, current: usize) -> usize
+    {
+        select_lod(self.screen_size(transformation, camera), &self.lod_screen_sizes(), current, self.lod_hysteresis)
+    }
+
     pub fn render(&self, transformation: &Mat4, camera: &camera::Camera)
     {
-        self.program.add_uniform_float("diffuse_intensity", &self.diffuse_intensity).unwrap();
-        self.program.add_uniform_float("specular_intensity", &self.specular_intensity).unwrap();
-        self.program.add_uniform_float("specular_power", &self.specular_power).unwrap();
+        self.render_lod(0, transformation, camera);
+    }
+
+    pub fn render_lod(&self, level: usize, transformation: &Mat4, camera: &camera::Camera)
+    {
+        if let Some(ref material) = self.physical_material
+        {
+            self.program.add_uniform_int("use_physical_material", &1).unwrap();
+            self.program.add_uniform_float("metallic", &material.metallic).unwrap();
+            self.program.add_uniform_float("roughness", &material.roughness).unwrap();
+            self.program.add_uniform_float("occlusion", &material.ambient_occlusion).unwrap();
+            self.program.add_uniform_vec3("emissive", &material.emissive).unwrap();
+        }
+        else {
+            self.program.add_uniform_int("use_physical_material", &0).unwrap();
+            self.program.add_uniform_float("diffuse_intensity", &self.diffuse_intensity).unwrap();
+            self.program.add_uniform_float("specular_intensity", &self.specular_intensity).unwrap();
+            self.program.add_uniform_float("specular_power", &self.specular_power).unwrap();
+        }
 
         if let Some(ref tex) = self.texture
         {
             self.program.add_uniform_int("use_texture", &1).unwrap();
-            self.program.use_texture(tex,"tex").unwrap();
+            self.program.use_texture(tex.as_ref(),"tex").unwrap();
+            let use_uvs = self.texture_mapping == TextureMapping::UV && self.uv_buffer.is_some();
+            self.program.add_uniform_int("use_uvs", &(use_uvs as i32)).unwrap();
         }
         else {
             self.program.add_uniform_int("use_texture", &0).unwrap();
-            self.program.add_uniform_vec3("color", &self.color).unwrap();
+            let color = self.physical_material.as_ref().map(|material| material.albedo).unwrap_or(self.color);
+            self.program.add_uniform_vec3("color", &color).unwrap();
+        }
+
+        match (&self.normal_map, &self.tangent_buffer) {
+            (Some(ref normal_map), Some(_)) => {
+                self.program.add_uniform_int("use_normal_map", &1).unwrap();
+                self.program.use_texture(normal_map.as_ref(),"normal_map").unwrap();
+            },
+            _ => self.program.add_uniform_int("use_normal_map", &0).unwrap()
         }
 
         self.program.add_uniform_mat4("modelMatrix", &transformation).unwrap();
@@ -104,8 +223,30 @@ impl Mesh
 
         self.program.use_attribute_vec3_float(&self.position_buffer, "position", 0).unwrap();
         self.program.use_attribute_vec3_float(&self.normal_buffer, "normal", 0).unwrap();
+        if let Some(ref uv_buffer) = self.uv_buffer {
+            self.program.use_attribute_vec2_float(uv_buffer, "uv_coordinates", 0).unwrap();
+        }
+        if let Some(ref tangent_buffer) = self.tangent_buffer {
+            self.program.use_attribute_vec4_float(tangent_buffer, "tangent", 0).unwrap();
+        }
+
+        match level.min(self.lods.len()) {
+            0 => self.program.draw_elements(&self.index_buffer),
+            level => self.program.draw_elements(&self.lods[level - 1].0)
+        }
+    }
 
-        self.program.draw_elements(&self.index_buffer);
+    pub(crate) fn render_geometry(&self, program: &program::Program, transformation: &Mat4, camera: &camera::Camera, with_normals: bool) -> Result<(), Error>
+    {
+        program.add_uniform_mat4("modelMatrix", transformation)?;
+        program.use_uniform_block(camera.matrix_buffer(), "Camera");
+        program.use_attribute_vec3_float(&self.position_buffer, "position", 0)?;
+        if with_normals {
+            program.add_uniform_mat4("normalMatrix", &transformation.invert().unwrap().transpose())?;
+            program.use_attribute_vec3_float(&self.normal_buffer, "normal", 0)?;
+        }
+        program.draw_elements(&self.index_buffer);
+        Ok(())
     }
 
     pub fn axis_aligned_bounding_box(&self) -> &AxisAllignedBoundingBox
@@ -114,7 +255,28 @@ impl Mesh
     }
 }
 
-fn compute_aabb(positions: &[f32]) -> AxisAllignedBoundingBox {
+pub(crate) fn projected_size(aabb: &AxisAllignedBoundingBox, camera: &camera::Camera) -> f32
+{
+    if aabb.min.x > aabb.max.x {
+        return 0.0;
+    }
+    let radius = 0.5 * (aabb.max - aabb.min).magnitude();
+    let projection = camera.get_unjittered_projection();
+    if projection.z.w == 0.0 {
+        return radius * projection.y.y;
+    }
+    let distance = (0.5 * (aabb.min + aabb.max) - camera.position()).magnitude();
+    if distance <= radius { f32::INFINITY } else { radius * projection.y.y / distance }
+}
+
+pub(crate) fn select_lod(screen_size: f32, lod_screen_sizes: &[f32], current: usize, hysteresis: f32) -> usize
+{
+    let coarser = lod_screen_sizes.iter().filter(|size| screen_size < **size * (1.0 - hysteresis)).count();
+    let finer = lod_screen_sizes.iter().filter(|size| screen_size < **size * (1.0 + hysteresis)).count();
+    if coarser > current { coarser } else if finer < current { finer } else { current }
+}
+
+pub(crate) fn compute_aabb(positions: &[f32]) -> AxisAllignedBoundingBox {
 
     let mut aabb = AxisAllignedBoundingBox {min: vec3(std::f32::INFINITY, std::f32::INFINITY, std::f32::INFINITY),
         max: vec3(std::f32::NEG_INFINITY, std::f32::NEG_INFINITY, std::f32::NEG_INFINITY)};
@@ -139,8 +301,8 @@ fn compute_aabb(positions: &[f32]) -> AxisAllignedBoundingBox {
     aabb
 }
 
-fn compute_normals(indices: &[u32], positions: &[f32]) -> Vec<f32> {
-    let mut normals = vec![0.0f32; positions.len() * 3];
+pub(crate) fn compute_normals(indices: &[u32], positions: &[f32]) -> Vec<f32> {
+    let mut normals = vec![0.0f32; positions.len()];
     for face in 0..indices.len()/3 {
         let index0 = indices[face*3] as usize;
         let p0 = vec3(positions[index0*3], positions[index0*3+1], positions[index0*3+2]);
@@ -168,4 +330,123 @@ fn compute_normals(indices: &[u32], positions: &[f32]) -> Vec<f32> {
         normals[3*i+2] = normal.z;
     }
     normals
-}
\ No newline at end of file
+}
+
+// At a seam between mirrored uv coordinates the corners of a shared vertex have different handedness, and only those with the handedness of most corners are used.
+pub(crate) fn compute_tangents(indices: &[u32], positions: &[f32], normals: &[f32], uvs: &[f32]) -> Vec<f32> {
+    let mut geometry = TangentGeometry { indices, positions, normals, uvs, corner_tangents: vec![[(vec3(0.0, 0.0, 0.0), 0); 2]; positions.len() / 3] };
+    mikktspace::generate_tangents(&mut geometry);
+
+    let mut tangents = Vec::with_capacity(4 * positions.len() / 3);
+    for (i, sides) in geometry.corner_tangents.iter().enumerate() {
+        let (handedness, (sum, _)) = if sides[1].1 > sides[0].1 {(-1.0, sides[1])} else {(1.0, sides[0])};
+        let normal = vec3(normals[3*i], normals[3*i+1], normals[3*i+2]);
+        let tangent = sum - normal * normal.dot(sum);
+        let tangent = if tangent.magnitude2() > 0.0 { tangent.normalize() } else { vec3(1.0, 0.0, 0.0) };
+        tangents.extend(&[tangent.x, tangent.y, tangent.z, handedness]);
+    }
+    tangents
+}
+
+struct TangentGeometry<'a> {
+    indices: &'a [u32],
+    positions: &'a [f32],
+    normals: &'a [f32],
+    uvs: &'a [f32],
+    corner_tangents: Vec<[(Vec3, usize); 2]>
+}
+
+impl<'a> TangentGeometry<'a> {
+    fn index(&self, face: usize, vert: usize) -> usize {
+        self.indices[face * 3 + vert] as usize
+    }
+}
+
+impl<'a> mikktspace::Geometry for TangentGeometry<'a> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        let i = self.index(face, vert);
+        [self.positions[3*i], self.positions[3*i+1], self.positions[3*i+2]]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        let i = self.index(face, vert);
+        [self.normals[3*i], self.normals[3*i+1], self.normals[3*i+2]]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        let i = self.index(face, vert);
+        [self.uvs[2*i], self.uvs[2*i+1]]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let i = self.index(face, vert);
+        let side = &mut self.corner_tangents[i][if tangent[3] < 0.0 {1} else {0}];
+        side.0 += vec3(tangent[0], tangent[1], tangent[2]);
+        side.1 += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirrored_strip() -> (Vec<u32>, Vec<f32>, Vec<f32>, Vec<f32>)
+    {
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        for x in 0..4 {
+            for y in 0..2 {
+                positions.extend(&[x as f32, y as f32, 0.0]);
+                uvs.extend(&[if x == 3 {0.5} else {x as f32 * 0.5}, y as f32]);
+            }
+        }
+        let normals = [0.0, 0.0, 1.0].repeat(8);
+        let mut indices = Vec::new();
+        for x in 0..3 {
+            let (a, b) = (2 * x, 2 * x + 2);
+            indices.extend(&[a, b, a + 1, b, b + 1, a + 1]);
+        }
+        (indices, positions, normals, uvs)
+    }
+
+    #[test]
+    fn transformed_bounding_box_contains_the_rotated_box()
+    {
+        let aabb = AxisAllignedBoundingBox {min: vec3(-1.0, -1.0, -1.0), max: vec3(1.0, 1.0, 1.0)};
+        let rotated = aabb.transform(&(Mat4::from_translation(vec3(5.0, 0.0, 0.0)) * Mat4::from_angle_y(degrees(45.0))));
+        assert!((rotated.min - vec3(5.0 - 2.0f32.sqrt(), -1.0, -2.0f32.sqrt())).magnitude() < 0.0001);
+        assert!((rotated.max - vec3(5.0 + 2.0f32.sqrt(), 1.0, 2.0f32.sqrt())).magnitude() < 0.0001);
+    }
+
+    #[test]
+    fn tangents_are_orthonormal_with_one_handedness()
+    {
+        let (indices, positions, normals, uvs) = mirrored_strip();
+        let tangents = compute_tangents(&indices, &positions, &normals, &uvs);
+        for (vertex, tangent) in tangents.chunks(4).enumerate() {
+            let t = vec3(tangent[0], tangent[1], tangent[2]);
+            assert!((t.magnitude() - 1.0).abs() < 1e-5 && t.z.abs() < 1e-5, "The tangent {:?} of the vertex {}", tangent, vertex);
+            assert!(tangent[3] == 1.0 || tangent[3] == -1.0);
+        }
+        assert!(tangents[4*6..].chunks(4).all(|tangent| (tangent[0] + 1.0).abs() < 1e-5 && tangent[3] == -1.0));
+        assert!(tangents[..4*4].chunks(4).all(|tangent| (tangent[0] - 1.0).abs() < 1e-5 && tangent[3] == 1.0));
+    }
+
+    #[test]
+    fn tangents_do_not_depend_on_the_order_of_the_triangles()
+    {
+        let (indices, positions, normals, uvs) = mirrored_strip();
+        let reversed: Vec<u32> = indices.chunks(3).rev().flat_map(|triangle| triangle.iter().cloned()).collect();
+        let tangents = compute_tangents(&indices, &positions, &normals, &uvs);
+        let reversed_tangents = compute_tangents(&reversed, &positions, &normals, &uvs);
+        assert!(tangents.iter().zip(reversed_tangents.iter()).all(|(a, b)| (a - b).abs() < 1e-5), "{:?} and {:?}", tangents, reversed_tangents);
+    }
+}
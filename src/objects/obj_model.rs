@@ -0,0 +1,224 @@
+use crate::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub enum Error {
+    OBJ(wavefront_obj::ParseError),
+    IO(std::io::Error),
+    Utf8(std::str::Utf8Error),
+    Texture(texture::Error),
+    Mesh(mesh::Error),
+    MissingResource {message: String}
+}
+
+impl From<wavefront_obj::ParseError> for Error {
+    fn from(other: wavefront_obj::ParseError) -> Self {
+        Error::OBJ(other)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(other: std::io::Error) -> Self {
+        Error::IO(other)
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(other: std::str::Utf8Error) -> Self {
+        Error::Utf8(other)
+    }
+}
+
+impl From<texture::Error> for Error {
+    fn from(other: texture::Error) -> Self {
+        Error::Texture(other)
+    }
+}
+
+impl From<mesh::Error> for Error {
+    fn from(other: mesh::Error) -> Self {
+        Error::Mesh(other)
+    }
+}
+
+/// The v coordinate is flipped, so the uv coordinates have their origin in the upper left corner like in glTF.
+pub struct OBJMesh {
+    pub name: String,
+    pub indices: Vec<u32>,
+    pub positions: Vec<f32>,
+    pub normals: Vec<f32>,
+    pub uvs: Option<Vec<f32>>,
+    pub material: Option<usize>
+}
+
+pub struct OBJMaterial {
+    pub name: String,
+    pub color: Vec3,
+    pub diffuse_intensity: f32,
+    pub specular_intensity: f32,
+    pub specular_power: f32,
+    pub texture: Option<String>
+}
+
+pub struct OBJModel {
+    pub meshes: Vec<OBJMesh>,
+    pub materials: Vec<OBJMaterial>,
+    textures: HashMap<String, Vec<u8>>
+}
+
+impl OBJModel {
+
+    pub fn new_from_bytes(bytes: &[u8], resources: &HashMap<&str, &[u8]>) -> Result<OBJModel, Error>
+    {
+        Self::load(bytes, &|name| resources.get(name).map(|bytes| bytes.to_vec())
+            .ok_or_else(|| Error::MissingResource {message: format!("The resource {} is not given", name)}))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_from_file(path: &str) -> Result<OBJModel, Error>
+    {
+        let path = std::path::Path::new(path);
+        let directory = path.parent().unwrap_or_else(|| std::path::Path::new(""));
+        Self::load(&std::fs::read(path)?, &|name| Ok(std::fs::read(directory.join(name))?))
+    }
+
+    fn load(bytes: &[u8], resolve: &dyn Fn(&str) -> Result<Vec<u8>, Error>) -> Result<OBJModel, Error>
+    {
+        let obj = wavefront_obj::obj::parse(std::str::from_utf8(bytes)?)?;
+
+        let mut materials = Vec::new();
+        let mut textures = HashMap::new();
+        if let Some(library) = obj.material_library.as_ref().and_then(|name| resolve(name).ok()) {
+            for material in wavefront_obj::mtl::parse(std::str::from_utf8(&library)?)?.materials {
+                if let Some(ref name) = material.uv_map {
+                    if let Ok(texture) = resolve(name) {
+                        textures.insert(name.clone(), texture);
+                    }
+                }
+                materials.push(material_from_mtl(material));
+            }
+        }
+
+        let mut meshes = Vec::new();
+        for object in obj.objects.iter() {
+            for geometry in object.geometry.iter() {
+                let material = geometry.material_name.as_ref()
+                    .and_then(|name| materials.iter().position(|material: &OBJMaterial| &material.name == name));
+                if let Some(mesh) = mesh_from_geometry(object, geometry, material) {
+                    meshes.push(mesh);
+                }
+            }
+        }
+        Ok(OBJModel { meshes, materials, textures })
+    }
+
+    pub fn to_meshes(&self, gl: &Gl) -> Result<Vec<Mesh>, Error>
+    {
+        let mut textures = HashMap::new();
+        for (name, bytes) in self.textures.iter() {
+            textures.insert(name, Rc::new(Texture2D::new_from_bytes(gl, bytes)?));
+        }
+
+        let mut meshes = Vec::new();
+        for obj_mesh in self.meshes.iter() {
+            let mut mesh = match obj_mesh.uvs {
+                Some(ref uvs) => Mesh::new_with_uvs(gl, &obj_mesh.indices, &obj_mesh.positions, &obj_mesh.normals, uvs)?,
+                None => Mesh::new(gl, &obj_mesh.indices, &obj_mesh.positions, &obj_mesh.normals)?
+            };
+            if let Some(material) = obj_mesh.material.map(|index| &self.materials[index]) {
+                mesh.color = material.color;
+                mesh.diffuse_intensity = material.diffuse_intensity;
+                mesh.specular_intensity = material.specular_intensity;
+                mesh.specular_power = material.specular_power;
+                mesh.texture = material.texture.as_ref().and_then(|name| textures.get(name)).cloned();
+            }
+            meshes.push(mesh);
+        }
+        Ok(meshes)
+    }
+}
+
+fn material_from_mtl(material: wavefront_obj::mtl::Material) -> OBJMaterial
+{
+    let diffuse = vec3(material.color_diffuse.r as f32, material.color_diffuse.g as f32, material.color_diffuse.b as f32);
+    let diffuse_intensity = diffuse.x.max(diffuse.y).max(diffuse.z);
+    let specular = &material.color_specular;
+    OBJMaterial {
+        name: material.name,
+        color: if diffuse_intensity > 0.0 { diffuse / diffuse_intensity } else { vec3(1.0, 1.0, 1.0) },
+        diffuse_intensity: diffuse_intensity.min(1.0),
+        specular_intensity: ((specular.r + specular.g + specular.b) as f32 / 3.0).clamp(0.0, 1.0),
+        specular_power: (material.specular_coefficient as f32).clamp(0.0, 30.0),
+        texture: material.uv_map
+    }
+}
+
+fn mesh_from_geometry(object: &wavefront_obj::obj::Object, geometry: &wavefront_obj::obj::Geometry, material: Option<usize>) -> Option<OBJMesh>
+{
+    let mut vertex_map = HashMap::new();
+    let mut indices = Vec::new();
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let (mut has_normals, mut has_uvs) = (true, true);
+    for shape in geometry.shapes.iter() {
+        if let wavefront_obj::obj::Primitive::Triangle(a, b, c) = shape.primitive {
+            for vertex in [a, b, c].iter() {
+                let index = *vertex_map.entry(*vertex).or_insert_with(|| {
+                    let (position_index, uv_index, normal_index) = *vertex;
+                    let position = object.vertices[position_index];
+                    positions.extend(&[position.x as f32, position.y as f32, position.z as f32]);
+                    match normal_index.map(|index| object.normals[index]) {
+                        Some(normal) => normals.extend(&[normal.x as f32, normal.y as f32, normal.z as f32]),
+                        None => { has_normals = false; normals.extend(&[0.0, 0.0, 0.0]); }
+                    }
+                    match uv_index.map(|index| object.tex_vertices[index]) {
+                        Some(uv) => uvs.extend(&[uv.u as f32, 1.0 - uv.v as f32]),
+                        None => { has_uvs = false; uvs.extend(&[0.0, 0.0]); }
+                    }
+                    positions.len() as u32 / 3 - 1
+                });
+                indices.push(index);
+            }
+        }
+    }
+    if indices.is_empty() {
+        return None;
+    }
+    if !has_normals {
+        normals = compute_normals(&indices, &positions);
+    }
+    Some(OBJMesh { name: object.name.clone(), indices, positions, normals, uvs: if has_uvs { Some(uvs) } else { None }, material })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_meshes_materials_and_textures()
+    {
+        let mut resources = HashMap::new();
+        resources.insert("boxes.mtl", &include_bytes!("../../examples/assets/models/boxes.mtl")[..]);
+        resources.insert("test_texture.jpg", &include_bytes!("../../examples/assets/textures/test_texture.jpg")[..]);
+        let model = OBJModel::new_from_bytes(include_bytes!("../../examples/assets/models/boxes.obj"), &resources).unwrap();
+        assert_eq!(model.meshes.len(), 2);
+        assert_eq!(model.meshes[0].positions.len(), 24 * 3);
+        assert_eq!(model.meshes[0].indices.len(), 36);
+        assert!(model.meshes[0].uvs.is_some());
+        assert!(model.meshes[1].uvs.is_none());
+        assert_eq!(model.meshes[1].normals.len(), model.meshes[1].positions.len());
+        let texture = model.meshes[0].material.and_then(|material| model.materials[material].texture.as_ref()).unwrap();
+        assert!(model.textures.contains_key(texture));
+    }
+
+    #[test]
+    fn missing_resources_give_the_default_appearance()
+    {
+        let model = OBJModel::new_from_bytes(include_bytes!("../../examples/assets/models/boxes.obj"), &HashMap::new()).unwrap();
+        assert_eq!(model.meshes.len(), 2);
+        assert!(model.materials.is_empty() && model.textures.is_empty());
+        assert!(model.meshes.iter().all(|mesh| mesh.material.is_none()));
+    }
+}
@@ -18,6 +18,8 @@ in vec3 position;
 
 out vec3 pos;
 out vec3 nor;
+out vec2 uvs;
+out vec4 tang;
 
 mat3 rotationMatrix(vec3 source_dir, vec3 target_dir)
 {
@@ -37,5 +39,7 @@ void main()
     pos = l2w * (position * vec3(length(direction), tube_radius, tube_radius)) + translation;
     mat3 normalMatrix = transpose(inverse(l2w));
     nor = normalize(normalMatrix * vec3(0.0, position.y, position.z));
+    uvs = vec2(0.0);
+    tang = vec4(0.0);
     gl_Position = camera.viewProjection * modelMatrix * vec4(pos, 1.0);
 }
\ No newline at end of file
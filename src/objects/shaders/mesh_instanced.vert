@@ -0,0 +1,43 @@
+uniform mat4 modelMatrix;
+uniform mat4 normalMatrix;
+uniform vec3 meshColor;
+
+layout (std140) uniform Camera
+{
+    mat4 viewProjection;
+    mat4 view;
+    mat4 projection;
+    vec3 position;
+    float padding;
+} camera;
+
+in vec3 position;
+in vec3 normal;
+
+in vec4 transformation_column0;
+in vec4 transformation_column1;
+in vec4 transformation_column2;
+in vec4 transformation_column3;
+in vec3 instance_color;
+in vec3 normal_column0;
+in vec3 normal_column1;
+in vec3 normal_column2;
+
+out vec3 pos;
+out vec3 nor;
+out vec2 uvs;
+out vec4 tang;
+out vec3 color;
+
+void main()
+{
+    mat4 instanceMatrix = mat4(transformation_column0, transformation_column1, transformation_column2, transformation_column3);
+    mat4 worldMatrix = modelMatrix * instanceMatrix;
+    vec4 worldPosition = worldMatrix * vec4(position, 1.);
+    nor = mat3(normalMatrix) * mat3(normal_column0, normal_column1, normal_column2) * normal;
+    pos = worldPosition.xyz;
+    uvs = vec2(0.0);
+    tang = vec4(0.0);
+    color = meshColor * instance_color;
+    gl_Position = camera.viewProjection * worldPosition;
+}
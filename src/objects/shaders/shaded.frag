@@ -1,16 +1,33 @@
 
 uniform bool use_texture;
+uniform bool use_uvs;
 uniform sampler2D tex;
+uniform bool use_normal_map;
+uniform sampler2D normal_map;
+#ifdef INSTANCED
+in vec3 color;
+#else
 uniform vec3 color;
+#endif
 uniform float diffuse_intensity;
 uniform float specular_intensity;
 uniform float specular_power;
 
+uniform bool use_physical_material;
+uniform float metallic;
+uniform float roughness;
+uniform float occlusion;
+uniform vec3 emissive;
+
 in vec3 nor;
 in vec3 pos;
+in vec2 uvs;
+in vec4 tang;
 
 layout (location = 0) out vec4 out_color;
 layout (location = 1) out vec4 normal;
+layout (location = 2) out vec4 out_material;
+layout (location = 3) out vec4 out_emissive;
 
 vec3 blendNormal(vec3 normal){
 	vec3 blending = abs(normal);
@@ -31,8 +48,30 @@ vec3 triplanarMapping (sampler2D t, vec3 normal, vec3 position) {
 void main()
 {
 	vec3 n = normalize(gl_FrontFacing ? nor : -nor);
-    out_color = vec4(use_texture ? triplanarMapping(tex, n, pos) : color, diffuse_intensity);
-	int intensity = int(floor(specular_intensity * 15.0));
-	int power = int(floor(specular_power*0.5));
-    normal = vec4(0.5 * n + 0.5, float(power << 4 | intensity)/255.0);
-}
\ No newline at end of file
+    if(use_normal_map)
+    {
+        vec3 t = normalize(tang.xyz - n * dot(n, tang.xyz));
+        vec3 b = tang.w * cross(n, t);
+        n = normalize(mat3(t, b, n) * (2.0 * texture(normal_map, uvs).xyz - 1.0));
+    }
+    vec3 surface_color = color;
+    if(use_texture)
+    {
+        surface_color = use_uvs ? texture(tex, uvs).rgb : triplanarMapping(tex, n, pos);
+    }
+    if(use_physical_material)
+    {
+        out_color = vec4(surface_color, 1.0);
+        normal = vec4(0.5 * n + 0.5, 0.0);
+        out_material = vec4(metallic, roughness, occlusion, 1.0);
+        out_emissive = vec4(emissive, 0.0);
+    }
+    else {
+        out_color = vec4(surface_color, diffuse_intensity);
+        int intensity = int(floor(specular_intensity * 15.0));
+        int power = int(floor(specular_power*0.5));
+        normal = vec4(0.5 * n + 0.5, float(power << 4 | intensity)/255.0);
+        out_material = vec4(0.0, 0.0, 1.0, 0.0);
+        out_emissive = vec4(0.0);
+    }
+}
@@ -13,8 +13,12 @@ in vec3 coords;
 
 layout (location = 0) out vec4 color;
 layout (location = 1) out vec4 normal;
+layout (location = 2) out vec4 material;
+layout (location = 3) out vec4 emissive;
 
 void main() {
     color = vec4(texture(texture0, coords).rgb, 0.0);
     normal = vec4(0.5 * normalize(-coords) + 0.5, 0.0);
+    material = vec4(0.0, 0.0, 1.0, 0.0);
+    emissive = vec4(0.0);
 }
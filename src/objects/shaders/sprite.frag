@@ -7,6 +7,8 @@ in vec2 cs;
 
 layout (location = 0) out vec4 out_color;
 layout (location = 1) out vec4 out_normal;
+layout (location = 2) out vec4 out_material;
+layout (location = 3) out vec4 out_emissive;
 
 void main()
 {
@@ -31,5 +33,7 @@ void main()
     out_normal = mix(normal0, normal1, frac);
     out_normal.xyz = 2.0 * out_normal.xyz - 1.0;
     out_normal.xyz = 0.5 + 0.5 * normalize(vec3(cs.x * out_normal.x + cs.y * out_normal.z, out_normal.y, -cs.y * out_normal.x + cs.x * out_normal.z));
+    out_material = mix(texture(tex, vec3(uv, 2.0 * float(no_views) + index0)), texture(tex, vec3(uv, 2.0 * float(no_views) + index1)), frac);
+    out_emissive = mix(texture(tex, vec3(uv, 3.0 * float(no_views) + index0)), texture(tex, vec3(uv, 3.0 * float(no_views) + index1)), frac);
     // Maybe update depth as well?
 }
@@ -17,10 +17,14 @@ in vec3 position;
 
 out vec3 pos;
 out vec3 nor;
+out vec2 uvs;
+out vec4 tang;
 
 void main()
 {
     pos = scale * position + translation;
     nor = normalize(position);
+    uvs = vec2(0.0);
+    tang = vec4(0.0);
     gl_Position = camera.viewProjection * modelMatrix * vec4(pos, 1.0);
 }
\ No newline at end of file
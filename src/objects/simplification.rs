@@ -0,0 +1,269 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::cmp::Reverse;
+
+type Quadric = [f64; 10];
+
+pub fn simplify(indices: &[u32], positions: &[f32], target_no_triangles: usize) -> Vec<u32>
+{
+    let mut ids = HashMap::new();
+    let mut vertex_id = Vec::with_capacity(positions.len() / 3);
+    let mut points: Vec<[f64; 3]> = Vec::new();
+    for vertex in 0..positions.len() / 3 {
+        let key = [positions[3 * vertex].to_bits(), positions[3 * vertex + 1].to_bits(), positions[3 * vertex + 2].to_bits()];
+        let id = *ids.entry(key).or_insert_with(|| {
+            points.push([positions[3 * vertex] as f64, positions[3 * vertex + 1] as f64, positions[3 * vertex + 2] as f64]);
+            points.len() - 1
+        });
+        vertex_id.push(id);
+    }
+    let no_ids = points.len();
+
+    let mut triangles: Vec<[u32; 3]> = Vec::new();
+    for triangle in indices.chunks(3) {
+        let (a, b, c) = (vertex_id[triangle[0] as usize], vertex_id[triangle[1] as usize], vertex_id[triangle[2] as usize]);
+        if a != b && b != c && a != c {
+            triangles.push([triangle[0], triangle[1], triangle[2]]);
+        }
+    }
+    let id_of = |vertex: u32| vertex_id[vertex as usize];
+
+    let mut alive = vec![true; triangles.len()];
+    let mut no_alive = triangles.len();
+    let mut adjacent_triangles = vec![Vec::new(); no_ids];
+    let mut quadrics = vec![[0.0; 10]; no_ids];
+    let mut edge_count = HashMap::new();
+    for (t, triangle) in triangles.iter().enumerate() {
+        let corners = [id_of(triangle[0]), id_of(triangle[1]), id_of(triangle[2])];
+        let normal = cross(sub(points[corners[1]], points[corners[0]]), sub(points[corners[2]], points[corners[0]]));
+        let area = length(normal);
+        if area > 0.0 {
+            let quadric = plane_quadric(scale(normal, 1.0 / area), points[corners[0]], area);
+            for corner in corners.iter() {
+                add_to(&mut quadrics[*corner], &quadric);
+            }
+        }
+        for i in 0..3 {
+            adjacent_triangles[corners[i]].push(t);
+            let (a, b) = (corners[i], corners[(i + 1) % 3]);
+            *edge_count.entry(if a < b {(a, b)} else {(b, a)}).or_insert(0) += 1;
+        }
+    }
+
+    for triangle in triangles.iter() {
+        let corners = [id_of(triangle[0]), id_of(triangle[1]), id_of(triangle[2])];
+        let normal = cross(sub(points[corners[1]], points[corners[0]]), sub(points[corners[2]], points[corners[0]]));
+        for i in 0..3 {
+            let (a, b) = (corners[i], corners[(i + 1) % 3]);
+            if edge_count[&if a < b {(a, b)} else {(b, a)}] == 1 {
+                let edge = sub(points[b], points[a]);
+                let perpendicular = cross(edge, normal);
+                let perpendicular_length = length(perpendicular);
+                if perpendicular_length > 0.0 {
+                    let quadric = plane_quadric(scale(perpendicular, 1.0 / perpendicular_length), points[a], BOUNDARY_WEIGHT * dot(edge, edge));
+                    add_to(&mut quadrics[a], &quadric);
+                    add_to(&mut quadrics[b], &quadric);
+                }
+            }
+        }
+    }
+
+    let mut versions = vec![0usize; no_ids];
+    let mut removed = vec![false; no_ids];
+    let mut heap = BinaryHeap::new();
+    let push_edge = |heap: &mut BinaryHeap<_>, quadrics: &[Quadric], versions: &[usize], a: usize, b: usize| {
+        let mut quadric = quadrics[a];
+        add_to(&mut quadric, &quadrics[b]);
+        let (cost_to_b, cost_to_a) = (evaluate(&quadric, points[b]), evaluate(&quadric, points[a]));
+        let (from, to, cost) = if cost_to_b <= cost_to_a {(a, b, cost_to_b)} else {(b, a, cost_to_a)};
+        heap.push(Reverse((cost.max(0.0).to_bits(), from, to, versions[from], versions[to])));
+    };
+    for (a, b) in edge_count.keys() {
+        push_edge(&mut heap, &quadrics, &versions, *a, *b);
+    }
+
+    while no_alive > target_no_triangles {
+        let Reverse((_, from, to, from_version, to_version)) = match heap.pop() {
+            Some(entry) => entry,
+            None => break
+        };
+        if removed[from] || removed[to] || versions[from] != from_version || versions[to] != to_version {
+            continue;
+        }
+
+        let flips = adjacent_triangles[from].iter().any(|t| {
+            if !alive[*t] { return false; }
+            let corners = [id_of(triangles[*t][0]), id_of(triangles[*t][1]), id_of(triangles[*t][2])];
+            if corners.contains(&to) { return false; }
+            let moved = corners.map(|corner| if corner == from {to} else {corner});
+            let before = cross(sub(points[corners[1]], points[corners[0]]), sub(points[corners[2]], points[corners[0]]));
+            let after = cross(sub(points[moved[1]], points[moved[0]]), sub(points[moved[2]], points[moved[0]]));
+            dot(before, after) <= 0.0
+        });
+        if flips {
+            continue;
+        }
+
+        // Each vertex at `from` moves onto the vertex at `to` which it shares a collapsed triangle with, that is the vertex on the same side of a seam.
+        // Reject the collapse if a vertex at `from` has no such vertex, since it would be moved across a seam.
+        let mut targets: Vec<(u32, u32)> = Vec::new();
+        for t in adjacent_triangles[from].iter().filter(|t| alive[**t]) {
+            if let Some(target) = triangles[*t].iter().find(|vertex| id_of(**vertex) == to) {
+                for vertex in triangles[*t].iter().filter(|vertex| id_of(**vertex) == from) {
+                    targets.push((*vertex, *target));
+                }
+            }
+        }
+        let target_of = |vertex: u32| targets.iter().find(|(source, _)| *source == vertex).map(|(_, target)| *target);
+        let crosses_seam = adjacent_triangles[from].iter().filter(|t| alive[**t])
+            .flat_map(|t| triangles[*t].iter())
+            .any(|vertex| id_of(*vertex) == from && target_of(*vertex).is_none());
+        if crosses_seam {
+            continue;
+        }
+
+        let from_triangles = std::mem::take(&mut adjacent_triangles[from]);
+        for t in from_triangles {
+            if !alive[t] { continue; }
+            let triangle = &mut triangles[t];
+            if triangle.iter().any(|vertex| vertex_id[*vertex as usize] == to) {
+                alive[t] = false;
+                no_alive -= 1;
+            } else {
+                for vertex in triangle.iter_mut() {
+                    if vertex_id[*vertex as usize] == from {
+                        *vertex = target_of(*vertex).unwrap();
+                    }
+                }
+                adjacent_triangles[to].push(t);
+            }
+        }
+        removed[from] = true;
+        let quadric = quadrics[from];
+        add_to(&mut quadrics[to], &quadric);
+        versions[to] += 1;
+        adjacent_triangles[to].retain(|t| alive[*t]);
+
+        let mut neighbours: Vec<usize> = adjacent_triangles[to].iter()
+            .flat_map(|t| triangles[*t].iter().map(|vertex| vertex_id[*vertex as usize]))
+            .filter(|id| *id != to).collect();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        for neighbour in neighbours {
+            push_edge(&mut heap, &quadrics, &versions, to, neighbour);
+        }
+    }
+
+    triangles.iter().zip(alive.iter()).filter(|(_, alive)| **alive).flat_map(|(triangle, _)| triangle.iter().cloned()).collect()
+}
+
+const BOUNDARY_WEIGHT: f64 = 10.0;
+
+fn plane_quadric(normal: [f64; 3], point: [f64; 3], weight: f64) -> Quadric
+{
+    let (a, b, c) = (normal[0], normal[1], normal[2]);
+    let d = -dot(normal, point);
+    [a * a * weight, a * b * weight, a * c * weight, a * d * weight,
+        b * b * weight, b * c * weight, b * d * weight,
+        c * c * weight, c * d * weight,
+        d * d * weight]
+}
+
+fn add_to(quadric: &mut Quadric, other: &Quadric)
+{
+    for i in 0..10 {
+        quadric[i] += other[i];
+    }
+}
+
+fn evaluate(q: &Quadric, p: [f64; 3]) -> f64
+{
+    let (x, y, z) = (p[0], p[1], p[2]);
+    q[0] * x * x + 2.0 * q[1] * x * y + 2.0 * q[2] * x * z + 2.0 * q[3] * x
+        + q[4] * y * y + 2.0 * q[5] * y * z + 2.0 * q[6] * y
+        + q[7] * z * z + 2.0 * q[8] * z
+        + q[9]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3]
+{
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f64; 3], s: f64) -> [f64; 3]
+{
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64
+{
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3]
+{
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn length(a: [f64; 3]) -> f64
+{
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_with_seam(n: usize) -> (Vec<u32>, Vec<f32>, Vec<bool>)
+    {
+        let mut positions = Vec::new();
+        let mut right_side = Vec::new();
+        for x in 0..n + 1 {
+            for z in 0..n + 1 {
+                let (px, pz) = (x as f32 / n as f32 * 4.0, z as f32 / n as f32 * 4.0);
+                positions.extend(&[px, 0.3 * (1.3 * px).sin() * (0.7 * pz).cos(), pz]);
+                right_side.push(x > n / 2);
+            }
+        }
+        let first_copy = right_side.len();
+        for z in 0..n + 1 {
+            let vertex = (n / 2) * (n + 1) + z;
+            positions.extend_from_within(3 * vertex..3 * vertex + 3);
+            right_side.push(true);
+        }
+        let vertex = |x: usize, z: usize, right: bool| (if right && x == n / 2 {first_copy + z} else {x * (n + 1) + z}) as u32;
+        let mut indices = Vec::new();
+        for x in 0..n {
+            for z in 0..n {
+                let right = x >= n / 2;
+                indices.extend(&[vertex(x, z, right), vertex(x, z + 1, right), vertex(x + 1, z, right),
+                    vertex(x + 1, z, right), vertex(x, z + 1, right), vertex(x + 1, z + 1, right)]);
+            }
+        }
+        (indices, positions, right_side)
+    }
+
+    #[test]
+    fn reaches_the_target_with_the_original_vertices()
+    {
+        let cpu_mesh = crate::CPUMesh::new(include_bytes!("../../examples/assets/models/suzanne.3d")).unwrap();
+        let no_triangles = cpu_mesh.indices.len() / 3;
+        for fraction in [0.2, 0.05, 0.01].iter() {
+            let target = (no_triangles as f32 * fraction).round() as usize;
+            let indices = simplify(&cpu_mesh.indices, &cpu_mesh.positions, target);
+            assert!(indices.len() / 3 <= target && indices.len() / 3 + 2 >= target, "{} triangles for the target {}", indices.len() / 3, target);
+            assert!(indices.iter().all(|index| (*index as usize) < cpu_mesh.positions.len() / 3));
+        }
+    }
+
+    #[test]
+    fn keeps_the_sides_of_a_seam_apart()
+    {
+        let (indices, positions, right_side) = grid_with_seam(16);
+        let simplified = simplify(&indices, &positions, indices.len() / 3 / 8);
+        assert!(simplified.len() <= indices.len() / 4);
+        for triangle in simplified.chunks(3) {
+            let side = right_side[triangle[0] as usize];
+            assert!(triangle.iter().all(|vertex| right_side[*vertex as usize] == side), "The triangle {:?} crosses the seam", triangle);
+        }
+    }
+}
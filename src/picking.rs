@@ -0,0 +1,461 @@
+use crate::*;
+use std::rc::Rc;
+use std::convert::TryFrom;
+
+#[derive(Debug)]
+pub enum Error {
+    Program(program::Error),
+    Texture(texture::Error),
+    RenderTarget(rendertarget::Error),
+    Mesh(mesh::Error),
+    ObjectNotFound {message: String},
+    InvalidObject {message: String}
+}
+
+impl From<program::Error> for Error {
+    fn from(other: program::Error) -> Self {
+        Error::Program(other)
+    }
+}
+
+impl From<texture::Error> for Error {
+    fn from(other: texture::Error) -> Self {
+        Error::Texture(other)
+    }
+}
+
+impl From<rendertarget::Error> for Error {
+    fn from(other: rendertarget::Error) -> Self {
+        Error::RenderTarget(other)
+    }
+}
+
+impl From<mesh::Error> for Error {
+    fn from(other: mesh::Error) -> Self {
+        Error::Mesh(other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickResult {
+    pub object: usize,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub triangle: usize,
+    pub distance: f32,
+    pub position: Vec3,
+    pub normal: Vec3
+}
+
+struct BvhNode {
+    aabb: AxisAllignedBoundingBox,
+    first: usize,
+    count: usize
+}
+
+const MAX_LEAF_SIZE: usize = 4;
+
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<[Vec3; 3]>,
+    triangle_indices: Vec<usize>
+}
+
+impl Bvh {
+
+    pub fn new(indices: &[u32], positions: &[f32]) -> Bvh
+    {
+        let position = |index: u32| vec3(positions[3 * index as usize], positions[3 * index as usize + 1], positions[3 * index as usize + 2]);
+        let triangles: Vec<[Vec3; 3]> = indices.chunks(3).map(|triangle| [position(triangle[0]), position(triangle[1]), position(triangle[2])]).collect();
+        let centers: Vec<Vec3> = triangles.iter().map(|triangle| (triangle[0] + triangle[1] + triangle[2]) / 3.0).collect();
+
+        let mut bvh = Bvh {nodes: Vec::new(), triangle_indices: (0..triangles.len()).collect(), triangles};
+        bvh.nodes.push(BvhNode {aabb: bvh.bounds(0, bvh.triangles.len()), first: 0, count: bvh.triangles.len()});
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let (first, count) = (bvh.nodes[node].first, bvh.nodes[node].count);
+            if count <= MAX_LEAF_SIZE {
+                continue;
+            }
+
+            let (min, max) = bvh.triangle_indices[first..first + count].iter().fold((centers[bvh.triangle_indices[first]], centers[bvh.triangle_indices[first]]),
+                |(min, max), t| (vec3(min.x.min(centers[*t].x), min.y.min(centers[*t].y), min.z.min(centers[*t].z)),
+                                 vec3(max.x.max(centers[*t].x), max.y.max(centers[*t].y), max.z.max(centers[*t].z))));
+            let extent = max - min;
+            let axis = if extent.x >= extent.y && extent.x >= extent.z {0} else if extent.y >= extent.z {1} else {2};
+            let middle = 0.5 * (min[axis] + max[axis]);
+            let range = &mut bvh.triangle_indices[first..first + count];
+            let mut left_count = 0;
+            for i in 0..count {
+                if centers[range[i]][axis] < middle {
+                    range.swap(i, left_count);
+                    left_count += 1;
+                }
+            }
+            if left_count == 0 || left_count == count {
+                left_count = count / 2;
+            }
+
+            let children = bvh.nodes.len();
+            bvh.nodes.push(BvhNode {aabb: bvh.bounds(first, left_count), first, count: left_count});
+            bvh.nodes.push(BvhNode {aabb: bvh.bounds(first + left_count, count - left_count), first: first + left_count, count: count - left_count});
+            bvh.nodes[node].first = children;
+            bvh.nodes[node].count = 0;
+            stack.push(children);
+            stack.push(children + 1);
+        }
+        bvh
+    }
+
+    pub fn intersect(&self, origin: Vec3, direction: Vec3) -> Option<RayHit>
+    {
+        let mut closest: Option<RayHit> = None;
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let max_distance = closest.map(|hit| hit.distance).unwrap_or(f32::INFINITY);
+            if !intersects_box(&node.aabb, origin, direction, max_distance) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.first);
+                stack.push(node.first + 1);
+                continue;
+            }
+            for t in self.triangle_indices[node.first..node.first + node.count].iter() {
+                if let Some(distance) = intersect_triangle(&self.triangles[*t], origin, direction) {
+                    if distance < max_distance && closest.map(|hit| distance < hit.distance).unwrap_or(true) {
+                        let [p0, p1, p2] = self.triangles[*t];
+                        let normal = (p1 - p0).cross(p2 - p0).normalize();
+                        let normal = if normal.dot(direction) > 0.0 { -normal } else { normal };
+                        closest = Some(RayHit {triangle: *t, distance, position: origin + distance * direction, normal});
+                    }
+                }
+            }
+        }
+        closest
+    }
+
+    pub fn axis_aligned_bounding_box(&self) -> &AxisAllignedBoundingBox
+    {
+        &self.nodes[0].aabb
+    }
+
+    fn bounds(&self, first: usize, count: usize) -> AxisAllignedBoundingBox
+    {
+        let mut aabb = AxisAllignedBoundingBox {min: vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)};
+        for t in self.triangle_indices[first..first + count].iter() {
+            for p in self.triangles[*t].iter() {
+                aabb.min = vec3(aabb.min.x.min(p.x), aabb.min.y.min(p.y), aabb.min.z.min(p.z));
+                aabb.max = vec3(aabb.max.x.max(p.x), aabb.max.y.max(p.y), aabb.max.z.max(p.z));
+            }
+        }
+        aabb
+    }
+}
+
+fn intersects_box(aabb: &AxisAllignedBoundingBox, origin: Vec3, direction: Vec3, max_distance: f32) -> bool
+{
+    let mut near = 0.0f32;
+    let mut far = max_distance;
+    for axis in 0..3 {
+        if direction[axis] == 0.0 {
+            if origin[axis] < aabb.min[axis] || origin[axis] > aabb.max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let inverse = 1.0 / direction[axis];
+        let t0 = (aabb.min[axis] - origin[axis]) * inverse;
+        let t1 = (aabb.max[axis] - origin[axis]) * inverse;
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+        if near > far {
+            return false;
+        }
+    }
+    true
+}
+
+// The distance along the ray to the triangle, using the Möller–Trumbore algorithm, where both sides of the triangle are hit
+fn intersect_triangle(triangle: &[Vec3; 3], origin: Vec3, direction: Vec3) -> Option<f32>
+{
+    let edge1 = triangle[1] - triangle[0];
+    let edge2 = triangle[2] - triangle[0];
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = origin - triangle[0];
+    let u = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(q) * inverse;
+    if distance > 0.0 { Some(distance) } else { None }
+}
+
+fn screen_ray(camera: &Camera, screen_coordinates: (f64, f64)) -> (Vec3, Vec3, f32)
+{
+    let inverse = (camera.get_projection() * camera.get_view()).invert().unwrap();
+    let (x, y) = (2.0 * screen_coordinates.0 as f32 - 1.0, 1.0 - 2.0 * screen_coordinates.1 as f32);
+    let unproject = |z: f32| { let p = inverse * vec4(x, y, z, 1.0); p.truncate() / p.w };
+    let near = unproject(-1.0);
+    let direction = (unproject(1.0) - near).normalize();
+    (near, direction, (near - camera.position()).dot(direction))
+}
+
+#[derive(Default)]
+pub struct RayPicker {
+    objects: Vec<(usize, Rc<Bvh>, Mat4, Mat4)>
+}
+
+impl RayPicker {
+
+    pub fn new() -> RayPicker
+    {
+        RayPicker {objects: Vec::new()}
+    }
+
+    pub fn add_object(&mut self, object: usize, bvh: Rc<Bvh>, transformation: &Mat4) -> Result<(), Error>
+    {
+        let inverse = transformation.invert()
+            .ok_or_else(|| Error::InvalidObject {message: format!("The transformation of the object {} is not invertible", object)})?;
+        self.objects.push((object, bvh, *transformation, inverse));
+        Ok(())
+    }
+
+    pub fn set_transformation(&mut self, object: usize, transformation: &Mat4) -> Result<(), Error>
+    {
+        let inverse = transformation.invert()
+            .ok_or_else(|| Error::InvalidObject {message: format!("The transformation of the object {} is not invertible", object)})?;
+        let entry = self.objects.iter_mut().find(|(id, ..)| *id == object)
+            .ok_or_else(|| Error::ObjectNotFound {message: format!("The object {} has not been added", object)})?;
+        entry.2 = *transformation;
+        entry.3 = inverse;
+        Ok(())
+    }
+
+    pub fn remove_object(&mut self, object: usize)
+    {
+        self.objects.retain(|(id, ..)| *id != object);
+    }
+
+    pub fn cast_ray(&self, origin: Vec3, direction: Vec3) -> Option<PickResult>
+    {
+        let direction = direction.normalize();
+        let mut closest: Option<PickResult> = None;
+        for (object, bvh, transformation, inverse) in self.objects.iter() {
+            let local_origin = (inverse * origin.extend(1.0)).truncate();
+            let local_direction = (inverse * direction.extend(0.0)).truncate();
+            if let Some(hit) = bvh.intersect(local_origin, local_direction) {
+                // The local direction maps back to the unit world direction, so the distance is in world units
+                if closest.map(|result| hit.distance < result.distance).unwrap_or(true) {
+                    let normal_matrix = inverse.transpose();
+                    closest = Some(PickResult {object: *object, position: (transformation * hit.position.extend(1.0)).truncate(),
+                        normal: (normal_matrix * hit.normal.extend(0.0)).truncate().normalize(), distance: hit.distance});
+                }
+            }
+        }
+        closest
+    }
+
+    /// The screen coordinates are between 0 and 1 from the top left corner like `Camera::view_direction_at`.
+    pub fn pick(&self, camera: &Camera, screen_coordinates: (f64, f64)) -> Option<PickResult>
+    {
+        let (origin, direction, offset) = screen_ray(camera, screen_coordinates);
+        self.cast_ray(origin, direction).map(|result| PickResult {distance: result.distance + offset, ..result})
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct IdBufferPicker {
+    gl: Gl,
+    program: program::Program,
+    rendertarget: rendertarget::RenderTarget,
+    id_texture: Texture2D,
+    depth_texture: Texture2D
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct IdPass<'a> {
+    program: &'a program::Program,
+    camera: &'a Camera
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a> IdPass<'a> {
+
+    /// The identifier must be less than `i32::MAX`.
+    pub fn render_mesh(&self, object: usize, mesh: &Mesh, transformation: &Mat4) -> Result<(), Error>
+    {
+        let id = i32::try_from(object).ok().filter(|id| *id < i32::MAX)
+            .ok_or_else(|| Error::InvalidObject {message: format!("The object identifier {} is too large", object)})?;
+        self.program.add_uniform_int("objectId", &id)?;
+        mesh.render_geometry(self.program, transformation, self.camera, true)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IdBufferPicker {
+
+    pub fn new(gl: &Gl, width: usize, height: usize) -> Result<IdBufferPicker, Error>
+    {
+        let program = program::Program::from_source(gl,
+                                                    include_str!("shaders/transparent.vert"),
+                                                    include_str!("shaders/picking.frag"))?;
+        let rendertarget = rendertarget::RenderTarget::new(gl, 1)?;
+        let id_texture = Texture2D::new_as_integer_color_target(gl, width, height)?;
+        let depth_texture = Texture2D::new_as_depth_target(gl, width, height)?;
+        Ok(IdBufferPicker {gl: gl.clone(), program, rendertarget, id_texture, depth_texture})
+    }
+
+    pub fn pick(&self, camera: &Camera, screen_coordinates: (f64, f64), render: &dyn Fn(&IdPass)) -> Result<Option<PickResult>, Error>
+    {
+        state::depth_write(&self.gl, true);
+        state::depth_test(&self.gl, state::DepthTestType::LessOrEqual);
+        state::cull(&self.gl, state::CullType::None);
+        state::blend(&self.gl, state::BlendType::None);
+
+        self.rendertarget.write_to_color_and_depth(&self.id_texture, &self.depth_texture)?;
+        self.rendertarget.clear_color_integer_and_depth(&[0, 0, 0, 0]);
+        render(&IdPass {program: &self.program, camera});
+
+        let (width, height) = (self.id_texture.width, self.id_texture.height);
+        let x = ((screen_coordinates.0 * width as f64) as usize).min(width - 1);
+        let y = (((1.0 - screen_coordinates.1) * height as f64) as usize).min(height - 1);
+        let mut pixel = [0u8; 16];
+        let mut depth = [0.0f32];
+        self.rendertarget.read();
+        self.gl.read_pixels(x as u32, y as u32, 1, 1, gl::consts::RGBA_INTEGER, gl::consts::UNSIGNED_INT, &mut pixel);
+        self.gl.read_depths(x as u32, y as u32, 1, 1, gl::consts::DEPTH_COMPONENT, gl::consts::FLOAT, &mut depth);
+
+        let value = |i: usize| u32::from_ne_bytes([pixel[4 * i], pixel[4 * i + 1], pixel[4 * i + 2], pixel[4 * i + 3]]);
+        if value(0) == 0 {
+            return Ok(None);
+        }
+
+        let ndc = vec4(2.0 * (x as f32 + 0.5) / width as f32 - 1.0, 2.0 * (y as f32 + 0.5) / height as f32 - 1.0, 2.0 * depth[0] - 1.0, 1.0);
+        let position = (camera.get_projection() * camera.get_view()).invert().unwrap() * ndc;
+        let position = position.truncate() / position.w;
+        let normal = vec3(f32::from_bits(value(1)), f32::from_bits(value(2)), f32::from_bits(value(3)));
+        let (_, direction, _) = screen_ray(camera, screen_coordinates);
+        Ok(Some(PickResult {object: value(0) as usize - 1, position, normal, distance: (position - camera.position()).dot(direction)}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube() -> (Vec<u32>, Vec<f32>)
+    {
+        let positions = vec![-0.5, -0.5, -0.5,  0.5, -0.5, -0.5,  0.5, 0.5, -0.5,  -0.5, 0.5, -0.5,
+                             -0.5, -0.5, 0.5,  0.5, -0.5, 0.5,  0.5, 0.5, 0.5,  -0.5, 0.5, 0.5];
+        let indices = vec![0, 2, 1, 0, 3, 2,  4, 5, 6, 4, 6, 7,  0, 1, 5, 0, 5, 4,
+                           3, 6, 2, 3, 7, 6,  0, 4, 7, 0, 7, 3,  1, 2, 6, 1, 6, 5];
+        (indices, positions)
+    }
+
+    fn wavy_grid(n: usize) -> (Vec<u32>, Vec<f32>)
+    {
+        let mut positions = Vec::new();
+        for i in 0..n + 1 {
+            for j in 0..n + 1 {
+                let (x, y) = (i as f32 / n as f32 * 2.0 - 1.0, j as f32 / n as f32 * 2.0 - 1.0);
+                positions.extend(&[x, y, 0.2 * (5.0 * x).sin() * (3.0 * y).cos()]);
+            }
+        }
+        let mut indices = Vec::new();
+        for i in 0..n as u32 {
+            for j in 0..n as u32 {
+                let (a, b) = (i * (n as u32 + 1) + j, (i + 1) * (n as u32 + 1) + j);
+                indices.extend(&[a, b, a + 1, b, b + 1, a + 1]);
+            }
+        }
+        (indices, positions)
+    }
+
+    #[test]
+    fn bvh_finds_the_closest_triangle()
+    {
+        let (indices, positions) = cube();
+        let bvh = Bvh::new(&indices, &positions);
+        assert_eq!(bvh.axis_aligned_bounding_box().min, vec3(-0.5, -0.5, -0.5));
+
+        let hit = bvh.intersect(vec3(0.1, 0.2, 5.0), vec3(0.0, 0.0, -1.0)).unwrap();
+        assert!((hit.distance - 4.5).abs() < 1e-5 && (hit.position - vec3(0.1, 0.2, 0.5)).magnitude() < 1e-5);
+        assert!((hit.normal - vec3(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+        assert!(hit.triangle == 2 || hit.triangle == 3);
+
+        let hit = bvh.intersect(vec3(0.1, 0.2, 5.0), vec3(0.0, 0.0, -2.0)).unwrap();
+        assert!((hit.distance - 2.25).abs() < 1e-5);
+        let hit = bvh.intersect(vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0)).unwrap();
+        assert!((hit.distance - 0.5).abs() < 1e-5 && (hit.normal - vec3(-1.0, 0.0, 0.0)).magnitude() < 1e-5);
+
+        assert!(bvh.intersect(vec3(2.0, 0.0, 5.0), vec3(0.0, 0.0, -1.0)).is_none());
+        assert!(bvh.intersect(vec3(0.0, 0.0, 5.0), vec3(0.0, 0.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn bvh_agrees_with_testing_every_triangle()
+    {
+        let (indices, positions) = wavy_grid(32);
+        let bvh = Bvh::new(&indices, &positions);
+        let position = |index: u32| vec3(positions[3 * index as usize], positions[3 * index as usize + 1], positions[3 * index as usize + 2]);
+        let triangles: Vec<[Vec3; 3]> = indices.chunks(3).map(|triangle| [position(triangle[0]), position(triangle[1]), position(triangle[2])]).collect();
+        for i in 0..40 {
+            for j in 0..40 {
+                let origin = vec3(1.0, 0.5, 3.0);
+                let direction = vec3(-2.5 + 0.1 * i as f32, -2.0 + 0.1 * j as f32, -3.0);
+                let closest = triangles.iter().filter_map(|triangle| intersect_triangle(triangle, origin, direction)).fold(None, |closest: Option<f32>, distance| {
+                    Some(closest.map(|closest| closest.min(distance)).unwrap_or(distance))
+                });
+                match (bvh.intersect(origin, direction), closest) {
+                    (Some(hit), Some(distance)) => assert!((hit.distance - distance).abs() < 1e-5),
+                    (None, None) => {},
+                    results => panic!("The hierarchy disagrees for the direction {:?}: {:?}", direction, results)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ray_picker_finds_the_closest_object()
+    {
+        let (indices, positions) = cube();
+        let bvh = Rc::new(Bvh::new(&indices, &positions));
+        let mut picker = RayPicker::new();
+        picker.add_object(7, bvh.clone(), &Mat4::from_translation(vec3(0.0, 0.0, -2.0))).unwrap();
+        picker.add_object(8, bvh, &(Mat4::from_translation(vec3(0.0, 0.0, -5.0)) * Mat4::from_nonuniform_scale(2.0, 1.0, 4.0))).unwrap();
+        assert!(matches!(picker.add_object(9, Rc::new(Bvh::new(&indices, &positions)), &Mat4::from_scale(0.0)), Err(Error::InvalidObject {..})));
+
+        let result = picker.cast_ray(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, -3.0)).unwrap();
+        assert_eq!(result.object, 7);
+        assert!((result.distance - 1.5).abs() < 1e-5 && (result.position - vec3(0.0, 0.0, -1.5)).magnitude() < 1e-5);
+        assert!((result.normal - vec3(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+
+        picker.set_transformation(7, &Mat4::from_translation(vec3(5.0, 0.0, -2.0))).unwrap();
+        let result = picker.cast_ray(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, -1.0)).unwrap();
+        assert_eq!(result.object, 8);
+        assert!((result.distance - 3.0).abs() < 1e-5);
+
+        picker.remove_object(8);
+        assert!(picker.cast_ray(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, -1.0)).is_none());
+        assert!(matches!(picker.set_transformation(8, &Mat4::identity()), Err(Error::ObjectNotFound {..})));
+    }
+}
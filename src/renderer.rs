@@ -9,7 +9,9 @@ pub enum Error {
     Texture(texture::Error),
     Buffer(buffer::Error),
     Light(light::Error),
-    LightExtendsMaxLimit {message: String}
+    Environment(environment::Error),
+    Mesh(mesh::Error),
+    Effects(effects::Error)
 }
 
 impl From<std::io::Error> for Error {
@@ -48,59 +50,186 @@ impl From<light::Error> for Error {
     }
 }
 
+impl From<environment::Error> for Error {
+    fn from(other: environment::Error) -> Self {
+        Error::Environment(other)
+    }
+}
+
+impl From<mesh::Error> for Error {
+    fn from(other: mesh::Error) -> Self {
+        Error::Mesh(other)
+    }
+}
+
+impl From<effects::Error> for Error {
+    fn from(other: effects::Error) -> Self {
+        Error::Effects(other)
+    }
+}
+
+// The layers of the geometry pass texture:
+// 0: surface color and Phong diffuse intensity
+// 1: normal and packed Phong specular intensity and power
+// 2: metallic, roughness, ambient occlusion and whether the material is physically based
+// 3: emissive color
+pub(crate) const GEOMETRY_PASS_LAYERS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransparencyMode {
+    Sorted,
+    WeightedBlended
+}
+
 pub struct DeferredPipeline {
     gl: Gl,
-    light_pass_program: program::Program,
+    ambient_light_program: program::Program,
+    directional_light_program: program::Program,
+    point_light_program: program::Program,
+    spot_light_program: program::Program,
+    environment_light_program: program::Program,
+    transparent_ambient_light_program: program::Program,
+    transparent_directional_light_program: program::Program,
+    transparent_point_light_program: program::Program,
+    transparent_spot_light_program: program::Program,
+    transparent_environment_light_program: program::Program,
+    transparent_composite_program: program::Program,
+    hdr_copy_program: program::Program,
     geometry_pass_rendertarget: rendertarget::RenderTarget,
     geometry_pass_texture: Texture2DArray,
     geometry_pass_depth_texture: Texture2DArray,
+    transparent_rendertarget: rendertarget::RenderTarget,
+    transparent_texture: Texture2DArray,
+    hdr_rendertarget: rendertarget::RenderTarget,
+    hdr_texture: Texture2D,
+    transparent_material: UniformBuffer,
+    shadow_rendertarget: rendertarget::RenderTarget,
+    no_shadow_map: Texture2D,
+    no_shadow_cube_map: Texture3D,
+    no_shadow_maps: Texture2DArray,
+    ssao_effect: Option<effects::SsaoEffect>,
+    no_ambient_occlusion: Texture2D,
+    fxaa_effect: Option<effects::FxaaEffect>,
+    taa_effect: Option<effects::TaaEffect>,
+    anti_aliased_texture: Option<Texture2D>,
+    jitter_index: usize,
     full_screen: VertexBuffer,
+    sphere: (VertexBuffer, ElementBuffer),
+    cone: (VertexBuffer, ElementBuffer),
     ambient_light: AmbientLight,
-    directional_lights: DirectionalLight,
-    point_lights: PointLight,
-    spot_lights: SpotLight,
-    pub background_color: Vec4
+    environment_light: Option<EnvironmentLight>,
+    directional_lights: Vec<DirectionalLight>,
+    point_lights: Vec<PointLight>,
+    spot_lights: Vec<SpotLight>,
+    pub background_color: Vec4,
+    pub transparency_mode: TransparencyMode
 }
 
+enum LightVolume {
+    FullScreen,
+    Sphere(Mat4),
+    Cone(Mat4),
+    Outside
+}
 
 impl DeferredPipeline
 {
     pub fn new(gl: &Gl, screen_width: usize, screen_height: usize, background_color: Vec4) -> Result<DeferredPipeline, Error>
     {
-        let light_pass_program = program::Program::from_source(gl,
-                                                               include_str!("shaders/light_pass.vert"),
-                                                               include_str!("shaders/light_pass.frag"))?;
-        let geometry_pass_rendertarget = rendertarget::RenderTarget::new(gl, 2)?;
-        let geometry_pass_texture = Texture2DArray::new_as_color_targets(gl, screen_width, screen_height, 2)?;
+        let light_program = |source: &str| program::Program::from_source(gl, include_str!("shaders/light_pass.vert"),
+                                                                          &format!("{}{}{}{}", include_str!("shaders/light_pass.frag"),
+                                                                                   include_str!("shaders/shadow.frag"), source,
+                                                                                   include_str!("shaders/deferred.frag")));
+        let transparent_program = |source: &str| program::Program::from_source(gl, include_str!("shaders/transparent.vert"),
+                                                                                &format!("{}{}{}", include_str!("shaders/light_pass.frag"), source,
+                                                                                         include_str!("shaders/transparent.frag")));
+        let transparent_light_program = |source: &str| transparent_program(&format!("{}{}", include_str!("shaders/shadow.frag"), source));
+        let ambient_light_program = program::Program::from_source(gl,
+                                                                  include_str!("shaders/light_pass.vert"),
+                                                                  include_str!("shaders/ambient_light.frag"))?;
+        let directional_light_program = light_program(include_str!("shaders/directional_light.frag"))?;
+        let point_light_program = light_program(include_str!("shaders/point_light.frag"))?;
+        let spot_light_program = light_program(include_str!("shaders/spot_light.frag"))?;
+        let environment_light_program = program::Program::from_source(gl, include_str!("shaders/light_pass.vert"),
+                                                                      &format!("{}{}{}", include_str!("shaders/light_pass.frag"),
+                                                                               include_str!("shaders/environment_light.frag"),
+                                                                               include_str!("shaders/deferred.frag")))?;
+
+        let transparent_ambient_light_program = transparent_program(include_str!("shaders/transparent_ambient_light.frag"))?;
+        let transparent_directional_light_program = transparent_light_program(include_str!("shaders/directional_light.frag"))?;
+        let transparent_point_light_program = transparent_light_program(include_str!("shaders/point_light.frag"))?;
+        let transparent_spot_light_program = transparent_light_program(include_str!("shaders/spot_light.frag"))?;
+        let transparent_environment_light_program = transparent_program(include_str!("shaders/environment_light.frag"))?;
+        let transparent_composite_program = program::Program::from_source(gl, include_str!("shaders/light_pass.vert"),
+                                                                          include_str!("shaders/transparent_composite.frag"))?;
+        let hdr_copy_program = program::Program::from_source(gl, include_str!("shaders/light_pass.vert"), include_str!("shaders/hdr_copy.frag"))?;
+
+        let geometry_pass_rendertarget = rendertarget::RenderTarget::new(gl, GEOMETRY_PASS_LAYERS)?;
+        let geometry_pass_texture = Texture2DArray::new_as_color_targets(gl, screen_width, screen_height, GEOMETRY_PASS_LAYERS)?;
         let geometry_pass_depth_texture = Texture2DArray::new_as_depth_targets(gl, screen_width, screen_height, 1)?;
 
+        let transparent_rendertarget = rendertarget::RenderTarget::new(gl, 2)?;
+        let transparent_texture = Texture2DArray::new_as_hdr_color_targets(gl, screen_width, screen_height, 2)?;
+        let transparent_material = UniformBuffer::new(gl, &[3, 1, 3, 1, 1, 1, 1, 1, 1, 1, 2])?;
+
+        let hdr_rendertarget = rendertarget::RenderTarget::new(gl, 1)?;
+        let hdr_texture = Texture2D::new_as_hdr_color_target(gl, screen_width, screen_height)?;
+
         let positions = vec![
             -3.0, -1.0, 0.0,
             3.0, -1.0, 0.0,
             0.0, 2.0, 0.0
         ];
-        let uvs = vec![
-            -1.0, 0.0,
-            2.0, 0.0,
-            0.5, 1.5
-        ];
-        let full_screen = VertexBuffer::new_with_two_static_attributes(&gl, &positions, &uvs).unwrap();
+        let full_screen = VertexBuffer::new_with_one_static_attribute(gl, &positions).unwrap();
+
+        let mut no_ambient_occlusion = Texture2D::new(gl, 1, 1)?;
+        no_ambient_occlusion.fill_with_u8(1, 1, &[255, 255, 255]);
 
         Ok(DeferredPipeline {
             gl: gl.clone(),
-            light_pass_program,
+            ambient_light_program,
+            directional_light_program,
+            point_light_program,
+            spot_light_program,
+            environment_light_program,
+            transparent_ambient_light_program,
+            transparent_directional_light_program,
+            transparent_point_light_program,
+            transparent_spot_light_program,
+            transparent_environment_light_program,
+            transparent_composite_program,
+            hdr_copy_program,
             full_screen,
+            sphere: sphere_volume(gl)?,
+            cone: cone_volume(gl)?,
             geometry_pass_rendertarget,
             geometry_pass_texture,
             geometry_pass_depth_texture,
+            transparent_rendertarget,
+            transparent_texture,
+            hdr_rendertarget,
+            hdr_texture,
+            transparent_material,
+            shadow_rendertarget: rendertarget::RenderTarget::new(gl, 0)?,
+            no_shadow_map: Texture2D::new_as_depth_target(gl, 1, 1)?,
+            no_shadow_cube_map: Texture3D::new_as_depth_target(gl, 1)?,
+            no_shadow_maps: Texture2DArray::new_as_depth_targets(gl, 1, 1, 1)?,
+            ssao_effect: None,
+            no_ambient_occlusion,
+            fxaa_effect: None,
+            taa_effect: None,
+            anti_aliased_texture: None,
+            jitter_index: 0,
             ambient_light: AmbientLight::new(),
-            directional_lights: DirectionalLight::new(gl)?,
-            point_lights: PointLight::new(gl)?,
-            spot_lights: SpotLight::new(gl)?,
-            background_color })
+            environment_light: None,
+            directional_lights: Vec::new(),
+            point_lights: Vec::new(),
+            spot_lights: Vec::new(),
+            background_color,
+            transparency_mode: TransparencyMode::Sorted })
     }
 
-    pub fn shadow_pass<F>(&self, render_scene: &F)
+    pub fn shadow_pass<F>(&mut self, camera: &Camera, render_scene: &F)
         where F: Fn(&Camera)
     {
         state::depth_write(&self.gl, true);
@@ -108,14 +237,21 @@ impl DeferredPipeline
         state::cull(&self.gl, state::CullType::None);
         state::blend(&self.gl, state::BlendType::None);
 
-        self.directional_lights.shadow_pass(render_scene);
-        self.spot_lights.shadow_pass(render_scene);
+        for light in self.directional_lights.iter_mut() {
+            light.shadow_pass(camera, &self.shadow_rendertarget, render_scene);
+        }
+        for light in self.spot_lights.iter() {
+            light.shadow_pass(&self.shadow_rendertarget, render_scene);
+        }
+        for light in self.point_lights.iter() {
+            light.shadow_pass(&self.shadow_rendertarget, render_scene);
+        }
     }
 
     pub fn geometry_pass<F>(&mut self, render_scene: &F) -> Result<(), Error>
         where F: Fn()
     {
-        self.geometry_pass_texture = Texture2DArray::new_as_color_targets(&self.gl, self.geometry_pass_texture.width, self.geometry_pass_texture.height, 2)?;
+        self.geometry_pass_texture = Texture2DArray::new_as_color_targets(&self.gl, self.geometry_pass_texture.width, self.geometry_pass_texture.height, GEOMETRY_PASS_LAYERS)?;
         self.geometry_pass_depth_texture = Texture2DArray::new_as_depth_targets(&self.gl, self.geometry_pass_depth_texture.width, self.geometry_pass_depth_texture.height, 1)?;
 
         self.geometry_pass_rendertarget.write_to_color_array_and_depth_array(&self.geometry_pass_texture,
@@ -132,14 +268,143 @@ impl DeferredPipeline
         Ok(())
     }
 
+    /// Must be called after the geometry pass and before the light pass. Changes the bound render target.
+    pub fn ssao_pass(&self, camera: &Camera) -> Result<(), Error>
+    {
+        if let Some(ref effect) = self.ssao_effect {
+            effect.apply(camera, &self.geometry_pass_texture, &self.geometry_pass_depth_texture)?;
+        }
+        Ok(())
+    }
+
+    pub fn enable_ssao(&mut self) -> Result<(), Error>
+    {
+        if self.ssao_effect.is_none() {
+            self.ssao_effect = Some(effects::SsaoEffect::new(&self.gl, self.geometry_pass_texture.width, self.geometry_pass_texture.height)?);
+        }
+        Ok(())
+    }
+
+    pub fn disable_ssao(&mut self)
+    {
+        self.ssao_effect = None;
+    }
+
+    pub fn ssao_effect(&mut self) -> Option<&mut effects::SsaoEffect>
+    {
+        self.ssao_effect.as_mut()
+    }
+
+    pub fn ambient_occlusion_texture(&self) -> Option<&Texture2D>
+    {
+        self.ssao_effect.as_ref().map(|effect| effect.occlusion_texture())
+    }
+
+    pub fn hdr_light_pass(&self, camera: &Camera) -> Result<(), Error>
+    {
+        self.write_to_hdr_texture()?;
+        self.hdr_rendertarget.clear_color(&vec4(0.0, 0.0, 0.0, 0.0));
+        self.light_pass_render_to_rendertarget(camera)?;
+        Ok(())
+    }
+
+    /// Must be called before the geometry pass of each frame.
+    pub fn jitter_camera(&mut self, camera: &mut Camera)
+    {
+        if self.taa_effect.is_some() {
+            self.jitter_index = (self.jitter_index + 1) % JITTER_SEQUENCE_LENGTH;
+            let offset = vec2(halton(self.jitter_index + 1, 2) - 0.5, halton(self.jitter_index + 1, 3) - 0.5);
+            camera.set_jitter(vec2(2.0 * offset.x / self.hdr_texture.width as f32, 2.0 * offset.y / self.hdr_texture.height as f32));
+        } else {
+            camera.set_jitter(vec2(0.0, 0.0));
+        }
+    }
+
+    /// Must be called after the light pass and before the post processing. Changes the bound render target.
+    pub fn anti_aliasing_pass(&mut self, camera: &Camera) -> Result<(), Error>
+    {
+        if self.fxaa_effect.is_none() && self.taa_effect.is_none() {
+            return Ok(());
+        }
+        if self.anti_aliased_texture.is_none() {
+            self.anti_aliased_texture = Some(Texture2D::new_as_hdr_color_target(&self.gl, self.hdr_texture.width, self.hdr_texture.height)?);
+        }
+        let mut anti_aliased_texture = self.anti_aliased_texture.take().unwrap();
+        if let Some(ref mut effect) = self.taa_effect {
+            effect.resolve(camera, &self.hdr_texture, &self.geometry_pass_depth_texture)?;
+        }
+        self.hdr_rendertarget.write_to_color(&anti_aliased_texture)?;
+        match (&self.fxaa_effect, &self.taa_effect) {
+            (Some(fxaa), Some(taa)) => fxaa.apply(taa.output_texture())?,
+            (Some(fxaa), None) => fxaa.apply(&self.hdr_texture)?,
+            (None, Some(taa)) => taa.apply()?,
+            (None, None) => {}
+        }
+        std::mem::swap(&mut self.hdr_texture, &mut anti_aliased_texture);
+        self.anti_aliased_texture = Some(anti_aliased_texture);
+        Ok(())
+    }
+
+    pub fn enable_fxaa(&mut self) -> Result<(), Error>
+    {
+        if self.fxaa_effect.is_none() {
+            self.fxaa_effect = Some(effects::FxaaEffect::new(&self.gl)?);
+        }
+        Ok(())
+    }
+
+    pub fn disable_fxaa(&mut self)
+    {
+        self.fxaa_effect = None;
+    }
+
+    pub fn enable_taa(&mut self) -> Result<(), Error>
+    {
+        if self.taa_effect.is_none() {
+            self.taa_effect = Some(effects::TaaEffect::new(&self.gl, self.hdr_texture.width, self.hdr_texture.height)?);
+        }
+        Ok(())
+    }
+
+    pub fn disable_taa(&mut self)
+    {
+        self.taa_effect = None;
+    }
+
+    pub fn taa_effect(&mut self) -> Option<&mut effects::TaaEffect>
+    {
+        self.taa_effect.as_mut()
+    }
+
+    pub fn write_to_hdr_texture(&self) -> Result<(), Error>
+    {
+        self.hdr_rendertarget.write_to_color(&self.hdr_texture)?;
+        Ok(())
+    }
+
+    /// The colors are clamped to the screen, use `hdr_light_pass` and a `ToneMappingEffect` to map the bright colors instead.
     pub fn light_pass(&self, camera: &Camera) -> Result<(), Error>
     {
-        ScreenRendertarget::write(&self.gl, self.geometry_pass_texture.width, self.geometry_pass_texture.height);
+        self.hdr_light_pass(camera)?;
+        ScreenRendertarget::write(&self.gl, self.hdr_texture.width, self.hdr_texture.height);
         ScreenRendertarget::clear_color_and_depth(&self.gl, &vec4(0.0, 0.0, 0.0, 0.0));
-        self.light_pass_render_to_rendertarget(camera)?;
+
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::None);
+
+        let program = &self.hdr_copy_program;
+        program.use_texture(&self.hdr_texture, "hdrMap")?;
+        program.add_uniform_vec2("screenSize", &self.screen_size())?;
+        program.add_uniform_mat4("viewProjection", &Mat4::identity())?;
+        program.add_uniform_mat4("modelMatrix", &Mat4::identity())?;
+        program.use_attribute_vec3_float(&self.full_screen, "position", 0)?;
+        program.draw_arrays(3);
         Ok(())
     }
 
+    /// The light is only accumulated beyond 1 if the bound target is a floating point texture like the `hdr_texture`.
     pub fn light_pass_render_to_rendertarget(&self, camera: &Camera) -> Result<(), Error>
     {
         state::depth_write(&self.gl,false);
@@ -147,72 +412,445 @@ impl DeferredPipeline
         state::cull(&self.gl,state::CullType::Back);
         state::blend(&self.gl, state::BlendType::OneOne);
 
-        self.light_pass_program.use_texture(self.geometry_pass_texture(), "gbuffer")?;
-        self.light_pass_program.use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
-
-        self.light_pass_program.add_uniform_vec3("eyePosition", &camera.position())?;
-        self.light_pass_program.add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
-
         // Ambient light
-        self.light_pass_program.add_uniform_vec3("ambientLight.base.color", &self.ambient_light.color())?;
-        self.light_pass_program.add_uniform_float("ambientLight.base.intensity", &self.ambient_light.intensity())?;
+        let program = &self.ambient_light_program;
+        program.use_texture(self.geometry_pass_texture(), "gbuffer")?;
+        program.use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+        program.use_texture(self.ambient_occlusion_texture().unwrap_or(&self.no_ambient_occlusion), "ambientOcclusionMap")?;
+        program.add_uniform_vec2("screenSize", &self.screen_size())?;
+        program.add_uniform_vec3("ambientLight.base.color", &self.ambient_light.color())?;
+        program.add_uniform_float("ambientLight.base.intensity", &self.ambient_light.intensity())?;
+        self.draw_light_volume(program, camera, &LightVolume::FullScreen)?;
+
+        if let Some(ref light) = self.environment_light {
+            let program = &self.environment_light_program;
+            self.use_geometry_pass(program, camera)?;
+            program.use_texture(light.irradiance_map(), "irradianceMap")?;
+            program.use_texture(light.prefiltered_map(), "prefilteredMap")?;
+            program.use_texture(light.brdf_lut(), "brdfLUT")?;
+            program.add_uniform_float("maxPrefilteredLod", &light.max_prefiltered_lod())?;
+            program.add_uniform_float("environmentIntensity", &light.intensity())?;
+            self.draw_light_volume(program, camera, &LightVolume::FullScreen)?;
+        }
 
         // Directional lights
-        self.light_pass_program.use_texture(self.directional_lights.shadow_maps(), "directionalLightShadowMaps")?;
-        self.light_pass_program.use_uniform_block(self.directional_lights.buffer(), "DirectionalLights");
+        for light in self.directional_lights.iter().filter(|light| light.intensity() > 0.0) {
+            let program = &self.directional_light_program;
+            self.use_geometry_pass(program, camera)?;
+            program.use_texture(light.shadow_maps().unwrap_or(&self.no_shadow_maps), "shadowMaps")?;
+            program.add_uniform_vec3("viewDirection", &(*camera.target() - *camera.position()).normalize())?;
+            program.use_uniform_block(light.buffer(), "DirectionalLightUniform");
+            self.draw_light_volume(program, camera, &LightVolume::FullScreen)?;
+        }
 
         // Point lights
-        self.light_pass_program.use_uniform_block(self.point_lights.buffer(), "PointLights");
+        for light in self.point_lights.iter().filter(|light| light.intensity() > 0.0) {
+            let volume = match light.range() {
+                Some(range) => sphere_light_volume(camera, light.position(), range),
+                None => LightVolume::FullScreen
+            };
+            if let LightVolume::Outside = volume { continue; }
+
+            let program = &self.point_light_program;
+            self.use_geometry_pass(program, camera)?;
+            program.use_texture(light.shadow_map().unwrap_or(&self.no_shadow_cube_map), "shadowMap")?;
+            program.use_uniform_block(light.buffer(), "PointLightUniform");
+            self.draw_light_volume(program, camera, &volume)?;
+        }
 
         // Spot lights
-        self.light_pass_program.use_texture(self.spot_lights.shadow_maps(), "spotLightShadowMaps")?;
-        self.light_pass_program.use_uniform_block(self.spot_lights.buffer(), "SpotLights");
+        for light in self.spot_lights.iter().filter(|light| light.intensity() > 0.0) {
+            let volume = match light.range() {
+                Some(range) => spot_light_volume(camera, light.position(), light.direction(), light.cutoff(), range),
+                None => LightVolume::FullScreen
+            };
+            if let LightVolume::Outside = volume { continue; }
+
+            let program = &self.spot_light_program;
+            self.use_geometry_pass(program, camera)?;
+            program.use_texture(light.shadow_map().unwrap_or(&self.no_shadow_map), "shadowMap")?;
+            program.use_uniform_block(light.buffer(), "SpotLightUniform");
+            self.draw_light_volume(program, camera, &volume)?;
+        }
+        Ok(())
+    }
 
-        // Render
-        self.light_pass_program.use_attribute_vec3_float(&self.full_screen, "position", 0).unwrap();
-        self.light_pass_program.use_attribute_vec2_float(&self.full_screen, "uv_coordinate", 1).unwrap();
-        self.light_pass_program.draw_arrays(3);
+    /// Must be called after the geometry pass, since the meshes are hidden by the opaque surfaces in the geometry pass depth texture.
+    pub fn transparent_pass(&mut self, camera: &Camera, meshes: &[(&Mesh, Mat4)]) -> Result<(), Error>
+    {
+        self.transparent_rendertarget.write_to_color_array_and_depth_array(&self.transparent_texture, &self.geometry_pass_depth_texture,
+                                                                           &|channel| {channel}, 0)?;
+        self.transparent_rendertarget.clear_color(&vec4(0.0, 0.0, 0.0, 0.0));
+
+        state::depth_write(&self.gl, false);
+        state::depth_test(&self.gl, state::DepthTestType::Less);
+
+        let mut meshes: Vec<&(&Mesh, Mat4)> = meshes.iter().filter(|(mesh, _)| mesh.opacity > 0.0).collect();
+        if self.transparency_mode == TransparencyMode::Sorted {
+            let distance = |(mesh, transformation): &(&Mesh, Mat4)| (transformed_center(mesh, transformation) - *camera.position()).magnitude2();
+            meshes.sort_by(|a, b| distance(b).partial_cmp(&distance(a)).unwrap_or(std::cmp::Ordering::Equal));
+        }
+
+        for (mesh, transformation) in meshes {
+            self.update_transparent_material(mesh)?;
+            match self.transparency_mode {
+                TransparencyMode::Sorted => {
+                    state::cull(&self.gl, state::CullType::Front);
+                    self.render_transparent_mesh(camera, mesh, transformation)?;
+                    state::cull(&self.gl, state::CullType::Back);
+                    self.render_transparent_mesh(camera, mesh, transformation)?;
+                },
+                TransparencyMode::WeightedBlended => {
+                    state::cull(&self.gl, state::CullType::None);
+                    self.render_transparent_mesh(camera, mesh, transformation)?;
+                }
+            }
+        }
         Ok(())
     }
 
+    pub fn transparent_composite_pass(&self) -> Result<(), Error>
+    {
+        ScreenRendertarget::write(&self.gl, self.transparent_texture.width, self.transparent_texture.height);
+        self.transparent_composite_pass_render_to_rendertarget()
+    }
+
+    pub fn transparent_composite_pass_render_to_rendertarget(&self) -> Result<(), Error>
+    {
+        state::depth_write(&self.gl,false);
+        state::depth_test(&self.gl, state::DepthTestType::None);
+        state::cull(&self.gl,state::CullType::Back);
+        state::blend(&self.gl, state::BlendType::OneOneMinusSrcAlpha);
+
+        let program = &self.transparent_composite_program;
+        program.use_texture(&self.transparent_texture, "transparentLayers")?;
+        program.add_uniform_vec2("screenSize", &self.screen_size())?;
+        program.add_uniform_int("weightedBlended", &((self.transparency_mode == TransparencyMode::WeightedBlended) as i32))?;
+        program.add_uniform_mat4("viewProjection", &Mat4::identity())?;
+        program.add_uniform_mat4("modelMatrix", &Mat4::identity())?;
+        program.use_attribute_vec3_float(&self.full_screen, "position", 0)?;
+        program.draw_arrays(3);
+        Ok(())
+    }
+
+    fn update_transparent_material(&mut self, mesh: &Mesh) -> Result<(), Error>
+    {
+        let material = &mut self.transparent_material;
+        match mesh.physical_material {
+            Some(ref physical_material) => {
+                material.update(0, &physical_material.albedo.to_slice())?;
+                material.update(2, &physical_material.emissive.to_slice())?;
+                material.update(3, &[1.0])?;
+                material.update(7, &[physical_material.metallic])?;
+                material.update(8, &[physical_material.roughness])?;
+                material.update(9, &[physical_material.ambient_occlusion])?;
+            },
+            None => {
+                material.update(0, &mesh.color.to_slice())?;
+                material.update(2, &[0.0, 0.0, 0.0])?;
+                material.update(3, &[0.0])?;
+                material.update(9, &[1.0])?;
+            }
+        }
+        material.update(1, &[mesh.opacity.min(1.0)])?;
+        material.update(4, &[mesh.diffuse_intensity])?;
+        material.update(5, &[mesh.specular_intensity])?;
+        material.update(6, &[mesh.specular_power])?;
+        Ok(())
+    }
+
+    fn render_transparent_mesh(&self, camera: &Camera, mesh: &Mesh, transformation: &Mat4) -> Result<(), Error>
+    {
+        let weighted_blended = self.transparency_mode == TransparencyMode::WeightedBlended;
+        let draw = |program: &program::Program, base_pass: bool| -> Result<(), Error> {
+            state::blend(&self.gl, if base_pass && !weighted_blended {state::BlendType::OneOneMinusSrcAlpha} else {state::BlendType::OneOne});
+            program.add_uniform_int("basePass", &(base_pass as i32))?;
+            program.add_uniform_int("weightedBlended", &(weighted_blended as i32))?;
+            program.add_uniform_vec3("eyePosition", camera.position())?;
+            program.use_uniform_block(&self.transparent_material, "TransparentMaterial");
+            mesh.render_geometry(program, transformation, camera, !base_pass)?;
+            Ok(())
+        };
+
+        let aabb = mesh.axis_aligned_bounding_box();
+        let center = transformed_center(mesh, transformation);
+        let radius = (transformation.transform_point(Point::from_vec(aabb.max)) - transformation.transform_point(Point::from_vec(aabb.min))).magnitude() * 0.5;
+        let is_reached = |position: Vec3, range: Option<f32>| range.map(|range| (center - position).magnitude() < range + radius).unwrap_or(true);
+
+        let program = &self.transparent_ambient_light_program;
+        program.add_uniform_vec3("ambientLight.base.color", &self.ambient_light.color())?;
+        program.add_uniform_float("ambientLight.base.intensity", &self.ambient_light.intensity())?;
+        draw(program, true)?;
+
+        if let Some(ref light) = self.environment_light {
+            let program = &self.transparent_environment_light_program;
+            program.use_texture(light.irradiance_map(), "irradianceMap")?;
+            program.use_texture(light.prefiltered_map(), "prefilteredMap")?;
+            program.use_texture(light.brdf_lut(), "brdfLUT")?;
+            program.add_uniform_float("maxPrefilteredLod", &light.max_prefiltered_lod())?;
+            program.add_uniform_float("environmentIntensity", &light.intensity())?;
+            draw(program, false)?;
+        }
+
+        for light in self.directional_lights.iter().filter(|light| light.intensity() > 0.0) {
+            let program = &self.transparent_directional_light_program;
+            program.use_texture(light.shadow_maps().unwrap_or(&self.no_shadow_maps), "shadowMaps")?;
+            program.add_uniform_vec3("viewDirection", &(*camera.target() - *camera.position()).normalize())?;
+            program.use_uniform_block(light.buffer(), "DirectionalLightUniform");
+            draw(program, false)?;
+        }
+
+        for light in self.point_lights.iter().filter(|light| light.intensity() > 0.0 && is_reached(light.position(), light.range())) {
+            let program = &self.transparent_point_light_program;
+            program.use_texture(light.shadow_map().unwrap_or(&self.no_shadow_cube_map), "shadowMap")?;
+            program.use_uniform_block(light.buffer(), "PointLightUniform");
+            draw(program, false)?;
+        }
+
+        for light in self.spot_lights.iter().filter(|light| light.intensity() > 0.0 && is_reached(light.position(), light.range())) {
+            let program = &self.transparent_spot_light_program;
+            program.use_texture(light.shadow_map().unwrap_or(&self.no_shadow_map), "shadowMap")?;
+            program.use_uniform_block(light.buffer(), "SpotLightUniform");
+            draw(program, false)?;
+        }
+        Ok(())
+    }
+
+    fn use_geometry_pass(&self, program: &program::Program, camera: &Camera) -> Result<(), Error>
+    {
+        program.use_texture(self.geometry_pass_texture(), "gbuffer")?;
+        program.use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+        program.add_uniform_vec2("screenSize", &self.screen_size())?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        program.add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
+        Ok(())
+    }
+
+    fn draw_light_volume(&self, program: &program::Program, camera: &Camera, volume: &LightVolume) -> Result<(), Error>
+    {
+        match volume {
+            LightVolume::Sphere(model) | LightVolume::Cone(model) => {
+                let (vertex_buffer, element_buffer) = if let LightVolume::Sphere(_) = volume {&self.sphere} else {&self.cone};
+                program.add_uniform_mat4("viewProjection", &(camera.get_projection() * camera.get_view()))?;
+                program.add_uniform_mat4("modelMatrix", model)?;
+                program.use_attribute_vec3_float(vertex_buffer, "position", 0)?;
+                program.draw_elements(element_buffer);
+            },
+            LightVolume::FullScreen => {
+                program.add_uniform_mat4("viewProjection", &Mat4::identity())?;
+                program.add_uniform_mat4("modelMatrix", &Mat4::identity())?;
+                program.use_attribute_vec3_float(&self.full_screen, "position", 0)?;
+                program.draw_arrays(3);
+            },
+            LightVolume::Outside => {}
+        }
+        Ok(())
+    }
+
+    fn screen_size(&self) -> Vec2
+    {
+        vec2(self.geometry_pass_texture.width as f32, self.geometry_pass_texture.height as f32)
+    }
+
     pub fn ambient_light(&mut self) -> &mut AmbientLight
     {
         &mut self.ambient_light
     }
 
+    pub fn set_environment_light(&mut self, light: Option<EnvironmentLight>)
+    {
+        self.environment_light = light;
+    }
+
+    pub fn environment_light(&mut self) -> Option<&mut EnvironmentLight>
+    {
+        self.environment_light.as_mut()
+    }
+
+    /// Missing lights up to the index are created with zero intensity.
     pub fn directional_light(&mut self, index: usize) -> Result<&mut DirectionalLight, Error>
     {
-        if index >= light::MAX_NO_LIGHTS
-        {
-            return Err(Error::LightExtendsMaxLimit {message: format!("Tried to get directional light number {}, but the limit is {}.", index, light::MAX_NO_LIGHTS)})
+        while self.directional_lights.len() <= index {
+            self.directional_lights.push(DirectionalLight::new(&self.gl)?);
         }
-        Ok(self.directional_lights.light_at(index))
+        Ok(&mut self.directional_lights[index])
+    }
+
+    pub fn no_directional_lights(&self) -> usize
+    {
+        self.directional_lights.len()
     }
 
     pub fn point_light(&mut self, index: usize) -> Result<&mut PointLight, Error>
     {
-        if index >= light::MAX_NO_LIGHTS
-        {
-            return Err(Error::LightExtendsMaxLimit {message: format!("Tried to get point light number {}, but the limit is {}.", index, light::MAX_NO_LIGHTS)})
+        while self.point_lights.len() <= index {
+            self.point_lights.push(PointLight::new(&self.gl)?);
         }
-        Ok(self.point_lights.light_at(index))
+        Ok(&mut self.point_lights[index])
+    }
+
+    pub fn no_point_lights(&self) -> usize
+    {
+        self.point_lights.len()
     }
 
     pub fn spot_light(&mut self, index: usize) -> Result<&mut SpotLight, Error>
     {
-        if index >= light::MAX_NO_LIGHTS
-        {
-            return Err(Error::LightExtendsMaxLimit {message: format!("Tried to get spot light number {}, but the limit is {}.", index, light::MAX_NO_LIGHTS)})
+        while self.spot_lights.len() <= index {
+            self.spot_lights.push(SpotLight::new(&self.gl)?);
         }
-        Ok(self.spot_lights.light_at(index))
+        Ok(&mut self.spot_lights[index])
+    }
+
+    pub fn no_spot_lights(&self) -> usize
+    {
+        self.spot_lights.len()
     }
 
     pub fn geometry_pass_texture(&self) -> &Texture2DArray
     {
         &self.geometry_pass_texture
     }
+    pub fn hdr_texture(&self) -> &Texture2D
+    {
+        &self.hdr_texture
+    }
+
     pub fn geometry_pass_depth_texture(&self) -> &Texture2DArray
     {
         &self.geometry_pass_depth_texture
     }
-}
\ No newline at end of file
+}
+
+const JITTER_SEQUENCE_LENGTH: usize = 8;
+
+fn halton(index: usize, base: usize) -> f32
+{
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    let mut i = index;
+    while i > 0 {
+        fraction /= base as f32;
+        result += fraction * (i % base) as f32;
+        i /= base;
+    }
+    result
+}
+
+fn transformed_center(mesh: &Mesh, transformation: &Mat4) -> Vec3
+{
+    let aabb = mesh.axis_aligned_bounding_box();
+    transformation.transform_point(Point::from_vec(0.5 * (aabb.min + aabb.max))).to_vec()
+}
+
+fn sphere_light_volume(camera: &Camera, position: Vec3, range: f32) -> LightVolume
+{
+    match bounding_sphere_visibility(camera, position, range) {
+        LightVolume::Sphere(_) => LightVolume::Sphere(Mat4::from_translation(position) * Mat4::from_scale(range)),
+        visibility => visibility
+    }
+}
+
+fn spot_light_volume(camera: &Camera, position: Vec3, direction: Vec3, cutoff: f32, range: f32) -> LightVolume
+{
+    let angle = cutoff.clamp(-1.0, 1.0).acos();
+    if angle > 80f32.to_radians() {
+        return sphere_light_volume(camera, position, range);
+    }
+    match bounding_sphere_visibility(camera, position, range) {
+        LightVolume::Sphere(_) => {
+            let x = if direction.x.abs() > 0.9 {vec3(0.0, 1.0, 0.0)} else {vec3(1.0, 0.0, 0.0)}.cross(direction).normalize();
+            let y = direction.cross(x);
+            let rotation = Mat4::from_cols(x.extend(0.0), y.extend(0.0), direction.extend(0.0), vec4(0.0, 0.0, 0.0, 1.0));
+            let radius = range * angle.tan();
+            LightVolume::Cone(Mat4::from_translation(position) * rotation * Mat4::from_nonuniform_scale(radius, radius, range))
+        },
+        visibility => visibility
+    }
+}
+
+fn bounding_sphere_visibility(camera: &Camera, center: Vec3, radius: f32) -> LightVolume
+{
+    if radius <= 0.0 || !camera.in_frustrum(&(center - vec3(radius, radius, radius)), &(center + vec3(radius, radius, radius))) {
+        return LightVolume::Outside;
+    }
+    let view_direction = (*camera.target() - *camera.position()).normalize();
+    let distance_to_near_plane = (center - *camera.position()).dot(view_direction) - camera.z_near();
+    if distance_to_near_plane < radius {
+        LightVolume::FullScreen
+    }
+    else {
+        LightVolume::Sphere(Mat4::identity())
+    }
+}
+
+fn sphere_volume(gl: &Gl) -> Result<(VertexBuffer, ElementBuffer), Error>
+{
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut positions = vec![vec3(-1.0, t, 0.0), vec3(1.0, t, 0.0), vec3(-1.0, -t, 0.0), vec3(1.0, -t, 0.0),
+                             vec3(0.0, -1.0, t), vec3(0.0, 1.0, t), vec3(0.0, -1.0, -t), vec3(0.0, 1.0, -t),
+                             vec3(t, 0.0, -1.0), vec3(t, 0.0, 1.0), vec3(-t, 0.0, -1.0), vec3(-t, 0.0, 1.0)];
+    let icosahedron: [u32; 60] = [0, 11, 5, 0, 5, 1, 0, 1, 7, 0, 7, 10, 0, 10, 11,
+                                  1, 5, 9, 5, 11, 4, 11, 10, 2, 10, 7, 6, 7, 1, 8,
+                                  3, 9, 4, 3, 4, 2, 3, 2, 6, 3, 6, 8, 3, 8, 9,
+                                  4, 9, 5, 2, 4, 11, 6, 2, 10, 8, 6, 7, 9, 8, 1];
+
+    let mut indices = Vec::new();
+    let mut midpoints = std::collections::HashMap::new();
+    for triangle in icosahedron.chunks(3) {
+        let mut mid = [0u32; 3];
+        for i in 0..3 {
+            let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+            mid[i] = *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push(0.5 * (positions[a as usize] + positions[b as usize]));
+                positions.len() as u32 - 1
+            });
+        }
+        indices.extend_from_slice(&[triangle[0], mid[0], mid[2], triangle[1], mid[1], mid[0],
+                                    triangle[2], mid[2], mid[1], mid[0], mid[1], mid[2]]);
+    }
+    for position in positions.iter_mut() {
+        *position = position.normalize();
+    }
+    orient_outwards(&positions, &mut indices, vec3(0.0, 0.0, 0.0));
+
+    let inradius = indices.chunks(3).map(|triangle| {
+        let (p0, p1, p2) = (positions[triangle[0] as usize], positions[triangle[1] as usize], positions[triangle[2] as usize]);
+        (p1 - p0).cross(p2 - p0).normalize().dot(p0)
+    }).fold(1.0, f32::min);
+    let positions: Vec<Vec3> = positions.iter().map(|p| p / inradius).collect();
+    volume_buffers(gl, &positions, &indices)
+}
+
+fn cone_volume(gl: &Gl) -> Result<(VertexBuffer, ElementBuffer), Error>
+{
+    let no_segments = 16;
+    let radius = 1.0 / (std::f32::consts::PI / no_segments as f32).cos();
+    let mut positions = vec![vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0)];
+    let mut indices = Vec::new();
+    for i in 0..no_segments {
+        let angle = 2.0 * std::f32::consts::PI * i as f32 / no_segments as f32;
+        positions.push(vec3(radius * angle.cos(), radius * angle.sin(), 1.0));
+        let (current, next) = (2 + i as u32, 2 + ((i + 1) % no_segments) as u32);
+        indices.extend_from_slice(&[0, current, next, 1, next, current]);
+    }
+    orient_outwards(&positions, &mut indices, vec3(0.0, 0.0, 0.5));
+    volume_buffers(gl, &positions, &indices)
+}
+
+// Orients the triangles of a convex mesh counter clockwise seen from the outside, so only the front of the volume is drawn when back faces are culled.
+fn orient_outwards(positions: &[Vec3], indices: &mut [u32], interior_point: Vec3)
+{
+    for triangle in indices.chunks_mut(3) {
+        let (p0, p1, p2) = (positions[triangle[0] as usize], positions[triangle[1] as usize], positions[triangle[2] as usize]);
+        if (p1 - p0).cross(p2 - p0).dot((p0 + p1 + p2) / 3.0 - interior_point) < 0.0 {
+            triangle.swap(1, 2);
+        }
+    }
+}
+
+fn volume_buffers(gl: &Gl, positions: &[Vec3], indices: &[u32]) -> Result<(VertexBuffer, ElementBuffer), Error>
+{
+    let data: Vec<f32> = positions.iter().flat_map(|p| vec![p.x, p.y, p.z]).collect();
+    Ok((VertexBuffer::new_with_one_static_attribute(gl, &data)?, ElementBuffer::new_with(gl, indices)?))
+}
@@ -0,0 +1,391 @@
+use crate::*;
+use std::rc::Rc;
+use std::cell::Cell;
+
+#[derive(Debug)]
+pub enum Error {
+    Renderer(renderer::Error),
+    NodeNotFound {message: String},
+    InvalidParent {message: String}
+}
+
+impl From<renderer::Error> for Error {
+    fn from(other: renderer::Error) -> Self {
+        Error::Renderer(other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeTransform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3
+}
+
+impl NodeTransform {
+
+    pub fn from_translation(translation: Vec3) -> NodeTransform
+    {
+        NodeTransform {translation, ..Default::default()}
+    }
+
+    pub fn from_rotation(rotation: Quat) -> NodeTransform
+    {
+        NodeTransform {rotation, ..Default::default()}
+    }
+
+    pub fn from_scale(scale: f32) -> NodeTransform
+    {
+        NodeTransform {scale: vec3(scale, scale, scale), ..Default::default()}
+    }
+
+    pub fn matrix(&self) -> Mat4
+    {
+        Mat4::from_translation(self.translation) * Mat4::from(self.rotation) * Mat4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+impl Default for NodeTransform {
+    fn default() -> Self
+    {
+        NodeTransform {translation: vec3(0.0, 0.0, 0.0), rotation: Quat::one(), scale: vec3(1.0, 1.0, 1.0)}
+    }
+}
+
+#[derive(Clone)]
+pub enum Renderable {
+    Mesh(Rc<Mesh>),
+    InstancedMesh(Rc<InstancedMesh>),
+    Wireframe(Rc<Wireframe>),
+    ShadedVertices(Rc<ShadedVertices>),
+    /// Placed at its own positions, see `Imposter::update_positions`, so the transformation of the node is ignored.
+    Imposter(Rc<Imposter>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneLight {
+    Directional(usize),
+    Point(usize),
+    Spot(usize)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+struct Node {
+    transform: NodeTransform,
+    world_transformation: Mat4,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    renderables: Vec<Renderable>,
+    lod_levels: Vec<Cell<usize>>,
+    lights: Vec<SceneLight>,
+    visible: bool
+}
+
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    roots: Vec<NodeId>,
+    culling: FrustumCulling
+}
+
+impl Scene {
+
+    pub fn new() -> Scene
+    {
+        Scene {nodes: Vec::new(), roots: Vec::new(), culling: FrustumCulling::new()}
+    }
+
+    pub fn add_node(&mut self, parent: Option<NodeId>, transform: NodeTransform) -> Result<NodeId, Error>
+    {
+        if let Some(parent) = parent {
+            self.node(parent)?;
+        }
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Some(Node {transform, world_transformation: Mat4::identity(), parent, children: Vec::new(),
+            renderables: Vec::new(), lod_levels: Vec::new(), lights: Vec::new(), visible: true}));
+        match parent {
+            Some(parent) => self.node_mut(parent)?.children.push(id),
+            None => self.roots.push(id)
+        }
+        self.update_world_transformations(id)?;
+        Ok(id)
+    }
+
+    pub fn remove_node(&mut self, id: NodeId) -> Result<(), Error>
+    {
+        self.detach_from_parent(id)?;
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.nodes[id.0].take() {
+                stack.extend(node.children);
+            }
+        }
+        Ok(())
+    }
+
+    /// The transform relative to the parent is kept, so the node moves with its new parent.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), Error>
+    {
+        self.node(id)?;
+        if let Some(parent) = parent {
+            let mut ancestor = Some(parent);
+            while let Some(current) = ancestor {
+                if current == id {
+                    Err(Error::InvalidParent {message: format!("The node {:?} cannot be a child of itself or of one of its descendants", id)})?;
+                }
+                ancestor = self.node(current)?.parent;
+            }
+        }
+        self.detach_from_parent(id)?;
+        self.node_mut(id)?.parent = parent;
+        match parent {
+            Some(parent) => self.node_mut(parent)?.children.push(id),
+            None => self.roots.push(id)
+        }
+        self.update_world_transformations(id)
+    }
+
+    pub fn parent(&self, id: NodeId) -> Result<Option<NodeId>, Error>
+    {
+        Ok(self.node(id)?.parent)
+    }
+
+    pub fn children(&self, id: NodeId) -> Result<&[NodeId], Error>
+    {
+        Ok(&self.node(id)?.children)
+    }
+
+    pub fn roots(&self) -> &[NodeId]
+    {
+        &self.roots
+    }
+
+    pub fn transform(&self, id: NodeId) -> Result<&NodeTransform, Error>
+    {
+        Ok(&self.node(id)?.transform)
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: NodeTransform) -> Result<(), Error>
+    {
+        self.node_mut(id)?.transform = transform;
+        self.update_world_transformations(id)
+    }
+
+    pub fn world_transformation(&self, id: NodeId) -> Result<Mat4, Error>
+    {
+        Ok(self.node(id)?.world_transformation)
+    }
+
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) -> Result<(), Error>
+    {
+        self.node_mut(id)?.visible = visible;
+        Ok(())
+    }
+
+    pub fn attach(&mut self, id: NodeId, renderable: Renderable) -> Result<(), Error>
+    {
+        let node = self.node_mut(id)?;
+        node.renderables.push(renderable);
+        node.lod_levels.push(Cell::new(0));
+        Ok(())
+    }
+
+    pub fn attach_light(&mut self, id: NodeId, light: SceneLight) -> Result<(), Error>
+    {
+        self.node_mut(id)?.lights.push(light);
+        Ok(())
+    }
+
+    pub fn renderables(&self, id: NodeId) -> Result<&[Renderable], Error>
+    {
+        Ok(&self.node(id)?.renderables)
+    }
+
+    pub fn lod_levels(&self, id: NodeId) -> Result<Vec<usize>, Error>
+    {
+        Ok(self.node(id)?.lod_levels.iter().map(|level| level.get()).collect())
+    }
+
+    pub fn update_lods(&self, camera: &Camera)
+    {
+        for node in self.nodes.iter().flatten() {
+            for (renderable, level) in node.renderables.iter().zip(node.lod_levels.iter()) {
+                if let Renderable::Mesh(mesh) = renderable {
+                    level.set(mesh.select_lod(&node.world_transformation, camera, level.get()));
+                }
+            }
+        }
+    }
+
+    pub fn frustum_culling(&self) -> &FrustumCulling
+    {
+        &self.culling
+    }
+
+    pub fn frustum_culling_mut(&mut self) -> &mut FrustumCulling
+    {
+        &mut self.culling
+    }
+
+    pub fn render(&self, camera: &Camera)
+    {
+        let mut stack: Vec<NodeId> = self.roots.clone();
+        while let Some(id) = stack.pop() {
+            let node = self.nodes[id.0].as_ref().unwrap();
+            if !node.visible {
+                continue;
+            }
+            for (renderable, level) in node.renderables.iter().zip(node.lod_levels.iter()) {
+                match renderable {
+                    Renderable::Mesh(mesh) => {
+                        if self.culling.is_visible(mesh.axis_aligned_bounding_box(), &node.world_transformation, camera) {
+                            mesh.render_lod(level.get(), &node.world_transformation, camera);
+                        }
+                    },
+                    Renderable::InstancedMesh(mesh) => {
+                        if self.culling.is_visible(mesh.axis_aligned_bounding_box(), &node.world_transformation, camera) {
+                            mesh.render(&node.world_transformation, camera);
+                        }
+                    },
+                    Renderable::Wireframe(wireframe) => wireframe.render(&node.world_transformation, camera),
+                    Renderable::ShadedVertices(vertices) => vertices.render(&node.world_transformation, camera),
+                    Renderable::Imposter(imposter) => imposter.render(camera)
+                }
+            }
+            stack.extend(node.children.iter());
+        }
+    }
+
+    pub fn update_lights(&self, pipeline: &mut DeferredPipeline) -> Result<(), Error>
+    {
+        for node in self.nodes.iter().flatten() {
+            let position = (node.world_transformation * vec4(0.0, 0.0, 0.0, 1.0)).truncate();
+            let direction = (node.world_transformation * vec4(0.0, 0.0, -1.0, 0.0)).truncate().normalize();
+            for light in node.lights.iter() {
+                match *light {
+                    SceneLight::Directional(index) => pipeline.directional_light(index)?.set_direction(&direction),
+                    SceneLight::Point(index) => pipeline.point_light(index)?.set_position(&position),
+                    SceneLight::Spot(index) => {
+                        let spot_light = pipeline.spot_light(index)?;
+                        spot_light.set_position(&position);
+                        spot_light.set_direction(&direction);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn shadow_pass(&self, pipeline: &mut DeferredPipeline, camera: &Camera) -> Result<(), Error>
+    {
+        self.update_lights(pipeline)?;
+        pipeline.shadow_pass(camera, &|shadow_camera: &Camera| self.render(shadow_camera));
+        Ok(())
+    }
+
+    pub fn geometry_pass(&self, pipeline: &mut DeferredPipeline, camera: &Camera) -> Result<(), Error>
+    {
+        self.update_lods(camera);
+        pipeline.geometry_pass(&|| self.render(camera))?;
+        Ok(())
+    }
+
+    fn node(&self, id: NodeId) -> Result<&Node, Error>
+    {
+        self.nodes.get(id.0).and_then(|node| node.as_ref())
+            .ok_or_else(|| Error::NodeNotFound {message: format!("The node {:?} is not in the scene", id)})
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Result<&mut Node, Error>
+    {
+        self.nodes.get_mut(id.0).and_then(|node| node.as_mut())
+            .ok_or_else(|| Error::NodeNotFound {message: format!("The node {:?} is not in the scene", id)})
+    }
+
+    fn detach_from_parent(&mut self, id: NodeId) -> Result<(), Error>
+    {
+        match self.node(id)?.parent {
+            Some(parent) => self.node_mut(parent)?.children.retain(|child| *child != id),
+            None => self.roots.retain(|root| *root != id)
+        }
+        Ok(())
+    }
+
+    fn update_world_transformations(&mut self, id: NodeId) -> Result<(), Error>
+    {
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let parent_transformation = match self.node(id)?.parent {
+                Some(parent) => self.node(parent)?.world_transformation,
+                None => Mat4::identity()
+            };
+            let node = self.node_mut(id)?;
+            node.world_transformation = parent_transformation * node.transform.matrix();
+            stack.extend(node.children.iter());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Mat4, b: &Mat4)
+    {
+        assert!((0..4).all(|i| (a[i] - b[i]).magnitude() < 0.0001), "{:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn moving_a_node_moves_its_descendants()
+    {
+        let mut scene = Scene::new();
+        let pivot = scene.add_node(None, NodeTransform::from_rotation(Quat::from_angle_y(degrees(30.0)))).unwrap();
+        let left = scene.add_node(Some(pivot), NodeTransform::from_translation(vec3(-2.0, 0.0, 0.0))).unwrap();
+        let carried = scene.add_node(Some(left), NodeTransform {translation: vec3(0.0, 1.5, 0.0), scale: vec3(0.3, 0.3, 0.3), ..Default::default()}).unwrap();
+
+        scene.set_transform(pivot, NodeTransform {translation: vec3(0.0, 0.5, 0.0), rotation: Quat::from_angle_y(degrees(-20.0)), ..Default::default()}).unwrap();
+        let expected = Mat4::from_translation(vec3(0.0, 0.5, 0.0)) * Mat4::from_angle_y(degrees(-20.0)) * Mat4::from_translation(vec3(-2.0, 0.0, 0.0))
+            * Mat4::from_translation(vec3(0.0, 1.5, 0.0)) * Mat4::from_scale(0.3);
+        assert_close(&scene.world_transformation(carried).unwrap(), &expected);
+    }
+
+    #[test]
+    fn reparenting_keeps_the_hierarchy_acyclic()
+    {
+        let mut scene = Scene::new();
+        let pivot = scene.add_node(None, NodeTransform::default()).unwrap();
+        let left = scene.add_node(Some(pivot), NodeTransform::from_translation(vec3(-2.0, 0.0, 0.0))).unwrap();
+        let right = scene.add_node(Some(pivot), NodeTransform::from_translation(vec3(2.0, 0.0, 0.0))).unwrap();
+        let carried = scene.add_node(Some(left), NodeTransform::from_translation(vec3(0.0, 1.5, 0.0))).unwrap();
+
+        scene.set_parent(carried, Some(right)).unwrap();
+        assert_eq!(scene.children(left).unwrap(), &[]);
+        assert_eq!(scene.children(right).unwrap(), &[carried]);
+        assert_close(&scene.world_transformation(carried).unwrap(), &Mat4::from_translation(vec3(2.0, 1.5, 0.0)));
+        assert!(matches!(scene.set_parent(pivot, Some(carried)), Err(Error::InvalidParent {..})));
+        assert!(matches!(scene.set_parent(pivot, Some(pivot)), Err(Error::InvalidParent {..})));
+
+        scene.set_parent(carried, None).unwrap();
+        assert_eq!(scene.roots(), &[pivot, carried]);
+        assert_close(&scene.world_transformation(carried).unwrap(), &Mat4::from_translation(vec3(0.0, 1.5, 0.0)));
+    }
+
+    #[test]
+    fn removing_a_node_removes_its_descendants()
+    {
+        let mut scene = Scene::new();
+        let pivot = scene.add_node(None, NodeTransform::default()).unwrap();
+        let hidden = scene.add_node(Some(pivot), NodeTransform::default()).unwrap();
+        let removed = scene.add_node(Some(hidden), NodeTransform::default()).unwrap();
+        scene.remove_node(hidden).unwrap();
+        assert_eq!(scene.children(pivot).unwrap(), &[]);
+        assert!(matches!(scene.transform(hidden), Err(Error::NodeNotFound {..})));
+        assert!(matches!(scene.transform(removed), Err(Error::NodeNotFound {..})));
+
+        let added = scene.add_node(None, NodeTransform::default()).unwrap();
+        assert!(added != hidden && added != removed);
+    }
+}
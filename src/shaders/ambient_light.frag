@@ -0,0 +1,38 @@
+uniform sampler2DArray gbuffer;
+uniform sampler2DArray depthMap;
+uniform sampler2D ambientOcclusionMap;
+
+uniform vec2 screenSize;
+
+struct BaseLight
+{
+    vec3 color;
+    float intensity;
+};
+
+struct AmbientLight
+{
+    BaseLight base;
+};
+
+uniform AmbientLight ambientLight;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    vec2 uv = gl_FragCoord.xy / screenSize;
+    float depth = texture(depthMap, vec3(uv, 0)).r;
+    vec3 surface_color = texture(gbuffer, vec3(uv, 0)).rgb;
+    bool is_far_away = depth > 0.99999;
+    if(is_far_away)
+    {
+        color = vec4(surface_color * ambientLight.base.color, 1.0);
+        return;
+    }
+
+    float occlusion = texture(gbuffer, vec3(uv, 2)).z * texture(ambientOcclusionMap, uv).r;
+    vec3 emissive = texture(gbuffer, vec3(uv, 3)).rgb;
+    vec3 light = ambientLight.base.color * ambientLight.base.intensity * occlusion;
+    color = vec4(surface_color * light + emissive, 1.0);
+}
@@ -0,0 +1,35 @@
+
+uniform float size;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    vec2 uv = gl_FragCoord.xy / size;
+    float n_dot_v = uv.x;
+    float roughness = uv.y;
+    vec3 view = vec3(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    vec3 normal = vec3(0.0, 0.0, 1.0);
+
+    float k = roughness * roughness / 2.0;
+    float scale = 0.0;
+    float bias = 0.0;
+    const uint NO_SAMPLES = 512u;
+    for (uint i = 0u; i < NO_SAMPLES; i++)
+    {
+        vec3 h = importance_sample_ggx(hammersley(i, NO_SAMPLES), normal, roughness);
+        vec3 l = normalize(2.0 * dot(view, h) * h - view);
+        float n_dot_l = max(l.z, 0.0);
+        float n_dot_h = max(h.z, 0.0);
+        float v_dot_h = max(dot(view, h), 0.0);
+        if(n_dot_l > 0.0)
+        {
+            float geometry = n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k);
+            float visibility = geometry * v_dot_h / (n_dot_h * n_dot_v);
+            float fresnel = pow(1.0 - v_dot_h, 5.0);
+            scale += (1.0 - fresnel) * visibility;
+            bias += fresnel * visibility;
+        }
+    }
+    color = vec4(scale / float(NO_SAMPLES), bias / float(NO_SAMPLES), 0.0, 1.0);
+}
@@ -0,0 +1,7 @@
+
+uniform samplerCube environmentMap;
+
+void main()
+{
+    color = vec4(texture(environmentMap, face_direction()).rgb, 1.0);
+}
@@ -0,0 +1,20 @@
+
+uniform int face;
+uniform float faceSize;
+
+layout (location = 0) out vec4 color;
+
+const float PI = 3.14159265359;
+
+vec3 face_direction()
+{
+    vec2 st = 2.0 * gl_FragCoord.xy / faceSize - 1.0;
+    vec3 direction;
+    if(face == 0) direction = vec3(1.0, -st.y, -st.x);
+    else if(face == 1) direction = vec3(-1.0, -st.y, st.x);
+    else if(face == 2) direction = vec3(st.x, 1.0, st.y);
+    else if(face == 3) direction = vec3(st.x, -1.0, -st.y);
+    else if(face == 4) direction = vec3(st.x, -st.y, 1.0);
+    else direction = vec3(-st.x, -st.y, -1.0);
+    return normalize(direction);
+}
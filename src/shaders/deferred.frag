@@ -0,0 +1,42 @@
+uniform sampler2DArray gbuffer;
+uniform sampler2DArray depthMap;
+
+layout (location = 0) out vec4 color;
+
+uniform mat4 viewProjectionInverse;
+uniform vec2 screenSize;
+
+vec3 WorldPosFromDepth(float depth, vec2 uv) {
+    vec4 clipSpacePosition = vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec4 position = viewProjectionInverse * clipSpacePosition;
+    return position.xyz / position.w;
+}
+
+void main()
+{
+    vec2 uv = gl_FragCoord.xy / screenSize;
+    float depth = texture(depthMap, vec3(uv,0)).r;
+    if(depth > 0.99999)
+    {
+        discard;
+    }
+    Surface surface;
+    surface.position = WorldPosFromDepth(depth, uv);
+   	vec4 c = texture(gbuffer, vec3(uv, 0));
+    surface.color = c.rgb;
+    surface.diffuse_intensity = c.w;
+
+    vec4 n = texture(gbuffer, vec3(uv, 1));
+    surface.normal = normalize(n.xyz*2.0 - 1.0);
+    int t = int(floor(n.w*255.0));
+    surface.specular_intensity = float(t & 15) / 15.0;
+    surface.specular_power = 2.0 * float((t & 240) >> 4);
+
+    vec4 material = texture(gbuffer, vec3(uv, 2));
+    surface.metallic = material.x;
+    surface.roughness = material.y;
+    surface.occlusion = material.z;
+    surface.is_physical = material.w > 0.5;
+
+    color = vec4(calculate_lighting(surface), 0.0);
+}
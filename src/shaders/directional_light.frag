@@ -0,0 +1,76 @@
+uniform sampler2DArray shadowMaps;
+uniform vec3 viewDirection;
+
+const int MAX_NO_CASCADES = 8;
+
+struct DirectionalLight
+{
+    BaseLight base;
+    vec3 direction;
+    float noCascades;
+    vec4 cascadeSplits[2];
+    mat4 shadowMVP[MAX_NO_CASCADES];
+    ShadowParameters shadow;
+};
+
+layout (std140) uniform DirectionalLightUniform
+{
+    DirectionalLight light;
+};
+
+float cascade_split(int cascade)
+{
+    return light.cascadeSplits[cascade / 4][cascade % 4];
+}
+
+float shadow_caster_distance(vec4 lookup, vec2 texel_offset)
+{
+    vec2 uv = lookup.xy + texel_offset / vec2(textureSize(shadowMaps, 0).xy);
+    if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0)
+    {
+        return 1.0e30;
+    }
+    return texture(shadowMaps, vec3(uv, lookup.z)).x / lookup.w;
+}
+
+float calculate_cascade_shadow(int cascade, vec3 position, vec3 normal)
+{
+    mat4 shadowMVP = light.shadowMVP[cascade];
+
+    float texel_size = 1.0 / (length(vec3(shadowMVP[0][0], shadowMVP[1][0], shadowMVP[2][0])) * float(textureSize(shadowMaps, 0).x));
+    float depth_scale = length(vec3(shadowMVP[0][2], shadowMVP[1][2], shadowMVP[2][2]));
+
+    vec4 shadow_coord = shadowMVP * vec4(position + normal * light.shadow.normalOffsetBias * texel_size, 1.0);
+    return calculate_visibility(light.shadow, vec4(shadow_coord.xy, float(cascade), depth_scale), shadow_coord.z / depth_scale, texel_size, dot(normal, -normalize(light.direction)), false);
+}
+
+float calculate_directional_shadow(vec3 position, vec3 normal)
+{
+    int no_cascades = int(light.noCascades + 0.5);
+    float depth = dot(position - eyePosition, viewDirection);
+    for (int cascade = 0; cascade < no_cascades; cascade++)
+    {
+        float end = cascade_split(cascade);
+        if(depth < end)
+        {
+            float shadow = calculate_cascade_shadow(cascade, position, normal);
+
+            // Blend with the next cascade (or no shadow after the last one) in the last tenth of the cascade to hide the seam
+            float start = cascade == 0 ? 0.0 : cascade_split(cascade - 1);
+            float blend_start = end - 0.1 * (end - start);
+            if(depth > blend_start)
+            {
+                float next_shadow = cascade + 1 < no_cascades ? calculate_cascade_shadow(cascade + 1, position, normal) : 1.0;
+                shadow = mix(shadow, next_shadow, (depth - blend_start) / (end - blend_start));
+            }
+            return shadow;
+        }
+    }
+    return 1.0;
+}
+
+vec3 calculate_lighting(Surface surface)
+{
+    return calculate_directional_shadow(surface.position, surface.normal)
+        * calculate_light(light.base, light.direction, surface);
+}
@@ -0,0 +1,29 @@
+
+uniform samplerCube irradianceMap;
+uniform samplerCube prefilteredMap;
+uniform sampler2D brdfLUT;
+uniform float maxPrefilteredLod;
+uniform float environmentIntensity;
+
+vec3 calculate_lighting(Surface surface)
+{
+    vec3 irradiance = texture(irradianceMap, surface.normal).rgb;
+    if(!surface.is_physical)
+    {
+        return environmentIntensity * surface.occlusion * surface.diffuse_intensity * surface.color * irradiance;
+    }
+
+    vec3 view = normalize(eyePosition - surface.position);
+    float n_dot_v = max(dot(surface.normal, view), 0.0001);
+    float roughness = clamp(surface.roughness, 0.0, 1.0);
+
+    vec3 f0 = mix(vec3(0.04), surface.color, surface.metallic);
+    vec3 fresnel = f0 + (max(vec3(1.0 - roughness), f0) - f0) * pow(1.0 - n_dot_v, 5.0);
+    vec3 diffuse = (1.0 - fresnel) * (1.0 - surface.metallic) * surface.color * irradiance;
+
+    vec3 prefiltered = textureLod(prefilteredMap, reflect(-view, surface.normal), roughness * maxPrefilteredLod).rgb;
+    vec2 brdf = texture(brdfLUT, vec2(n_dot_v, roughness)).rg;
+    vec3 specular = prefiltered * (f0 * brdf.x + brdf.y);
+
+    return environmentIntensity * surface.occlusion * (diffuse + specular);
+}
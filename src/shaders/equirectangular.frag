@@ -0,0 +1,9 @@
+
+uniform sampler2D equirectangularMap;
+
+void main()
+{
+    vec3 direction = face_direction();
+    vec2 uv = vec2(atan(direction.z, direction.x) / (2.0 * PI) + 0.5, acos(clamp(direction.y, -1.0, 1.0)) / PI);
+    color = vec4(texture(equirectangularMap, uv).rgb, 1.0);
+}
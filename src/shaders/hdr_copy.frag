@@ -0,0 +1,9 @@
+uniform sampler2D hdrMap;
+uniform vec2 screenSize;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    color = texture(hdrMap, gl_FragCoord.xy / screenSize);
+}
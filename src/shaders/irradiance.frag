@@ -0,0 +1,28 @@
+
+uniform samplerCube environmentMap;
+
+void main()
+{
+    vec3 normal = face_direction();
+    vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 right = normalize(cross(up, normal));
+    up = cross(normal, right);
+
+    const int NO_AZIMUTH_SAMPLES = 128;
+    const int NO_ZENITH_SAMPLES = 32;
+    float lod = max(log2(float(textureSize(environmentMap, 0).x) / 32.0), 0.0);
+
+    vec3 irradiance = vec3(0.0);
+    for (int i = 0; i < NO_AZIMUTH_SAMPLES; i++)
+    {
+        float phi = 2.0 * PI * (float(i) + 0.5) / float(NO_AZIMUTH_SAMPLES);
+        for (int j = 0; j < NO_ZENITH_SAMPLES; j++)
+        {
+            float theta = 0.5 * PI * (float(j) + 0.5) / float(NO_ZENITH_SAMPLES);
+            vec3 tangent_sample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 direction = tangent_sample.x * right + tangent_sample.y * up + tangent_sample.z * normal;
+            irradiance += textureLod(environmentMap, direction, lod).rgb * cos(theta) * sin(theta);
+        }
+    }
+    color = vec4(PI * irradiance / float(NO_AZIMUTH_SAMPLES * NO_ZENITH_SAMPLES), 1.0);
+}
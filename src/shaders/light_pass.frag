@@ -1,24 +1,4 @@
-uniform sampler2DArray gbuffer;
-uniform sampler2DArray depthMap;
-uniform samplerCube shadowCubeMap;
-
-layout (location = 0) out vec4 color;
-
-uniform mat4 viewProjectionInverse;
-
 uniform vec3 eyePosition;
-uniform mat4 shadowMVP0;
-uniform mat4 shadowMVP1;
-uniform mat4 shadowMVP2;
-uniform mat4 shadowMVP3;
-uniform mat4 shadowMVP4;
-uniform mat4 shadowMVP5;
-
-in vec2 uv;
-
-const int MAX_NO_LIGHTS = 4;
-uniform sampler2DArray directionalLightShadowMaps;
-uniform sampler2DArray spotLightShadowMaps;
 
 struct BaseLight
 {
@@ -34,234 +14,110 @@ struct Attenuation
     float padding;
 };
 
-struct AmbientLight
-{
-    BaseLight base;
-};
-
-struct DirectionalLight
-{
-    BaseLight base;
-    vec3 direction;
-    float padding;
-    mat4 shadowMVP;
-};
-
-struct PointLight
-{
-    BaseLight base;
-    Attenuation attenuation;
-    vec3 position;
-    float padding;
-};
-
-struct SpotLight
+struct Surface
 {
-    BaseLight base;
-    Attenuation attenuation;
     vec3 position;
-    float cutoff;
-    vec3 direction;
-    float padding;
-    mat4 shadowMVP;
-};
-
-uniform AmbientLight ambientLight;
-
-layout (std140) uniform DirectionalLights
-{
-    DirectionalLight directionalLights[MAX_NO_LIGHTS];
+    vec3 normal;
+    vec3 color;
+    bool is_physical;
+    float diffuse_intensity;
+    float specular_intensity;
+    float specular_power;
+    float metallic;
+    float roughness;
+    float occlusion;
 };
 
-layout (std140) uniform PointLights
-{
-    PointLight pointLights[MAX_NO_LIGHTS];
-};
+const float PI = 3.14159265359;
 
-layout (std140) uniform SpotLights
+vec3 phong(vec3 radiance, vec3 light_direction, Surface surface)
 {
-    SpotLight spotLights[MAX_NO_LIGHTS];
-};
-
-float is_visible(int lightIndex, sampler2DArray shadowMap, vec4 shadow_coord, vec2 offset)
-{
-    vec2 uv = (shadow_coord.xy + offset)/shadow_coord.w;
-    float true_distance = (shadow_coord.z - 0.005)/shadow_coord.w;
-    float shadow_cast_distance = texture(shadowMap, vec3(uv, lightIndex)).x;
-    return uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0 || shadow_cast_distance > true_distance ? 1.0 : 0.0;
-}
-
-float calculate_shadow(int lightIndex, sampler2DArray shadowMap, mat4 shadowMVP, vec3 position)
-{
-    if(shadowMVP[3][3] < 0.1) // Shadow disabled
-    {
-        return 1.0;
-    }
-    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
-    float visibility = 0.0;
-    vec2 poissonDisk[4] = vec2[](
-                                 vec2( -0.94201624, -0.39906216 ),
-                                 vec2( 0.94558609, -0.76890725 ),
-                                 vec2( -0.094184101, -0.92938870 ),
-                                 vec2( 0.34495938, 0.29387760 )
-                                 );
-    for (int i=0;i<4;i++)
-    {
-        visibility += is_visible(lightIndex, shadowMap, shadow_coord, poissonDisk[i] * 0.001f);
-    }
-    return visibility * 0.25;
-}
-
-vec3 calculate_light(BaseLight light, vec3 lightDirection, vec3 position, vec3 normal, float diffuse_intensity, float specular_intensity, float specular_power)
-{
-    float DiffuseFactor = dot(normal, -lightDirection);
+    float DiffuseFactor = dot(surface.normal, -light_direction);
 
     vec3 DiffuseColor  = vec3(0.0);
     vec3 SpecularColor = vec3(0.0);
 
     if (DiffuseFactor > 0.0)
     {
-        DiffuseColor = light.color * diffuse_intensity * light.intensity * DiffuseFactor;
+        DiffuseColor = surface.color * radiance * surface.diffuse_intensity * DiffuseFactor;
 
-        vec3 VertexToEye = normalize(eyePosition - position);
-        vec3 lightReflect = normalize(reflect(lightDirection, normal));
+        vec3 VertexToEye = normalize(eyePosition - surface.position);
+        vec3 lightReflect = normalize(reflect(light_direction, surface.normal));
         float SpecularFactor = dot(VertexToEye, lightReflect);
         if (SpecularFactor > 0.0)
         {
-            SpecularFactor = pow(SpecularFactor, specular_power);
-            SpecularColor = light.color * specular_intensity * light.intensity  * SpecularFactor;
+            SpecularFactor = pow(SpecularFactor, surface.specular_power);
+            SpecularColor = surface.color * radiance * surface.specular_intensity * SpecularFactor;
         }
     }
 
     return DiffuseColor + SpecularColor;
 }
 
-vec3 calculate_attenuated_light(BaseLight light, Attenuation attenuation, vec3 light_position, vec3 position, vec3 normal, float diffuse_intensity, float specular_intensity, float specular_power)
+float distribution_ggx(float n_dot_h, float roughness)
 {
-    vec3 light_direction = position - light_position;
-    float distance = length(light_direction);
-    light_direction = light_direction / distance;
-
-    vec3 color = calculate_light(light, light_direction, position, normal, diffuse_intensity, specular_intensity, specular_power);
-
-    float att =  attenuation.constant +
-        attenuation.linear * distance +
-        attenuation.exp * distance * distance;
-
-    return color / max(1.0, att);
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
 }
 
-/*vec3 calculate_point_light(vec3 position)
+float geometry_smith(float n_dot_v, float n_dot_l, float roughness)
 {
-    vec3 color = calculate_attenuated_light(pointLight.base, pointLight.attenuation, pointLight.position, position);
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    return n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k);
+}
 
-    mat4 shadowMatrix;
-    float x = abs(lightDirection.x);
-    float y = abs(lightDirection.y);
-    float z = abs(lightDirection.z);
-    if(x > y && x > z)
-    {
-        if(lightDirection.x > 0.0)
-        {
-            shadowMatrix = shadowMVP0;
-        }
-        else {
-            shadowMatrix = shadowMVP1;
-        }
-    }
-    else if(y > x && y > z)
-    {
-        if(lightDirection.y > 0.0)
-        {
-            shadowMatrix = shadowMVP2;
-        }
-        else {
-            shadowMatrix = shadowMVP3;
-        }
-    }
-    else if(z > x && z > y)
-    {
-        if(lightDirection.z > 0.0)
-        {
-            shadowMatrix = shadowMVP4;
-        }
-        else {
-            shadowMatrix = shadowMVP5;
-        }
-    }
-    else {
-        return vec4(1., 0., 0., 1.);
-    }
+vec3 fresnel_schlick(float cos_theta, vec3 f0)
+{
+    return f0 + (1.0 - f0) * pow(1.0 - cos_theta, 5.0);
+}
 
-    float shadow = 1.f;
-    vec4 shadow_coord = shadowMatrix * vec4(position, 1.);
-    if ( texture(shadowCubeMap, lightDirection).x < (shadow_coord.z - 0.005)/shadow_coord.w)
+vec3 cook_torrance(vec3 radiance, vec3 light_direction, Surface surface)
+{
+    vec3 n = surface.normal;
+    vec3 l = -light_direction;
+    vec3 v = normalize(eyePosition - surface.position);
+    vec3 h = normalize(v + l);
+    float n_dot_l = max(dot(n, l), 0.0);
+    if(n_dot_l <= 0.0)
     {
-        shadow = 0.5f;
+        return vec3(0.0);
     }
+    float n_dot_v = max(dot(n, v), 0.0001);
+    float roughness = clamp(surface.roughness, 0.05, 1.0);
 
-    return color;
-}*/
+    vec3 f0 = mix(vec3(0.04), surface.color, surface.metallic);
+    vec3 fresnel = fresnel_schlick(max(dot(h, v), 0.0), f0);
+    vec3 specular = distribution_ggx(max(dot(n, h), 0.0), roughness) * geometry_smith(n_dot_v, n_dot_l, roughness) * fresnel
+        / (4.0 * n_dot_v * n_dot_l + 0.0001);
 
-vec3 calculate_spot_light(int i, vec3 position, vec3 normal, float diffuse_intensity, float specular_intensity, float specular_power)
-{
-    SpotLight spotLight = spotLights[i];
-    if(spotLight.base.intensity > 0.0)
-    {
-        vec3 light_direction = normalize(position - spotLight.position);
-        float SpotFactor = dot(light_direction, spotLight.direction);
+    vec3 diffuse = (1.0 - fresnel) * (1.0 - surface.metallic) * surface.color / PI;
 
-        if (SpotFactor > spotLight.cutoff) {
-            return calculate_shadow(i, spotLightShadowMaps, spotLight.shadowMVP, position) *
-                calculate_attenuated_light(spotLight.base, spotLight.attenuation, spotLight.position, position, normal, diffuse_intensity, specular_intensity, specular_power)
-                * (1.0 - (1.0 - SpotFactor) * 1.0/(1.0 - spotLight.cutoff));
-        }
-    }
-    return vec3(0.0);
+    // The light intensity is scaled by pi so a white diffuse surface facing the light has the color of the light
+    return (diffuse + specular) * radiance * PI * n_dot_l;
 }
 
-vec3 WorldPosFromDepth(float depth, vec2 uv) {
-    vec4 clipSpacePosition = vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
-    vec4 position = viewProjectionInverse * clipSpacePosition;
-    return position.xyz / position.w;
+vec3 calculate_light(BaseLight light, vec3 light_direction, Surface surface)
+{
+    vec3 radiance = light.color * light.intensity;
+    return surface.is_physical ? cook_torrance(radiance, light_direction, surface) : phong(radiance, light_direction, surface);
 }
 
-void main()
+vec3 calculate_attenuated_light(BaseLight light, Attenuation attenuation, vec3 light_position, Surface surface)
 {
-    float depth = texture(depthMap, vec3(uv,0)).r;
-    vec3 position = WorldPosFromDepth(depth, uv);
-   	vec4 c = texture(gbuffer, vec3(uv, 0));
-    vec3 surface_color = c.rgb;
-    bool is_far_away = depth > 0.99999;
-
-    vec3 light = ambientLight.base.color * (is_far_away? 1.0 : ambientLight.base.intensity);
-    if(!is_far_away)
-    {
-        vec4 n = texture(gbuffer, vec3(uv, 1));
-        vec3 normal = normalize(n.xyz*2.0 - 1.0);
-        float diffuse_intensity = c.w;
-        int t = int(floor(n.w*255.0));
-        float specular_intensity = float(t & 15) / 15.0;
-        float specular_power = 2.0 * float((t & 240) >> 4);
-        for(int i = 0; i < MAX_NO_LIGHTS; i++)
-        {
-            DirectionalLight directionalLight = directionalLights[i];
-            if(directionalLight.base.intensity > 0.0)
-            {
-                light += calculate_shadow(i, directionalLightShadowMaps, directionalLight.shadowMVP, position)
-                    * calculate_light(directionalLight.base, directionalLight.direction, position, normal, diffuse_intensity, specular_intensity, specular_power);
-            }
-
-            PointLight pointLight = pointLights[i];
-            if(pointLight.base.intensity > 0.0)
-            {
-                light += calculate_attenuated_light(pointLight.base, pointLight.attenuation, pointLight.position, position, normal, diffuse_intensity, specular_intensity, specular_power);
-            }
+    vec3 light_direction = surface.position - light_position;
+    float distance = length(light_direction);
+    light_direction = light_direction / distance;
 
-            light += calculate_spot_light(i, position, normal, diffuse_intensity, specular_intensity, specular_power);
+    vec3 color = calculate_light(light, light_direction, surface);
 
-        }
-    }
+    float att =  attenuation.constant +
+        attenuation.linear * distance +
+        attenuation.exp * distance * distance;
 
-    color = vec4(surface_color * light, 1.0);
+    return color / max(1.0, att);
 }
+
+// Defined by the light type specific part of the shader
+vec3 calculate_lighting(Surface surface);
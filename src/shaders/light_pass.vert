@@ -1,11 +1,10 @@
 
-in vec3 position;
-in vec2 uv_coordinate;
+uniform mat4 viewProjection;
+uniform mat4 modelMatrix;
 
-out vec2 uv;
+in vec3 position;
 
 void main()
 {
-    uv = uv_coordinate;
-    gl_Position = vec4(position, 1.0);
+    gl_Position = viewProjection * modelMatrix * vec4(position, 1.0);
 }
@@ -0,0 +1,13 @@
+uniform int objectId;
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out highp uvec4 outId;
+
+void main()
+{
+    // The identifier is offset by one, so that zero means that there is no object
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+    outId = uvec4(uint(objectId) + 1u, floatBitsToUint(normal));
+}
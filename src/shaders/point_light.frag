@@ -0,0 +1,48 @@
+uniform samplerCube shadowMap;
+
+struct PointLight
+{
+    BaseLight base;
+    Attenuation attenuation;
+    vec3 position;
+    float padding;
+    ShadowParameters shadow;
+};
+
+layout (std140) uniform PointLightUniform
+{
+    PointLight light;
+};
+
+float shadow_caster_distance(vec4 lookup, vec2 texel_offset)
+{
+    vec3 direction = lookup.xyz;
+    vec3 a = abs(direction);
+    float major = max(a.x, max(a.y, a.z));
+
+    vec3 u = a.x >= major ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 v = a.z >= major ? vec3(0.0, 1.0, 0.0) : vec3(0.0, 0.0, 1.0);
+    direction += (u * texel_offset.x + v * texel_offset.y) * 2.0 * major / float(textureSize(shadowMap, 0).x);
+    return linear_depth(texture(shadowMap, direction).x, light.shadow.near, light.shadow.far);
+}
+
+float calculate_point_shadow(vec3 position, vec3 normal)
+{
+    if(light.shadow.enabled < 0.5)
+    {
+        return 1.0;
+    }
+
+    vec3 direction = position - light.position;
+    float texel_size = 2.0 * max(abs(direction.x), max(abs(direction.y), abs(direction.z))) / float(textureSize(shadowMap, 0).x);
+
+    direction += normal * light.shadow.normalOffsetBias * texel_size;
+    float distance = max(abs(direction.x), max(abs(direction.y), abs(direction.z)));
+    return calculate_visibility(light.shadow, vec4(direction, 0.0), distance, texel_size, dot(normal, -normalize(direction)), true);
+}
+
+vec3 calculate_lighting(Surface surface)
+{
+    return calculate_point_shadow(surface.position, surface.normal)
+        * calculate_attenuated_light(light.base, light.attenuation, light.position, surface);
+}
@@ -0,0 +1,41 @@
+
+uniform samplerCube environmentMap;
+uniform float roughness;
+
+float distribution_ggx(float n_dot_h, float roughness)
+{
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+void main()
+{
+    vec3 normal = face_direction();
+    vec3 view = normal;
+
+    const uint NO_SAMPLES = 256u;
+    float size = float(textureSize(environmentMap, 0).x);
+    float texel_solid_angle = 4.0 * PI / (6.0 * size * size);
+
+    vec3 prefiltered = vec3(0.0);
+    float total_weight = 0.0;
+    for (uint i = 0u; i < NO_SAMPLES; i++)
+    {
+        vec3 h = importance_sample_ggx(hammersley(i, NO_SAMPLES), normal, roughness);
+        vec3 l = normalize(2.0 * dot(view, h) * h - view);
+        float n_dot_l = dot(normal, l);
+        if(n_dot_l > 0.0)
+        {
+            float n_dot_h = max(dot(normal, h), 0.0);
+            float pdf = distribution_ggx(n_dot_h, roughness) * 0.25 + 0.0001;
+            float sample_solid_angle = 1.0 / (float(NO_SAMPLES) * pdf + 0.0001);
+            float lod = roughness == 0.0 ? 0.0 : max(0.5 * log2(sample_solid_angle / texel_solid_angle) + 1.0, 0.0);
+
+            prefiltered += textureLod(environmentMap, l, lod).rgb * n_dot_l;
+            total_weight += n_dot_l;
+        }
+    }
+    color = vec4(prefiltered / total_weight, 1.0);
+}
@@ -0,0 +1,90 @@
+struct ShadowParameters
+{
+    float depthBias;
+    float normalOffsetBias;
+    float filterMode;
+    float kernelSize;
+    float lightSize;
+    float near;
+    float far;
+    float enabled;
+};
+
+const int SHADOW_FILTER_PCF = 1;
+const int SHADOW_FILTER_PCSS = 2;
+
+const float MAX_FILTER_RADIUS = 16.0;
+
+const int MAX_KERNEL_SIZE = 33;
+
+float linear_depth(float depth, float near, float far)
+{
+    return 2.0 * near * far / (far + near - (2.0 * depth - 1.0) * (far - near));
+}
+
+// Defined by the light type specific part of the shader: The distance from the light to the closest shadow caster
+// at the given shadow map lookup offset by the given number of texels
+float shadow_caster_distance(vec4 lookup, vec2 texel_offset);
+
+float percentage_closer_filter(vec4 lookup, float receiver_distance, float radius, int kernel_size)
+{
+    if(kernel_size < 2)
+    {
+        return shadow_caster_distance(lookup, vec2(0.0)) > receiver_distance ? 1.0 : 0.0;
+    }
+    float step = 2.0 * radius / float(kernel_size - 1);
+    float visibility = 0.0;
+    for (int x = 0; x < MAX_KERNEL_SIZE && x < kernel_size; x++)
+    {
+        for (int y = 0; y < MAX_KERNEL_SIZE && y < kernel_size; y++)
+        {
+            vec2 offset = vec2(float(x), float(y)) * step - radius;
+            visibility += shadow_caster_distance(lookup, offset) > receiver_distance ? 1.0 : 0.0;
+        }
+    }
+    return visibility / float(kernel_size * kernel_size);
+}
+
+float calculate_visibility(ShadowParameters shadow, vec4 lookup, float receiver_distance, float texel_size, float cos_angle, bool perspective)
+{
+    float slope = clamp(sqrt(1.0 - cos_angle * cos_angle) / max(cos_angle, 0.01), 0.0, 10.0);
+    receiver_distance -= shadow.depthBias * texel_size;
+    int filter_mode = int(shadow.filterMode + 0.5);
+    int kernel_size = clamp(int(shadow.kernelSize + 0.5), 1, MAX_KERNEL_SIZE);
+    if(filter_mode == SHADOW_FILTER_PCF)
+    {
+        float radius = 0.5 * float(kernel_size - 1);
+        return percentage_closer_filter(lookup, receiver_distance - slope * radius * texel_size, radius, kernel_size);
+    }
+    if(filter_mode == SHADOW_FILTER_PCSS)
+    {
+        float search_width = perspective ? shadow.lightSize * (receiver_distance - shadow.near) / receiver_distance : shadow.lightSize * receiver_distance;
+        float search_radius = clamp(0.5 * search_width / texel_size, 1.0, MAX_FILTER_RADIUS);
+        float search_distance = receiver_distance - slope * search_radius * texel_size;
+        float step = 2.0 * search_radius / float(max(kernel_size - 1, 1));
+        float blocker_distance = 0.0;
+        float no_blockers = 0.0;
+        for (int x = 0; x < MAX_KERNEL_SIZE && x < kernel_size; x++)
+        {
+            for (int y = 0; y < MAX_KERNEL_SIZE && y < kernel_size; y++)
+            {
+                float distance = shadow_caster_distance(lookup, vec2(float(x), float(y)) * step - search_radius);
+                if(distance < search_distance)
+                {
+                    blocker_distance += distance;
+                    no_blockers += 1.0;
+                }
+            }
+        }
+        if(no_blockers < 0.5)
+        {
+            return 1.0;
+        }
+        blocker_distance /= no_blockers;
+
+        float penumbra_width = perspective ? shadow.lightSize * (receiver_distance - blocker_distance) / blocker_distance : shadow.lightSize * (receiver_distance - blocker_distance);
+        float radius = clamp(0.5 * penumbra_width / texel_size, 0.5, MAX_FILTER_RADIUS);
+        return percentage_closer_filter(lookup, receiver_distance - slope * radius * texel_size, radius, kernel_size);
+    }
+    return percentage_closer_filter(lookup, receiver_distance, 0.0, 1);
+}
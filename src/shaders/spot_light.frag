@@ -0,0 +1,54 @@
+uniform sampler2D shadowMap;
+
+struct SpotLight
+{
+    BaseLight base;
+    Attenuation attenuation;
+    vec3 position;
+    float cutoff;
+    vec3 direction;
+    float shadowTexelScale;
+    mat4 shadowMVP;
+    ShadowParameters shadow;
+};
+
+layout (std140) uniform SpotLightUniform
+{
+    SpotLight light;
+};
+
+float shadow_caster_distance(vec4 lookup, vec2 texel_offset)
+{
+    vec2 uv = lookup.xy + texel_offset / vec2(textureSize(shadowMap, 0));
+    if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0)
+    {
+        return 1.0e30;
+    }
+    return linear_depth(texture(shadowMap, uv).x, light.shadow.near, light.shadow.far);
+}
+
+float calculate_spot_shadow(vec3 position, vec3 normal)
+{
+    if(light.shadow.enabled < 0.5)
+    {
+        return 1.0;
+    }
+
+    float texel_size = (light.shadowMVP * vec4(position, 1.0)).w * light.shadowTexelScale;
+
+    vec4 shadow_coord = light.shadowMVP * vec4(position + normal * light.shadow.normalOffsetBias * texel_size, 1.0);
+    return calculate_visibility(light.shadow, vec4(shadow_coord.xy / shadow_coord.w, 0.0, 0.0), shadow_coord.w, texel_size, dot(normal, normalize(light.position - position)), true);
+}
+
+vec3 calculate_lighting(Surface surface)
+{
+    vec3 light_direction = normalize(surface.position - light.position);
+    float SpotFactor = dot(light_direction, light.direction);
+
+    if (SpotFactor > light.cutoff) {
+        return calculate_spot_shadow(surface.position, surface.normal) *
+            calculate_attenuated_light(light.base, light.attenuation, light.position, surface)
+            * (1.0 - (1.0 - SpotFactor) * 1.0/(1.0 - light.cutoff));
+    }
+    return vec3(0.0);
+}
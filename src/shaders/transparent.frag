@@ -0,0 +1,61 @@
+
+layout (std140) uniform TransparentMaterial
+{
+    vec3 color;
+    float opacity;
+    vec3 emissive;
+    float isPhysical;
+    float diffuseIntensity;
+    float specularIntensity;
+    float specularPower;
+    float metallic;
+    float roughness;
+    float occlusion;
+} material;
+
+uniform bool basePass;
+uniform bool weightedBlended;
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out vec4 accumulation;
+layout (location = 1) out vec4 revealage;
+
+void main()
+{
+    Surface surface;
+    surface.position = pos;
+    surface.normal = normalize(gl_FrontFacing ? nor : -nor);
+    surface.color = material.color;
+    surface.is_physical = material.isPhysical > 0.5;
+    surface.diffuse_intensity = material.diffuseIntensity;
+    surface.specular_intensity = material.specularIntensity;
+    surface.specular_power = material.specularPower;
+    surface.metallic = material.metallic;
+    surface.roughness = material.roughness;
+    surface.occlusion = material.occlusion;
+
+    vec3 light = calculate_lighting(surface);
+    float alpha = material.opacity;
+    float coverage = 0.0;
+    if(basePass)
+    {
+        light += material.emissive;
+        coverage = alpha;
+    }
+
+    if(weightedBlended)
+    {
+        // The depth weight of McGuire and Bavoil, which makes the surfaces closest to the camera dominate the average color
+        float z = distance(pos, eyePosition);
+        float weight = alpha * clamp(10.0 / (0.00001 + pow(z / 5.0, 2.0) + pow(z / 200.0, 6.0)), 0.01, 3000.0);
+        accumulation = vec4(light * alpha * weight, coverage * weight);
+        // The transmittance is the product of one minus the opacities, which is accumulated as a sum of logarithms
+        revealage = vec4(basePass ? log(max(1.0 - alpha, 0.0001)) : 0.0, 0.0, 0.0, 0.0);
+    }
+    else {
+        accumulation = vec4(light * alpha, coverage);
+        revealage = vec4(0.0);
+    }
+}
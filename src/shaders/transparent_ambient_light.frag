@@ -0,0 +1,12 @@
+
+struct AmbientLight
+{
+    BaseLight base;
+};
+
+uniform AmbientLight ambientLight;
+
+vec3 calculate_lighting(Surface surface)
+{
+    return surface.color * ambientLight.base.color * ambientLight.base.intensity * surface.occlusion;
+}
@@ -0,0 +1,19 @@
+uniform sampler2DArray transparentLayers;
+uniform vec2 screenSize;
+uniform bool weightedBlended;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    vec2 uv = gl_FragCoord.xy / screenSize;
+    vec4 accumulation = texture(transparentLayers, vec3(uv, 0));
+    if(weightedBlended)
+    {
+        float coverage = 1.0 - exp(texture(transparentLayers, vec3(uv, 1)).r);
+        color = vec4(coverage * accumulation.rgb / max(accumulation.a, 0.00001), coverage);
+    }
+    else {
+        color = accumulation;
+    }
+}
@@ -11,4 +11,9 @@ pub use crate::glutin_window::*;
 #[cfg(all(feature = "canvas", target_arch = "wasm32"))]
 pub mod canvas;
 #[cfg(all(feature = "canvas", target_arch = "wasm32"))]
-pub use crate::canvas::*;
\ No newline at end of file
+pub use crate::canvas::*;
+
+#[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+pub mod headless;
+#[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+pub use crate::headless::HeadlessContext;
@@ -0,0 +1,236 @@
+use std::os::raw::{c_char, c_void};
+
+#[derive(Debug)]
+pub enum Error {
+    LibraryLoadingError(libloading::Error),
+    ContextCreationError {message: String}
+}
+
+impl From<libloading::Error> for Error {
+    fn from(other: libloading::Error) -> Self {
+        Error::LibraryLoadingError(other)
+    }
+}
+
+type EglDisplay = *mut c_void;
+type EglConfig = *mut c_void;
+type EglContext = *mut c_void;
+type EglSurface = *mut c_void;
+type EglDevice = *mut c_void;
+
+const EGL_FALSE: u32 = 0;
+const EGL_NONE: i32 = 0x3038;
+const EGL_SUCCESS: i32 = 0x3000;
+const EGL_RED_SIZE: i32 = 0x3024;
+const EGL_GREEN_SIZE: i32 = 0x3023;
+const EGL_BLUE_SIZE: i32 = 0x3022;
+const EGL_ALPHA_SIZE: i32 = 0x3021;
+const EGL_DEPTH_SIZE: i32 = 0x3025;
+const EGL_SURFACE_TYPE: i32 = 0x3033;
+const EGL_PBUFFER_BIT: i32 = 0x0001;
+const EGL_RENDERABLE_TYPE: i32 = 0x3040;
+const EGL_OPENGL_BIT: i32 = 0x0008;
+const EGL_WIDTH: i32 = 0x3057;
+const EGL_HEIGHT: i32 = 0x3056;
+const EGL_OPENGL_API: u32 = 0x30A2;
+const EGL_CONTEXT_MAJOR_VERSION: i32 = 0x3098;
+const EGL_CONTEXT_MINOR_VERSION: i32 = 0x30FB;
+const EGL_CONTEXT_OPENGL_PROFILE_MASK: i32 = 0x30FD;
+const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: i32 = 0x0001;
+const EGL_PLATFORM_DEVICE_EXT: u32 = 0x313F;
+const MAX_NO_DEVICES: usize = 16;
+
+struct Egl {
+    _library: libloading::Library,
+    get_proc_address: unsafe extern "C" fn(*const c_char) -> *const c_void,
+    get_display: unsafe extern "C" fn(*mut c_void) -> EglDisplay,
+    initialize: unsafe extern "C" fn(EglDisplay, *mut i32, *mut i32) -> u32,
+    terminate: unsafe extern "C" fn(EglDisplay) -> u32,
+    choose_config: unsafe extern "C" fn(EglDisplay, *const i32, *mut EglConfig, i32, *mut i32) -> u32,
+    bind_api: unsafe extern "C" fn(u32) -> u32,
+    create_context: unsafe extern "C" fn(EglDisplay, EglConfig, EglContext, *const i32) -> EglContext,
+    destroy_context: unsafe extern "C" fn(EglDisplay, EglContext) -> u32,
+    create_pbuffer_surface: unsafe extern "C" fn(EglDisplay, EglConfig, *const i32) -> EglSurface,
+    destroy_surface: unsafe extern "C" fn(EglDisplay, EglSurface) -> u32,
+    make_current: unsafe extern "C" fn(EglDisplay, EglSurface, EglSurface, EglContext) -> u32,
+    get_error: unsafe extern "C" fn() -> i32
+}
+
+impl Egl
+{
+    fn load() -> Result<Egl, Error>
+    {
+        let library = libloading::Library::new("libEGL.so.1").or_else(|_| libloading::Library::new("libEGL.so"))?;
+        unsafe {
+            Ok(Egl {
+                get_proc_address: *library.get(b"eglGetProcAddress\0")?,
+                get_display: *library.get(b"eglGetDisplay\0")?,
+                initialize: *library.get(b"eglInitialize\0")?,
+                terminate: *library.get(b"eglTerminate\0")?,
+                choose_config: *library.get(b"eglChooseConfig\0")?,
+                bind_api: *library.get(b"eglBindAPI\0")?,
+                create_context: *library.get(b"eglCreateContext\0")?,
+                destroy_context: *library.get(b"eglDestroyContext\0")?,
+                create_pbuffer_surface: *library.get(b"eglCreatePbufferSurface\0")?,
+                destroy_surface: *library.get(b"eglDestroySurface\0")?,
+                make_current: *library.get(b"eglMakeCurrent\0")?,
+                get_error: *library.get(b"eglGetError\0")?,
+                _library: library
+            })
+        }
+    }
+
+    fn proc_address(&self, name: &str) -> *const c_void
+    {
+        let c_str = std::ffi::CString::new(name).unwrap();
+        unsafe { (self.get_proc_address)(c_str.as_ptr()) }
+    }
+
+    // One display per GPU found through EGL_EXT_device_enumeration, which needs no display server, followed by the default display
+    fn candidate_displays(&self) -> Vec<EglDisplay>
+    {
+        let mut displays = Vec::new();
+        let query_devices = self.proc_address("eglQueryDevicesEXT");
+        let get_platform_display = self.proc_address("eglGetPlatformDisplayEXT");
+        if !query_devices.is_null() && !get_platform_display.is_null()
+        {
+            let query_devices: unsafe extern "C" fn(i32, *mut EglDevice, *mut i32) -> u32 = unsafe { std::mem::transmute(query_devices) };
+            let get_platform_display: unsafe extern "C" fn(u32, *mut c_void, *const i32) -> EglDisplay = unsafe { std::mem::transmute(get_platform_display) };
+
+            let mut devices = [std::ptr::null_mut(); MAX_NO_DEVICES];
+            let mut no_devices = 0;
+            if unsafe { query_devices(MAX_NO_DEVICES as i32, devices.as_mut_ptr(), &mut no_devices) } != EGL_FALSE
+            {
+                for device in devices.iter().take(no_devices as usize) {
+                    let display = unsafe { get_platform_display(EGL_PLATFORM_DEVICE_EXT, *device, std::ptr::null()) };
+                    if !display.is_null() {
+                        displays.push(display);
+                    }
+                }
+            }
+        }
+        let display = unsafe { (self.get_display)(std::ptr::null_mut()) };
+        if !display.is_null() {
+            displays.push(display);
+        }
+        displays
+    }
+
+    fn error(&self, message: &str) -> Error
+    {
+        let code = unsafe { (self.get_error)() };
+        if code == EGL_SUCCESS {
+            Error::ContextCreationError {message: message.to_string()}
+        }
+        else {
+            Error::ContextCreationError {message: format!("{} (EGL error code 0x{:X})", message, code)}
+        }
+    }
+}
+
+/// The default framebuffer is an offscreen buffer of the size given at construction.
+pub struct HeadlessContext
+{
+    egl: Egl,
+    display: EglDisplay,
+    context: EglContext,
+    surface: EglSurface,
+    width: usize,
+    height: usize,
+    gl: crate::Gl
+}
+
+impl HeadlessContext
+{
+    pub fn new(width: usize, height: usize) -> Result<HeadlessContext, Error>
+    {
+        let egl = Egl::load()?;
+        let mut last_error = Error::ContextCreationError {message: "No EGL display found".to_string()};
+        for display in egl.candidate_displays() {
+            match Self::create(&egl, display, width, height) {
+                Ok((context, surface)) => {
+                    let gl = gl::Gl::load_with(|s| egl.proc_address(s));
+                    return Ok(HeadlessContext {egl, display, context, surface, width, height, gl: std::rc::Rc::new(gl)});
+                },
+                Err(e) => {
+                    unsafe { (egl.terminate)(display); }
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    fn create(egl: &Egl, display: EglDisplay, width: usize, height: usize) -> Result<(EglContext, EglSurface), Error>
+    {
+        let (mut major, mut minor) = (0, 0);
+        if unsafe { (egl.initialize)(display, &mut major, &mut minor) } == EGL_FALSE {
+            return Err(egl.error("Unable to initialize the EGL display"));
+        }
+
+        let config_attributes = [EGL_SURFACE_TYPE, EGL_PBUFFER_BIT,
+                                 EGL_RENDERABLE_TYPE, EGL_OPENGL_BIT,
+                                 EGL_RED_SIZE, 8, EGL_GREEN_SIZE, 8, EGL_BLUE_SIZE, 8, EGL_ALPHA_SIZE, 8,
+                                 EGL_DEPTH_SIZE, 24,
+                                 EGL_NONE];
+        let mut config = std::ptr::null_mut();
+        let mut no_configs = 0;
+        if unsafe { (egl.choose_config)(display, config_attributes.as_ptr(), &mut config, 1, &mut no_configs) } == EGL_FALSE || no_configs == 0 {
+            return Err(egl.error("The EGL display has no offscreen OpenGL configuration"));
+        }
+
+        if unsafe { (egl.bind_api)(EGL_OPENGL_API) } == EGL_FALSE {
+            return Err(egl.error("Unable to bind the OpenGL API"));
+        }
+
+        let context_attributes = [EGL_CONTEXT_MAJOR_VERSION, 3,
+                                  EGL_CONTEXT_MINOR_VERSION, 3,
+                                  EGL_CONTEXT_OPENGL_PROFILE_MASK, EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                                  EGL_NONE];
+        let context = unsafe { (egl.create_context)(display, config, std::ptr::null_mut(), context_attributes.as_ptr()) };
+        if context.is_null() {
+            return Err(egl.error("Unable to create an OpenGL 3.3 core context"));
+        }
+
+        let surface_attributes = [EGL_WIDTH, width as i32, EGL_HEIGHT, height as i32, EGL_NONE];
+        let surface = unsafe { (egl.create_pbuffer_surface)(display, config, surface_attributes.as_ptr()) };
+        if surface.is_null() {
+            let error = egl.error("Unable to create an offscreen surface");
+            unsafe { (egl.destroy_context)(display, context); }
+            return Err(error);
+        }
+
+        if unsafe { (egl.make_current)(display, surface, surface, context) } == EGL_FALSE {
+            let error = egl.error("Unable to make the context current");
+            unsafe {
+                (egl.destroy_surface)(display, surface);
+                (egl.destroy_context)(display, context);
+            }
+            return Err(error);
+        }
+        Ok((context, surface))
+    }
+
+    pub fn framebuffer_size(&self) -> (usize, usize)
+    {
+        (self.width, self.height)
+    }
+
+    pub fn gl(&self) -> crate::Gl
+    {
+        self.gl.clone()
+    }
+}
+
+impl Drop for HeadlessContext
+{
+    fn drop(&mut self)
+    {
+        unsafe {
+            (self.egl.make_current)(self.display, std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::null_mut());
+            (self.egl.destroy_surface)(self.display, self.surface);
+            (self.egl.destroy_context)(self.display, self.context);
+            (self.egl.terminate)(self.display);
+        }
+    }
+}
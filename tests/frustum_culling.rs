@@ -0,0 +1,53 @@
+mod support;
+
+use dust::*;
+use std::rc::Rc;
+
+#[test]
+fn frustum_culling()
+{
+    support::headless_test("frustum_culling", |gl| {
+        // A box below the frustum which is not outside any single plane of the frustum, so it is only culled by testing the corners of the frustum
+        let test_camera = Camera::new_perspective(gl, vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0), degrees(45.0), 1.0, 0.1, 10.0);
+        assert!(!test_camera.in_frustrum(&vec3(-18.0, -17.0, -20.0), &vec3(-4.0, -6.0, -5.0)));
+        assert!(test_camera.in_frustrum(&vec3(-1.0, -1.0, -6.0), &vec3(1.0, 1.0, -4.0)));
+
+        let (width, height) = (256, 128);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 3.0, 6.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 30.0);
+        let cube_mesh = tri_mesh::MeshBuilder::new().cube().build().unwrap();
+        let cube = Rc::new(Mesh::new(gl, &cube_mesh.indices_buffer(), &cube_mesh.positions_buffer_f32(), &cube_mesh.normals_buffer_f32()).unwrap());
+
+        // A grid of small cubes of which only a part is in view
+        let mut scene = Scene::new();
+        for x in -10..11 {
+            for z in -10..11 {
+                let node = scene.add_node(None, NodeTransform {translation: vec3(x as f32, 0.0, z as f32), scale: vec3(0.3, 0.3, 0.3), ..Default::default()}).unwrap();
+                scene.attach(node, Renderable::Mesh(cube.clone())).unwrap();
+            }
+        }
+
+        // The shadow camera only covers the middle of the grid
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.7);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -0.5));
+        directional_light.enable_shadows();
+        directional_light.update_shadows(vec3(0.0, 0.0, 0.0), 8.0, 20.0);
+        scene.shadow_pass(&mut renderer, &camera).unwrap();
+        let shadow_stats = scene.frustum_culling().stats();
+        assert_eq!(shadow_stats.tested, 21 * 21);
+        assert!(shadow_stats.culled > 0);
+
+        scene.frustum_culling().reset_stats();
+        scene.geometry_pass(&mut renderer, &camera).unwrap();
+        let stats = scene.frustum_culling().stats();
+        assert_eq!(stats.tested, 21 * 21);
+        assert!(stats.culled > 100 && stats.visible() > 50);
+
+        scene.frustum_culling_mut().enabled = false;
+        scene.frustum_culling().reset_stats();
+        scene.geometry_pass(&mut renderer, &camera).unwrap();
+        assert_eq!(scene.frustum_culling().stats(), CullingStats {tested: 21 * 21, culled: 0});
+    });
+}
@@ -0,0 +1,25 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn gltf_scene()
+{
+    support::headless_test("gltf_scene", |gl| {
+        // A binary glTF file with a ground plane, two boxes in a transformed group, a camera and three lights
+        let scene = GLTFScene::new_from_bytes(gl, include_bytes!("../examples/assets/models/scene.glb")).unwrap();
+        assert_eq!(scene.nodes.len(), 8);
+        assert_eq!(scene.textures.len(), 1);
+        assert_eq!(scene.materials[0].base_color_texture, Some(0));
+        assert!(scene.meshes[0].primitives[0].uvs.is_some());
+        assert_eq!(scene.cameras.len(), 1);
+        assert_eq!(scene.lights.len(), 3);
+
+        let meshes = scene.to_meshes(gl).unwrap();
+        // The ground plane and the two boxes, which share a mesh
+        assert_eq!(meshes.len(), 3);
+        let mut renderer = DeferredPipeline::new(gl, 256, 128, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        scene.add_lights(&mut renderer).unwrap();
+        assert_eq!(renderer.no_directional_lights() + renderer.no_point_lights() + renderer.no_spot_lights(), 3);
+    });
+}
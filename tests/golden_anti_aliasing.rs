@@ -0,0 +1,49 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn anti_aliasing()
+{
+    support::golden_test("anti_aliasing", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        // The same scene without anti-aliasing, with FXAA and with temporal anti-aliasing side by side, leaving the last column of pixels empty
+        let (width, height) = (offscreen.width / 3, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let mut camera = Camera::new_perspective(gl, vec3(4.0, 3.0, 7.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                                 degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let cube_mesh = tri_mesh::MeshBuilder::new().cube().build().unwrap();
+        let mut cube = Mesh::new(gl, &cube_mesh.indices_buffer(), &cube_mesh.positions_buffer_f32(), &cube_mesh.normals_buffer_f32()).unwrap();
+        cube.color = vec3(0.9, 0.9, 0.9);
+        let transformation = Mat4::from_angle_z(degrees(20.0));
+
+        renderer.ambient_light().set_intensity(0.2);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.8);
+        directional_light.set_direction(&vec3(-1.0, -2.0, -0.5));
+
+        let tone_mapping_effect = effects::ToneMappingEffect::new(gl).unwrap();
+        for i in 0..3 {
+            renderer.disable_fxaa();
+            renderer.disable_taa();
+            // Temporal anti-aliasing converges over a number of frames with different jitter
+            let no_frames = match i {
+                1 => { renderer.enable_fxaa().unwrap(); 1 },
+                2 => { renderer.enable_taa().unwrap(); 16 },
+                _ => 1
+            };
+            for _ in 0..no_frames {
+                renderer.jitter_camera(&mut camera);
+                renderer.geometry_pass(&|| {
+                    cube.render(&transformation, &camera);
+                }).unwrap();
+                renderer.hdr_light_pass(&camera).unwrap();
+                renderer.anti_aliasing_pass(&camera).unwrap();
+            }
+
+            offscreen.bind();
+            gl.viewport((i * width) as i32, 0, width as i32, height as i32);
+            tone_mapping_effect.apply(renderer.hdr_texture()).unwrap();
+        }
+    });
+}
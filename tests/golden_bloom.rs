@@ -0,0 +1,77 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn bloom()
+{
+    support::golden_test("bloom", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.02, 0.02, 0.05, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 1.0, 8.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(40.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.color = vec3(0.2, 0.2, 0.2);
+
+        // Glossy spheres with highlights far brighter than white, which glow, and a rough sphere which should not glow
+        let materials = [(vec3(1.0, 0.4, 0.1), 0.2), (vec3(0.1, 0.4, 1.0), 0.2), (vec3(0.5, 0.5, 0.5), 0.9)];
+        let mut spheres = Vec::new();
+        for (i, (albedo, roughness)) in materials.iter().enumerate() {
+            let mut sphere = sphere(gl);
+            sphere.physical_material = Some(PhysicalMaterial {albedo: *albedo, metallic: 0.0, roughness: *roughness, ..PhysicalMaterial::default()});
+            spheres.push((sphere, Mat4::from_translation(vec3(-2.5 + 2.5 * i as f32, 0.0, 0.0)) * Mat4::from_scale(0.8)));
+        }
+
+        renderer.ambient_light().set_intensity(0.1);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(2.5);
+        directional_light.set_direction(&vec3(-0.5, -1.0, -1.0));
+
+        renderer.geometry_pass(&|| {
+            plane.render(&Mat4::identity(), &camera);
+            for (sphere, transformation) in spheres.iter() {
+                sphere.render(transformation, &camera);
+            }
+        }).unwrap();
+
+        let mut bloom_effect = effects::BloomEffect::new(gl, width, height, 5).unwrap();
+        bloom_effect.threshold = 1.5;
+        bloom_effect.intensity = 1.0;
+        let tone_mapping_effect = effects::ToneMappingEffect::new(gl).unwrap();
+
+        renderer.hdr_light_pass(&camera).unwrap();
+        bloom_effect.blur(renderer.hdr_texture()).unwrap();
+        renderer.write_to_hdr_texture().unwrap();
+        bloom_effect.apply().unwrap();
+
+        offscreen.bind();
+        tone_mapping_effect.apply(renderer.hdr_texture()).unwrap();
+    });
+}
+
+// A unit sphere with smooth normals
+fn sphere(gl: &Gl) -> Mesh
+{
+    let (no_rings, no_segments) = (24, 48);
+    let mut positions = Vec::new();
+    for ring in 0..no_rings + 1 {
+        let theta = std::f32::consts::PI * ring as f32 / no_rings as f32;
+        for segment in 0..no_segments + 1 {
+            let phi = 2.0 * std::f32::consts::PI * segment as f32 / no_segments as f32;
+            positions.extend(&[theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()]);
+        }
+    }
+    let mut indices = Vec::new();
+    for ring in 0..no_rings {
+        for segment in 0..no_segments {
+            let i = ring * (no_segments + 1) + segment;
+            let j = i + no_segments + 1;
+            indices.extend(&[i, i + 1, j, i + 1, j + 1, j]);
+        }
+    }
+    Mesh::new(gl, &indices, &positions, &positions).unwrap()
+}
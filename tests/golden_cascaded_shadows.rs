@@ -0,0 +1,52 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn cascaded_shadows()
+{
+    support::golden_test("cascaded_shadows", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 3.0, 10.0), vec3(0.0, 1.0, -10.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(60.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.diffuse_intensity = 0.5;
+        monkey.specular_intensity = 0.5;
+        monkey.specular_power = 20.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(200.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.diffuse_intensity = 0.5;
+        plane.specular_intensity = 0.2;
+        plane.specular_power = 5.0;
+
+        renderer.ambient_light().set_intensity(0.2);
+
+        // A row of monkeys from close to the camera to far away, all casting shadows from the same light
+        let transformations: Vec<Mat4> = (0..12).map(|i| Mat4::from_translation(vec3(if i % 2 == 0 {-2.0} else {2.0}, 0.0, 6.0 - 8.0 * i as f32))).collect();
+
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.8);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -0.5));
+        directional_light.set_shadow_cascades(4, 100.0).unwrap();
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            for transformation in transformations.iter() {
+                monkey.render(transformation, camera);
+            }
+        });
+        renderer.geometry_pass(&|| {
+            for transformation in transformations.iter() {
+                monkey.render(transformation, &camera);
+            }
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
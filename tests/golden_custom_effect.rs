@@ -0,0 +1,76 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn custom_effect()
+{
+    support::golden_test("custom_effect", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.2, 0.3, 0.6, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 1.5, 6.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.color = vec3(1.0, 0.8, 0.3);
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(20.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.color = vec3(0.5, 0.5, 0.5);
+
+        renderer.ambient_light().set_intensity(0.4);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(1.0);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -1.0));
+
+        // Compile errors and unknown uniforms give effect errors
+        assert!(matches!(effects::CustomEffect::new(gl, "void main() { color = undefined; }"), Err(effects::Error::Program(_))));
+
+        // An outline where the normals or the depth changes, which uses the geometry pass and extra uniforms
+        let mut outline_effect = effects::CustomEffect::new(gl, "
+            uniform vec3 outlineColor;
+            uniform float thickness;
+
+            void main()
+            {
+                vec3 normal = texture(gbuffer, vec3(uv, 1)).xyz;
+                float depth = texture(depthMap, vec3(uv, 0)).r;
+                float edge = 0.0;
+                for(int i = 0; i < 4; i++)
+                {
+                    vec2 offset = thickness * vec2(i == 0 ? 1.0 : (i == 1 ? -1.0 : 0.0), i == 2 ? 1.0 : (i == 3 ? -1.0 : 0.0)) / resolution;
+                    edge = max(edge, length(texture(gbuffer, vec3(uv + offset, 1)).xyz - normal));
+                    edge = max(edge, 100.0 * abs(texture(depthMap, vec3(uv + offset, 0)).r - depth));
+                }
+                color = vec4(mix(texture(colorMap, uv).rgb, outlineColor, step(0.4, edge)), 1.0);
+            }").unwrap();
+        assert!(matches!(outline_effect.set_uniform("unknown", 1.0), Err(effects::Error::Program(program::Error::FailedToFindUniform {..}))));
+        outline_effect.set_uniform("outlineColor", vec3(0.05, 0.05, 0.1)).unwrap();
+        outline_effect.set_uniform("thickness", 1.5).unwrap();
+        assert_eq!(outline_effect.uniform("thickness"), Some(&effects::UniformValue::Float(1.5)));
+
+        // A vignette which only uses the color map and the screen position
+        let mut vignette_effect = effects::CustomEffect::new(gl, "
+            uniform float strength;
+
+            void main()
+            {
+                vec2 d = uv - 0.5;
+                color = vec4(texture(colorMap, uv).rgb * (1.0 - strength * dot(d, d)), 1.0);
+            }").unwrap();
+        vignette_effect.set_uniform("strength", 2.0).unwrap();
+        let mut tone_mapping_effect = effects::ToneMappingEffect::new(gl).unwrap();
+
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+        renderer.hdr_light_pass(&camera).unwrap();
+
+        let mut chain = effects::PostProcessChain::new(gl, width, height).unwrap();
+        let target = effects::PostProcessTarget {width, height, elapsed_time: 16.0, bind: &|| offscreen.bind()};
+        chain.apply_render_to_rendertarget(&target, &camera, &renderer, &mut [&mut outline_effect, &mut vignette_effect, &mut tone_mapping_effect]).unwrap();
+    });
+}
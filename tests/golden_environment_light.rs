@@ -0,0 +1,92 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn environment_light()
+{
+    support::golden_test("environment_light", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 0.0, 14.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(30.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        // Dielectric spheres in the top row and metallic spheres in the bottom row, both getting rougher from left to right.
+        // The last column has a sphere with the Phong model and a sphere with ambient occlusion.
+        let mut spheres = Vec::new();
+        for column in 0..6 {
+            for row in 0..2 {
+                let mut sphere = sphere(gl);
+                let roughness = 0.1 + 0.2 * column as f32;
+                sphere.physical_material = match (column, row) {
+                    (5, 0) => None,
+                    (5, 1) => Some(PhysicalMaterial {albedo: vec3(0.8, 0.8, 0.8), ambient_occlusion: 0.3, ..PhysicalMaterial::default()}),
+                    (_, 0) => Some(PhysicalMaterial {albedo: vec3(0.8, 0.1, 0.1), metallic: 0.0, roughness, ..PhysicalMaterial::default()}),
+                    _ => Some(PhysicalMaterial {albedo: vec3(1.0, 0.8, 0.4), metallic: 1.0, roughness, ..PhysicalMaterial::default()})
+                };
+                let translation = Mat4::from_translation(vec3(2.2 * column as f32 - 5.5, 1.1 - 2.2 * row as f32, 0.0));
+                spheres.push((sphere, translation));
+            }
+        }
+
+        renderer.ambient_light().set_intensity(0.0);
+        renderer.set_environment_light(Some(EnvironmentLight::new(gl, &environment_map(gl)).unwrap()));
+
+        renderer.geometry_pass(&|| {
+            for (sphere, translation) in spheres.iter() {
+                sphere.render(translation, &camera);
+            }
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
+
+// A cube map with a blue sky, a brown ground, differently colored sides and a bright window behind the camera
+fn environment_map(gl: &Gl) -> Texture3D
+{
+    let size = 16;
+    let face = |color: [u8; 3], window: bool| {
+        let mut data = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let inside = window && (5..11).contains(&x) && (5..11).contains(&y);
+                data.extend(&if inside {[255, 255, 255]} else {color});
+            }
+        }
+        data
+    };
+    let mut right = face([200, 120, 60], false);
+    let mut left = face([60, 160, 80], false);
+    let mut top = face([90, 150, 230], false);
+    let mut bottom = face([90, 60, 40], false);
+    let mut front = face([120, 120, 120], true);
+    let mut back = face([40, 40, 60], false);
+    let mut texture = Texture3D::new(gl).unwrap();
+    texture.fill_with_u8(size, size, [&mut right, &mut left, &mut top, &mut bottom, &mut front, &mut back]);
+    texture
+}
+
+// A unit sphere with smooth normals
+fn sphere(gl: &Gl) -> Mesh
+{
+    let (no_rings, no_segments) = (24, 48);
+    let mut positions = Vec::new();
+    for ring in 0..no_rings + 1 {
+        let theta = std::f32::consts::PI * ring as f32 / no_rings as f32;
+        for segment in 0..no_segments + 1 {
+            let phi = 2.0 * std::f32::consts::PI * segment as f32 / no_segments as f32;
+            positions.extend(&[theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()]);
+        }
+    }
+    let mut indices = Vec::new();
+    for ring in 0..no_rings {
+        for segment in 0..no_segments {
+            let i = ring * (no_segments + 1) + segment;
+            let j = i + no_segments + 1;
+            indices.extend(&[i, i + 1, j, i + 1, j + 1, j]);
+        }
+    }
+    Mesh::new(gl, &indices, &positions, &positions).unwrap()
+}
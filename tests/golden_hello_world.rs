@@ -0,0 +1,34 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn hello_world()
+{
+    support::golden_test("hello_world", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let camera = Camera::new_perspective(gl, vec3(0.0, 0.0, 2.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), offscreen.width as f32 / offscreen.height as f32, 0.1, 10.0);
+
+        let positions: Vec<f32> = vec![
+            0.5, -0.5, 0.0,
+            -0.5, -0.5, 0.0,
+            0.0,  0.5, 0.0
+        ];
+        let colors: Vec<f32> = vec![
+            1.0, 0.0, 0.0,
+            0.0, 1.0, 0.0,
+            0.0, 0.0, 1.0
+        ];
+        let buffer = VertexBuffer::new_with_two_static_attributes(gl, &positions, &colors).unwrap();
+        let program = Program::from_source(gl,
+                                           include_str!("../examples/assets/shaders/color.vert"),
+                                           include_str!("../examples/assets/shaders/color.frag")).unwrap();
+
+        offscreen.clear(&vec4(0.8, 0.8, 0.8, 1.0));
+        program.use_attribute_vec3_float(&buffer, "position", 0).unwrap();
+        program.use_attribute_vec3_float(&buffer, "color", 1).unwrap();
+        program.add_uniform_mat4("viewMatrix", camera.get_view()).unwrap();
+        program.add_uniform_mat4("projectionMatrix", camera.get_projection()).unwrap();
+        program.draw_arrays(3);
+    });
+}
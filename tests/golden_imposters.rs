@@ -0,0 +1,70 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn imposters()
+{
+    support::golden_test("imposters", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(10.0, 25.0, 40.0), vec3(0.0, 7.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut tree_mesh = CPUMesh::new(include_bytes!("../examples/assets/models/tree1.3d")).unwrap().to_mesh(gl).unwrap();
+        tree_mesh.color = vec3(0.5, 0.2, 0.2);
+        tree_mesh.specular_intensity = 0.0;
+        let aabb = tree_mesh.axis_aligned_bounding_box();
+        let mut imposter = Imposter::new(gl, &|camera: &Camera| {
+                tree_mesh.render(&Mat4::identity(), camera);
+            }, (aabb.min, aabb.max), 256);
+        let t = 4;
+        let mut positions = Vec::new();
+        let mut angles = Vec::new();
+        for x in -t..t {
+            for y in -t..t {
+                if x != 0 || y != 0 {
+                    positions.push(10.0 * x as f32);
+                    positions.push(0.0);
+                    positions.push(10.0 * y as f32);
+                    angles.push((1.0 + y as f32 / t as f32) * std::f32::consts::PI);
+                }
+            }
+        }
+        imposter.update_positions(&positions, &angles);
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(100.0);
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.color = vec3(0.5, 0.7, 0.3);
+        plane.diffuse_intensity = 0.5;
+        plane.specular_intensity = 0.0;
+
+        renderer.ambient_light().set_intensity(0.1);
+
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_direction(&vec3(1.0, -1.0, -1.0));
+        directional_light.set_intensity(0.5);
+        directional_light.enable_shadows();
+        directional_light.update_shadows(vec3(0.0, 0.0, 0.0), 100.0, 100.0);
+
+        let directional_light = renderer.directional_light(1).unwrap();
+        directional_light.set_direction(&vec3(-1.0, -1.0, 1.0));
+        directional_light.set_intensity(0.5);
+        directional_light.enable_shadows();
+        directional_light.update_shadows(vec3(0.0, 0.0, 0.0), 100.0, 100.0);
+
+        let render_scene = |camera: &Camera| {
+            tree_mesh.render(&Mat4::identity(), camera);
+            imposter.render(camera);
+        };
+        renderer.shadow_pass(&camera, &render_scene);
+        renderer.geometry_pass(&|| {
+            render_scene(&camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
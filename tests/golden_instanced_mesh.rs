@@ -0,0 +1,61 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn instanced_mesh()
+{
+    support::golden_test("instanced_mesh", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 6.0, 9.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let cube_mesh = tri_mesh::MeshBuilder::new().cube().build().unwrap();
+        let mut cubes = InstancedMesh::new(gl, &cube_mesh.indices_buffer(), &cube_mesh.positions_buffer_f32(), &cube_mesh.normals_buffer_f32()).unwrap();
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(20.0);
+        let plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+
+        // A grid of 100 by 100 columns, first all with the color of the mesh and then updated with a height and a color each
+        let instances = |height: &dyn Fn(f32, f32) -> f32| {
+            let mut transformations = Vec::new();
+            for i in 0..100 {
+                for j in 0..100 {
+                    let (x, z) = (-5.0 + 0.1 * i as f32, -5.0 + 0.1 * j as f32);
+                    transformations.push(Mat4::from_translation(vec3(x, 0.0, z)) * Mat4::from_nonuniform_scale(0.04, height(x, z), 0.04));
+                }
+            }
+            transformations
+        };
+        cubes.update_instances(&instances(&|_, _| 0.1), None).unwrap();
+        assert_eq!(cubes.instance_count(), 10000);
+        assert!(matches!(cubes.update_instances(&instances(&|_, _| 0.1), Some(&[vec3(1.0, 0.0, 0.0)])), Err(instanced_mesh::Error::InvalidData {..})));
+
+        let wave = |x: f32, z: f32| 0.3 + 0.25 * (1.5 * x).sin() * (1.5 * z).cos();
+        let colors: Vec<Vec3> = instances(&wave).iter().map(|transformation| {
+            let height = transformation.y.y;
+            vec3(height, 0.6 - 0.5 * height, 1.0 - height)
+        }).collect();
+        cubes.update_instances(&instances(&wave), Some(&colors)).unwrap();
+        assert!((cubes.axis_aligned_bounding_box().max.y - 0.55).abs() < 0.01);
+
+        renderer.ambient_light().set_intensity(0.3);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.7);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -1.0));
+        directional_light.enable_shadows();
+        directional_light.update_shadows(vec3(0.0, 0.0, 0.0), 40.0, 40.0);
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            cubes.render(&Mat4::identity(), camera);
+        });
+        renderer.geometry_pass(&|| {
+            cubes.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
@@ -0,0 +1,39 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn light_range()
+{
+    // The clipped part of the highlight covers only about 40 pixels
+    let tolerance = support::Tolerance {max_mismatch_fraction: 0.0005, ..support::Tolerance::default()};
+    support::golden_test("light_range", 256, 128, tolerance, |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 6.0, 10.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        // A glossy metal floor which mirrors the point light. The light is too weak to be visible on a diffuse surface
+        // beyond a distance of about 10, but the view ray through the highlight is further away, so the highlight is clipped
+        // where it crosses the light volume if the range of the light ignores the specular peak.
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(20.0);
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.physical_material = Some(PhysicalMaterial {albedo: vec3(0.9, 0.9, 0.9), metallic: 1.0, roughness: 0.15, ..PhysicalMaterial::default()});
+
+        renderer.ambient_light().set_intensity(0.1);
+
+        let point_light = renderer.point_light(0).unwrap();
+        point_light.set_intensity(4.0);
+        point_light.set_attenuation(0.5, 0.5, 20.0);
+        point_light.set_position(&vec3(0.0, 6.0, -10.0));
+        assert!(point_light.range().unwrap() > 100.0);
+
+        renderer.geometry_pass(&|| {
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
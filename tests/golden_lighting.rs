@@ -0,0 +1,69 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn lighting()
+{
+    support::golden_test("lighting", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(2.0, 2.0, 5.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.diffuse_intensity = 0.3;
+        monkey.specular_intensity = 0.8;
+        monkey.specular_power = 20.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.diffuse_intensity = 0.3;
+        plane.specular_intensity = 0.8;
+        plane.specular_power = 20.0;
+
+        renderer.ambient_light().set_intensity(0.1);
+
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.3);
+        directional_light.set_direction(&vec3(-2.0, -1.0, 1.0));
+        directional_light.enable_shadows();
+
+        let directional_light = renderer.directional_light(1).unwrap();
+        directional_light.set_intensity(0.3);
+        directional_light.set_direction(&vec3(2.0, -1.0, -1.0));
+        directional_light.enable_shadows();
+
+        let point_light = renderer.point_light(0).unwrap();
+        point_light.set_intensity(0.5);
+        point_light.set_color(&vec3(0.0, 1.0, 0.0));
+        point_light.set_position(&vec3(-5.0, 5.0, 0.0));
+
+        let point_light = renderer.point_light(1).unwrap();
+        point_light.set_intensity(0.5);
+        point_light.set_color(&vec3(1.0, 0.0, 0.0));
+        point_light.set_position(&vec3(5.0, 5.0, 0.0));
+
+        let spot_light = renderer.spot_light(0).unwrap();
+        spot_light.set_intensity(0.8);
+        spot_light.set_color(&vec3(0.0, 0.0, 1.0));
+        spot_light.set_cutoff(0.1 * std::f32::consts::PI);
+        spot_light.set_attenuation(0.1, 0.001, 0.0001);
+        spot_light.set_position(&vec3(4.0, 5.0, 3.0));
+        spot_light.set_direction(&-vec3(4.0, 5.0, 3.0));
+        spot_light.enable_shadows();
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            monkey.render(&Mat4::identity(), camera);
+        });
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
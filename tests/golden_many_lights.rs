@@ -0,0 +1,64 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn many_lights()
+{
+    support::golden_test("many_lights", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 30.0, 40.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(12.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.diffuse_intensity = 0.5;
+        monkey.specular_intensity = 0.8;
+        monkey.specular_power = 20.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.diffuse_intensity = 0.5;
+        plane.specular_intensity = 0.2;
+        plane.specular_power = 5.0;
+
+        renderer.ambient_light().set_intensity(0.05);
+
+        // A grid of small point lights, each only affecting a small part of the screen
+        for i in 0..36 {
+            let (x, z) = ((i % 6) as f32 * 3.0 - 7.5, (i / 6) as f32 * 3.0 - 7.5);
+            let point_light = renderer.point_light(i).unwrap();
+            point_light.set_intensity(3.0);
+            point_light.set_color(&vec3((i % 3) as f32 * 0.5, ((i + 1) % 3) as f32 * 0.5, ((i + 2) % 3) as f32 * 0.5));
+            point_light.set_attenuation(1.0, 0.0, 30.0);
+            point_light.set_position(&vec3(x, -0.5, z));
+        }
+
+        // Spot lights with shadows from the sides
+        for i in 0..6 {
+            let angle = i as f32 * std::f32::consts::PI / 3.0;
+            let position = vec3(3.0 * angle.cos(), 2.0, 3.0 * angle.sin());
+            let spot_light = renderer.spot_light(i).unwrap();
+            spot_light.set_intensity(6.0);
+            spot_light.set_color(&vec3(1.0, 1.0, 0.8));
+            spot_light.set_cutoff(0.9);
+            spot_light.set_attenuation(1.0, 0.0, 2.0);
+            spot_light.set_position(&position);
+            spot_light.set_direction(&-position);
+            spot_light.enable_shadows();
+        }
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            monkey.render(&Mat4::identity(), camera);
+        });
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
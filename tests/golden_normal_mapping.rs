@@ -0,0 +1,55 @@
+mod support;
+
+use dust::*;
+use std::rc::Rc;
+
+#[test]
+fn normal_mapping()
+{
+    support::golden_test("normal_mapping", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 0.0, 8.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), degrees(25.0), width as f32 / height as f32, 0.1, 100.0);
+
+        // A normal map with a round bump in the middle of each tile
+        let size = 32;
+        let mut data = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let (dx, dy) = ((x as f32 + 0.5 - size as f32 * 0.5) / 12.0, (y as f32 + 0.5 - size as f32 * 0.5) / 12.0);
+                let d = dx * dx + dy * dy;
+                let normal = if d < 1.0 { vec3(dx, dy, (1.0 - d).sqrt()) } else { vec3(0.0, 0.0, 1.0) };
+                data.extend(&[(127.5 + 127.5 * normal.x) as u8, (127.5 + 127.5 * normal.y) as u8, (127.5 + 127.5 * normal.z) as u8]);
+            }
+        }
+        let mut normal_map = texture::Texture2D::new(gl, size, size).unwrap();
+        normal_map.fill_with_u8(size, size, &data);
+        let normal_map = Rc::new(normal_map);
+
+        // A plain quad, a normal mapped quad and a normal mapped quad where the uv coordinates are rotated, which should look like the second
+        let indices = [0, 1, 2, 0, 2, 3];
+        let positions = [-0.9, -0.9, 0.0, 0.9, -0.9, 0.0, 0.9, 0.9, 0.0, -0.9, 0.9, 0.0];
+        let normals = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+        let uvs = [0.0, 0.0, 3.0, 0.0, 3.0, 3.0, 0.0, 3.0];
+        let rotated_uvs = [3.0, 0.0, 3.0, 3.0, 0.0, 3.0, 0.0, 0.0];
+        let plain = Mesh::new(gl, &indices, &positions, &normals).unwrap();
+        let mut mapped = Mesh::new_with_uvs(gl, &indices, &positions, &normals, &uvs).unwrap();
+        mapped.normal_map = Some(normal_map.clone());
+        let mut rotated = Mesh::new_with_uvs(gl, &indices, &positions, &normals, &rotated_uvs).unwrap();
+        rotated.normal_map = Some(normal_map);
+
+        renderer.ambient_light().set_intensity(0.2);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(1.0);
+        directional_light.set_direction(&vec3(1.0, -1.0, -0.5));
+
+        renderer.geometry_pass(&|| {
+            plain.render(&Mat4::from_translation(vec3(-2.0, 0.0, 0.0)), &camera);
+            mapped.render(&Mat4::identity(), &camera);
+            rotated.render(&Mat4::from_translation(vec3(2.0, 0.0, 0.0)), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
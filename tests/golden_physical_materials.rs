@@ -0,0 +1,76 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn physical_materials()
+{
+    support::golden_test("physical_materials", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 0.0, 14.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(30.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        // Dielectric spheres in the top row and metallic spheres in the bottom row, both getting rougher from left to right.
+        // The last column has an emissive sphere and a sphere with the Phong model.
+        let mut spheres = Vec::new();
+        for column in 0..6 {
+            for row in 0..2 {
+                let mut sphere = sphere(gl);
+                let roughness = 0.1 + 0.2 * column as f32;
+                sphere.physical_material = match (column, row) {
+                    (5, 0) => Some(PhysicalMaterial {albedo: vec3(0.1, 0.1, 0.1), emissive: vec3(1.0, 0.5, 0.1), ..PhysicalMaterial::default()}),
+                    (5, 1) => None,
+                    (_, 0) => Some(PhysicalMaterial {albedo: vec3(0.8, 0.1, 0.1), metallic: 0.0, roughness, ..PhysicalMaterial::default()}),
+                    _ => Some(PhysicalMaterial {albedo: vec3(1.0, 0.8, 0.4), metallic: 1.0, roughness, ambient_occlusion: 0.5, ..PhysicalMaterial::default()})
+                };
+                let translation = Mat4::from_translation(vec3(2.2 * column as f32 - 5.5, 1.1 - 2.2 * row as f32, 0.0));
+                spheres.push((sphere, translation));
+            }
+        }
+
+        renderer.ambient_light().set_intensity(0.2);
+
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(1.0);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -1.0));
+
+        let point_light = renderer.point_light(0).unwrap();
+        point_light.set_intensity(1.0);
+        point_light.set_color(&vec3(0.5, 0.7, 1.0));
+        point_light.set_attenuation(0.5, 0.05, 0.01);
+        point_light.set_position(&vec3(3.0, 2.0, 4.0));
+
+        renderer.geometry_pass(&|| {
+            for (sphere, translation) in spheres.iter() {
+                sphere.render(translation, &camera);
+            }
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
+
+// A unit sphere with smooth normals
+fn sphere(gl: &Gl) -> Mesh
+{
+    let (no_rings, no_segments) = (24, 48);
+    let mut positions = Vec::new();
+    for ring in 0..no_rings + 1 {
+        let theta = std::f32::consts::PI * ring as f32 / no_rings as f32;
+        for segment in 0..no_segments + 1 {
+            let phi = 2.0 * std::f32::consts::PI * segment as f32 / no_segments as f32;
+            positions.extend(&[theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()]);
+        }
+    }
+    let mut indices = Vec::new();
+    for ring in 0..no_rings {
+        for segment in 0..no_segments {
+            let i = ring * (no_segments + 1) + segment;
+            let j = i + no_segments + 1;
+            indices.extend(&[i, i + 1, j, i + 1, j + 1, j]);
+        }
+    }
+    Mesh::new(gl, &indices, &positions, &positions).unwrap()
+}
@@ -0,0 +1,55 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn point_light_shadows()
+{
+    support::golden_test("point_light_shadows", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(4.0, 4.0, 7.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.diffuse_intensity = 0.5;
+        monkey.specular_intensity = 0.5;
+        monkey.specular_power = 20.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.diffuse_intensity = 0.5;
+        plane.specular_intensity = 0.2;
+        plane.specular_power = 5.0;
+
+        renderer.ambient_light().set_intensity(0.1);
+
+        // Placed next to the monkey so that the shadows fall in several directions and the light covers several cube map faces
+        let point_light = renderer.point_light(0).unwrap();
+        point_light.set_intensity(1.0);
+        point_light.set_color(&vec3(1.0, 0.9, 0.7));
+        point_light.set_attenuation(0.5, 0.1, 0.02);
+        point_light.set_position(&vec3(-1.5, 1.5, 0.5));
+        point_light.enable_shadows();
+
+        let point_light = renderer.point_light(1).unwrap();
+        point_light.set_intensity(0.5);
+        point_light.set_color(&vec3(0.3, 0.5, 1.0));
+        point_light.set_position(&vec3(2.5, 0.5, -1.0));
+        point_light.enable_shadows();
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            monkey.render(&Mat4::identity(), camera);
+            plane.render(&Mat4::identity(), camera);
+        });
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
@@ -0,0 +1,50 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn post_process_chain()
+{
+    support::golden_test("post_process_chain", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.2, 0.3, 0.6, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 1.5, 6.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.color = vec3(0.5, 1.0, 0.5);
+        monkey.specular_intensity = 1.0;
+        monkey.specular_power = 30.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(20.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.color = vec3(0.5, 0.5, 0.5);
+
+        renderer.ambient_light().set_intensity(0.3);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(1.5);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -1.0));
+
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+        renderer.hdr_light_pass(&camera).unwrap();
+
+        // The chain and effects are created with another size, so they are resized to the given screen size
+        let mut chain = effects::PostProcessChain::new(gl, width / 2, height / 2).unwrap();
+        let mut fog_effect = effects::FogEffect::new(gl).unwrap();
+        fog_effect.color = vec3(0.3, 0.3, 0.4);
+        fog_effect.density = 0.08;
+        let mut bloom_effect = effects::BloomEffect::new(gl, width / 2, height / 2, 4).unwrap();
+        bloom_effect.threshold = 1.0;
+        let mut tone_mapping_effect = effects::ToneMappingEffect::new(gl).unwrap();
+
+        let target = effects::PostProcessTarget {width, height, elapsed_time: 16.0, bind: &|| offscreen.bind()};
+        let other_renderer = DeferredPipeline::new(gl, width / 2, height / 2, vec4(0.2, 0.3, 0.6, 1.0)).unwrap();
+        assert!(matches!(chain.apply_render_to_rendertarget(&target, &camera, &other_renderer, &mut []), Err(effects::Error::InvalidSize {..})));
+        chain.apply_render_to_rendertarget(&target, &camera, &renderer, &mut [&mut fog_effect, &mut bloom_effect, &mut tone_mapping_effect]).unwrap();
+    });
+}
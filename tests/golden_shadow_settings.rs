@@ -0,0 +1,70 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn shadow_settings()
+{
+    support::golden_test("shadow_settings", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 7.0, 8.0), vec3(0.0, -1.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.diffuse_intensity = 0.5;
+        monkey.specular_intensity = 0.5;
+        monkey.specular_power = 20.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.diffuse_intensity = 0.5;
+        plane.specular_intensity = 0.2;
+        plane.specular_power = 5.0;
+
+        renderer.ambient_light().set_intensity(0.1);
+
+        // A low resolution shadow map without filtering gives jagged shadow edges
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.4);
+        directional_light.set_color(&vec3(0.3, 0.5, 1.0));
+        directional_light.set_direction(&vec3(1.0, -1.0, 0.0));
+        directional_light.set_shadow_settings(ShadowSettings {resolution: 128, filter: ShadowFilter::Hard, ..ShadowSettings::default()});
+        directional_light.enable_shadows();
+        directional_light.update_shadows(vec3(0.0, 0.0, 0.0), 6.0, 20.0);
+
+        // Soft shadows which are sharp close to the monkey and blurred further away
+        let spot_light = renderer.spot_light(0).unwrap();
+        spot_light.set_intensity(0.8);
+        spot_light.set_color(&vec3(1.0, 0.9, 0.7));
+        spot_light.set_attenuation(0.5, 0.05, 0.005);
+        spot_light.set_position(&vec3(-3.0, 4.0, 1.0));
+        spot_light.set_direction(&vec3(0.6, -1.0, -0.2));
+        spot_light.set_cutoff(0.8);
+        spot_light.set_shadow_settings(ShadowSettings {near: 1.0, far: 20.0, filter: ShadowFilter::Pcss {light_size: 1.0, kernel_size: 6}, ..ShadowSettings::default()});
+        spot_light.enable_shadows();
+
+        // A wide filter kernel
+        let point_light = renderer.point_light(0).unwrap();
+        point_light.set_intensity(0.6);
+        point_light.set_color(&vec3(1.0, 0.4, 0.4));
+        point_light.set_attenuation(0.5, 0.1, 0.02);
+        point_light.set_position(&vec3(2.0, 1.5, 2.0));
+        point_light.set_shadow_settings(ShadowSettings {resolution: 256, near: 0.5, far: 20.0, filter: ShadowFilter::Pcf {kernel_size: 5}, ..ShadowSettings::default()});
+        point_light.enable_shadows();
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            monkey.render(&Mat4::identity(), camera);
+            plane.render(&Mat4::identity(), camera);
+        });
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
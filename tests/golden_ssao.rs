@@ -0,0 +1,48 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn ssao()
+{
+    support::golden_test("ssao", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(2.0, 2.0, 5.0), vec3(0.0, -0.3, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+
+        let mut cube_mesh = tri_mesh::MeshBuilder::new().cube().build().unwrap();
+        cube_mesh.scale(0.5);
+        cube_mesh.translate(tri_mesh::prelude::vec3(-1.8, -0.5, -0.5));
+        let cube = Mesh::new_with_computed_normals(gl, &cube_mesh.indices_buffer(), &cube_mesh.positions_buffer_f32()).unwrap();
+
+        // Mostly ambient light, so the occlusion in the creases of the monkey and where the objects meet the floor is visible
+        renderer.ambient_light().set_intensity(0.8);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.2);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -1.0));
+
+        renderer.enable_ssao().unwrap();
+        let effect = renderer.ssao_effect().unwrap();
+        effect.radius = 0.6;
+        effect.no_samples = 24;
+        effect.intensity = 1.5;
+
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::from_translation(vec3(0.0, -0.3, 0.0)), &camera);
+            plane.render(&Mat4::identity(), &camera);
+            cube.render(&Mat4::identity(), &camera);
+        }).unwrap();
+        renderer.ssao_pass(&camera).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
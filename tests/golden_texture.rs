@@ -0,0 +1,37 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn texture()
+{
+    support::golden_test("texture", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.0, 0.0, 0.0, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(5.0, 5.0, 5.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let box_mesh = tri_mesh::MeshBuilder::new().unconnected_cube().build().unwrap();
+        let mut box_mesh = Mesh::new(gl, &box_mesh.indices_buffer(), &box_mesh.positions_buffer_f32(), &box_mesh.normals_buffer_f32()).unwrap();
+        box_mesh.texture = Some(std::rc::Rc::new(texture::Texture2D::new_from_bytes(gl, include_bytes!("../examples/assets/textures/test_texture.jpg")).unwrap()));
+
+        let texture3d = texture::Texture3D::new_from_bytes(gl,
+                                                           include_bytes!("../examples/assets/textures/skybox_evening/back.jpg"),
+                                                           include_bytes!("../examples/assets/textures/skybox_evening/front.jpg"),
+                                                           include_bytes!("../examples/assets/textures/skybox_evening/top.jpg"),
+                                                           include_bytes!("../examples/assets/textures/skybox_evening/left.jpg"),
+                                                           include_bytes!("../examples/assets/textures/skybox_evening/right.jpg")).unwrap();
+        let skybox = objects::Skybox::new(gl, texture3d);
+
+        renderer.ambient_light().set_intensity(0.2);
+        renderer.directional_light(0).unwrap().set_intensity(1.0);
+
+        renderer.geometry_pass(&|| {
+            box_mesh.render(&Mat4::identity(), &camera);
+            skybox.render(&camera).unwrap();
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
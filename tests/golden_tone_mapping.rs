@@ -0,0 +1,59 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn tone_mapping()
+{
+    support::golden_test("tone_mapping", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        // The same high dynamic range image tone mapped with each operator side by side, leaving the last column of pixels empty
+        let (width, height) = (offscreen.width / 3, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.2, 0.3, 0.6, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 1.0, 4.5), vec3(0.0, -0.2, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let mut monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+        monkey.color = vec3(1.0, 0.6, 0.3);
+        monkey.specular_intensity = 0.8;
+        monkey.specular_power = 20.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+
+        // Lights which are far brighter than what can be displayed
+        renderer.ambient_light().set_intensity(0.5);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(3.0);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -1.0));
+        directional_light.enable_shadows();
+
+        let point_light = renderer.point_light(0).unwrap();
+        point_light.set_intensity(8.0);
+        point_light.set_color(&vec3(1.0, 0.3, 0.3));
+        point_light.set_position(&vec3(1.5, 0.0, 1.0));
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            monkey.render(&Mat4::identity(), camera);
+        });
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+        renderer.hdr_light_pass(&camera).unwrap();
+
+        let mut effect = effects::ToneMappingEffect::new(gl).unwrap();
+        let settings = [(effects::ToneMappingOperator::Reinhard, false), (effects::ToneMappingOperator::AcesFilmic, false),
+                        (effects::ToneMappingOperator::Uncharted2, true)];
+        for (i, (operator, eye_adaptation)) in settings.iter().enumerate() {
+            effect.operator = *operator;
+            effect.eye_adaptation = *eye_adaptation;
+            effect.update_eye_adaptation(renderer.hdr_texture(), 16.0).unwrap();
+
+            offscreen.bind();
+            gl.viewport((i * width) as i32, 0, width as i32, height as i32);
+            effect.apply(renderer.hdr_texture()).unwrap();
+        }
+    });
+}
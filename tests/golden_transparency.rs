@@ -0,0 +1,91 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn transparency()
+{
+    support::golden_test("transparency", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, vec3(0.0, 2.0, 9.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(40.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap().to_mesh(gl).unwrap();
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(10.0);
+        plane_mesh.translate(tri_mesh::prelude::vec3(0.0, -1.0, 0.0));
+        let plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+
+        // Three overlapping translucent spheres on each side of the monkey, where one of them partly intersects the floor
+        let colors = [vec3(1.0, 0.2, 0.2), vec3(0.2, 1.0, 0.2), vec3(0.2, 0.4, 1.0)];
+        let offsets = [vec3(-0.5, 0.0, 1.0), vec3(0.5, 0.0, 1.5), vec3(0.0, -0.6, 2.0)];
+        let mut spheres = Vec::new();
+        for (color, offset) in colors.iter().zip(offsets.iter()) {
+            let mut sphere = sphere(gl);
+            sphere.color = *color;
+            sphere.opacity = 0.5;
+            sphere.specular_intensity = 0.8;
+            sphere.specular_power = 20.0;
+            spheres.push((sphere, *offset));
+        }
+        let side = |x: f32| -> Vec<(&Mesh, Mat4)> {
+            spheres.iter().map(|(sphere, offset)| (sphere, Mat4::from_translation(vec3(x, 0.0, 0.0) + *offset) * Mat4::from_scale(0.7))).collect()
+        };
+
+        renderer.ambient_light().set_intensity(0.3);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.6);
+        directional_light.set_direction(&vec3(-1.0, -1.0, -1.0));
+        directional_light.enable_shadows();
+
+        let point_light = renderer.point_light(0).unwrap();
+        point_light.set_intensity(0.5);
+        point_light.set_position(&vec3(0.0, 3.0, 3.0));
+
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            monkey.render(&Mat4::identity(), camera);
+        });
+        renderer.geometry_pass(&|| {
+            monkey.render(&Mat4::identity(), &camera);
+            plane.render(&Mat4::identity(), &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+
+        // Sorted to the left and weighted blended to the right
+        renderer.transparent_pass(&camera, &side(-2.3)).unwrap();
+        offscreen.bind();
+        renderer.transparent_composite_pass_render_to_rendertarget().unwrap();
+
+        renderer.transparency_mode = TransparencyMode::WeightedBlended;
+        renderer.transparent_pass(&camera, &side(2.3)).unwrap();
+        offscreen.bind();
+        renderer.transparent_composite_pass_render_to_rendertarget().unwrap();
+    });
+}
+
+// A unit sphere with smooth normals
+fn sphere(gl: &Gl) -> Mesh
+{
+    let (no_rings, no_segments) = (24, 48);
+    let mut positions = Vec::new();
+    for ring in 0..no_rings + 1 {
+        let theta = std::f32::consts::PI * ring as f32 / no_rings as f32;
+        for segment in 0..no_segments + 1 {
+            let phi = 2.0 * std::f32::consts::PI * segment as f32 / no_segments as f32;
+            positions.extend(&[theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()]);
+        }
+    }
+    let mut indices = Vec::new();
+    for ring in 0..no_rings {
+        for segment in 0..no_segments {
+            let i = ring * (no_segments + 1) + segment;
+            let j = i + no_segments + 1;
+            indices.extend(&[i, i + 1, j, i + 1, j + 1, j]);
+        }
+    }
+    Mesh::new(gl, &indices, &positions, &positions).unwrap()
+}
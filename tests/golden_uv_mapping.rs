@@ -0,0 +1,55 @@
+mod support;
+
+use dust::*;
+use std::rc::Rc;
+
+#[test]
+fn uv_mapping()
+{
+    support::golden_test("uv_mapping", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let camera = Camera::new_orthographic(gl, vec3(0.0, 0.0, 5.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), 8.2, 4.1, 10.0);
+
+        // A quad with uv coordinates from -1 to 2, stored and read back as a CPU mesh
+        let cpu_mesh = CPUMesh {
+            indices: vec![0, 1, 2, 0, 2, 3],
+            positions: vec![-0.9, -0.9, 0.0, 0.9, -0.9, 0.0, 0.9, 0.9, 0.0, -0.9, 0.9, 0.0],
+            normals: vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0],
+            uvs: Some(vec![-1.0, 2.0, 2.0, 2.0, 2.0, -1.0, -1.0, -1.0]),
+            ..Default::default()
+        };
+        let cpu_mesh = CPUMesh::new(&cpu_mesh.to_bytes().unwrap()).unwrap();
+        assert_eq!(cpu_mesh.uvs.as_ref().map(|uvs| uvs.len()), Some(8));
+
+        // Repeat, mirrored repeat and clamp to edge with linear and nearest filtering, and last triplanar mapping
+        let settings = [(Wrapping::Repeat, Interpolation::Linear, TextureMapping::UV),
+                        (Wrapping::MirroredRepeat, Interpolation::Linear, TextureMapping::UV),
+                        (Wrapping::ClampToEdge, Interpolation::Nearest, TextureMapping::UV),
+                        (Wrapping::Repeat, Interpolation::Linear, TextureMapping::Triplanar)];
+        let mut meshes = Vec::new();
+        for (wrapping, interpolation, mapping) in settings.iter() {
+            let texture = texture::Texture2D::new_from_bytes(gl, include_bytes!("../examples/assets/textures/test_texture.jpg")).unwrap();
+            texture.set_wrapping(*wrapping, *wrapping);
+            texture.set_interpolation(*interpolation, *interpolation, None);
+            let mut mesh = cpu_mesh.to_mesh(gl).unwrap();
+            mesh.texture = Some(Rc::new(texture));
+            mesh.texture_mapping = *mapping;
+            meshes.push(mesh);
+        }
+
+        renderer.ambient_light().set_intensity(0.5);
+        let directional_light = renderer.directional_light(0).unwrap();
+        directional_light.set_intensity(0.5);
+        directional_light.set_direction(&vec3(0.0, 0.0, -1.0));
+
+        renderer.geometry_pass(&|| {
+            for (i, mesh) in meshes.iter().enumerate() {
+                mesh.render(&Mat4::from_translation(vec3(-3.0 + 2.0 * i as f32, 0.0, 0.0)), &camera);
+            }
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
@@ -0,0 +1,55 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn wireframe()
+{
+    support::golden_test("wireframe", 256, 128, support::Tolerance::default(), |gl, offscreen| {
+        let (width, height) = (offscreen.width, offscreen.height);
+        let scene_center = vec3(0.0, 2.0, 0.0);
+        let scene_radius = 6.0;
+        let mut renderer = DeferredPipeline::new(gl, width, height, vec4(0.8, 0.8, 0.8, 1.0)).unwrap();
+        let camera = Camera::new_perspective(gl, scene_center + scene_radius * vec3(0.6, 0.6, 1.0).normalize(), scene_center, vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        let cpu_mesh = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap();
+        let mut wireframe = objects::Wireframe::new(gl, &cpu_mesh.indices, &cpu_mesh.positions, 0.01);
+        wireframe.set_parameters(0.8, 0.2, 5.0);
+        wireframe.set_color(&vec3(0.9, 0.2, 0.2));
+
+        let mut model = Mesh::new(gl, &cpu_mesh.indices, &cpu_mesh.positions, &cpu_mesh.normals).unwrap();
+        model.diffuse_intensity = 0.2;
+        model.specular_intensity = 0.4;
+        model.specular_power = 20.0;
+
+        let mut plane_mesh = tri_mesh::MeshBuilder::new().plane().build().unwrap();
+        plane_mesh.scale(100.0);
+        let mut plane = Mesh::new(gl, &plane_mesh.indices_buffer(), &plane_mesh.positions_buffer_f32(), &plane_mesh.normals_buffer_f32()).unwrap();
+        plane.diffuse_intensity = 0.2;
+        plane.specular_intensity = 0.4;
+        plane.specular_power = 20.0;
+
+        for (index, (x, z)) in [(5.0, 5.0), (-5.0, 5.0), (-5.0, -5.0), (5.0, -5.0)].iter().enumerate() {
+            let light = renderer.spot_light(index).unwrap();
+            light.set_intensity(0.3);
+            light.set_position(&vec3(*x, 7.0, *z));
+            light.set_direction(&vec3(-*x, -5.0, -*z));
+            light.enable_shadows();
+        }
+
+        let transformation = Mat4::from_translation(vec3(0.0, 2.0, 0.0));
+        renderer.shadow_pass(&camera, &|camera: &Camera| {
+            model.render(&transformation, camera);
+            wireframe.render(&transformation, camera);
+        });
+        renderer.geometry_pass(&|| {
+            model.render(&transformation, &camera);
+            plane.render(&Mat4::identity(), &camera);
+            wireframe.render(&transformation, &camera);
+        }).unwrap();
+
+        offscreen.bind();
+        renderer.light_pass_render_to_rendertarget(&camera).unwrap();
+    });
+}
@@ -0,0 +1,55 @@
+mod support;
+
+use dust::*;
+use std::rc::Rc;
+
+#[test]
+fn ray_and_id_buffer_picking_agree()
+{
+    support::headless_test("ray_and_id_buffer_picking_agree", |gl| {
+        let (width, height) = (256, 128);
+        let camera = Camera::new_perspective(gl, vec3(0.0, 2.0, 6.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0),
+                                             degrees(45.0), width as f32 / height as f32, 0.1, 1000.0);
+
+        // A monkey and a box, where the hierarchies are built from the same geometry as the meshes
+        let monkey = CPUMesh::new(include_bytes!("../examples/assets/models/suzanne.3d")).unwrap();
+        let box_mesh = tri_mesh::MeshBuilder::new().unconnected_cube().build().unwrap();
+        let (box_indices, box_positions) = (box_mesh.indices_buffer(), box_mesh.positions_buffer_f32());
+        let meshes = [monkey.to_mesh(gl).unwrap(), Mesh::new(gl, &box_indices, &box_positions, &box_mesh.normals_buffer_f32()).unwrap()];
+        let transformations = [Mat4::from_translation(vec3(-1.5, 0.0, 0.0)) * Mat4::from_angle_y(degrees(30.0)),
+                               Mat4::from_translation(vec3(1.5, 0.0, -0.5)) * Mat4::from_angle_y(degrees(-20.0)) * Mat4::from_nonuniform_scale(0.8, 1.0, 0.8)];
+
+        let mut ray_picker = RayPicker::new();
+        ray_picker.add_object(0, Rc::new(Bvh::new(&monkey.indices, &monkey.positions)), &transformations[0]).unwrap();
+        ray_picker.add_object(1, Rc::new(Bvh::new(&box_indices, &box_positions)), &Mat4::identity()).unwrap();
+        ray_picker.set_transformation(1, &transformations[1]).unwrap();
+        let id_picker = IdBufferPicker::new(gl, width, height).unwrap();
+
+        // Both pickers find the same objects at the same points at the centers of the pixels of a grid
+        let mut hits = Vec::new();
+        for i in 0..16 {
+            for j in 0..8 {
+                let (x, y) = (width * (2 * i + 1) / 32, height * (2 * j + 1) / 16);
+                let screen_coordinates = ((x as f64 + 0.5) / width as f64, (y as f64 + 0.5) / height as f64);
+                let ray_result = ray_picker.pick(&camera, screen_coordinates);
+                let id_result = id_picker.pick(&camera, screen_coordinates, &|pass: &IdPass| {
+                    for (object, (mesh, transformation)) in meshes.iter().zip(transformations.iter()).enumerate() {
+                        pass.render_mesh(object, mesh, transformation).unwrap();
+                    }
+                }).unwrap();
+                match (ray_result, id_result) {
+                    (Some(ray), Some(id)) => {
+                        assert_eq!(ray.object, id.object);
+                        assert!((ray.position - id.position).magnitude() < 0.01, "{:?} {:?}", ray, id);
+                        assert!((ray.distance - id.distance).abs() < 0.01 && (ray.distance - (ray.position - camera.position()).magnitude()).abs() < 0.001);
+                        assert!(ray.normal.dot(id.normal) > 0.5 && ray.normal.dot(camera.position() - ray.position) > 0.0);
+                        hits.push(ray);
+                    },
+                    (None, None) => {},
+                    results => panic!("The pickers disagree at {:?}: {:?}", screen_coordinates, results)
+                }
+            }
+        }
+        assert!(hits.iter().any(|hit| hit.object == 0) && hits.iter().any(|hit| hit.object == 1) && hits.len() < 128);
+    });
+}
@@ -0,0 +1,22 @@
+mod support;
+
+use dust::*;
+
+#[test]
+fn scene_places_the_attached_lights()
+{
+    support::headless_test("scene_places_the_attached_lights", |gl| {
+        let mut renderer = DeferredPipeline::new(gl, 256, 128, vec4(0.1, 0.1, 0.1, 1.0)).unwrap();
+        let mut scene = Scene::new();
+        let pivot = scene.add_node(None, NodeTransform::from_rotation(Quat::from_angle_y(degrees(90.0)))).unwrap();
+        let lamp = scene.add_node(Some(pivot), NodeTransform::from_translation(vec3(0.0, 1.0, 2.0))).unwrap();
+        scene.attach_light(lamp, SceneLight::Point(0)).unwrap();
+        scene.attach_light(lamp, SceneLight::Spot(0)).unwrap();
+
+        scene.update_lights(&mut renderer).unwrap();
+        assert!((renderer.point_light(0).unwrap().position() - vec3(2.0, 1.0, 0.0)).magnitude() < 0.0001);
+        let spot_light = renderer.spot_light(0).unwrap();
+        assert!((spot_light.position() - vec3(2.0, 1.0, 0.0)).magnitude() < 0.0001);
+        assert!((spot_light.direction() - vec3(-1.0, 0.0, 0.0)).magnitude() < 0.0001);
+    });
+}
@@ -0,0 +1,215 @@
+//!
+//! Golden-image test support: renders a named scene into an offscreen render target of a headless context,
+//! reads it back and compares it against the reference image `tests/golden/<name>.png`.
+//!
+//! Run with the environment variable `DUST_UPDATE_GOLDEN=1` to (re)write the reference images.
+//! On a mismatch, the actual image and a difference image are written to `<target tmpdir>/golden/`.
+//! Tests which need a headless context but no image use `headless_test` instead.
+//! A test fails if no headless context can be created, unless the environment variable `DUST_SKIP_GOLDEN=1` is set to skip it.
+//!
+#![allow(dead_code)]
+
+use dust::*;
+
+/// An offscreen color and depth target which the scene renders into.
+pub struct Offscreen {
+    rendertarget: RenderTarget,
+    color_texture: Texture2D,
+    depth_texture: Texture2D,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Offscreen
+{
+    fn new(gl: &Gl, width: usize, height: usize) -> Offscreen
+    {
+        Offscreen {
+            rendertarget: RenderTarget::new(gl, 1).unwrap(),
+            color_texture: Texture2D::new_as_color_target(gl, width, height).unwrap(),
+            depth_texture: Texture2D::new_as_depth_target(gl, width, height).unwrap(),
+            width, height }
+    }
+
+    /// Binds the offscreen target for writing. Must be called again after any pass which binds another target,
+    /// for example before `DeferredPipeline::light_pass_render_to_rendertarget`.
+    pub fn bind(&self)
+    {
+        self.rendertarget.write_to_color_and_depth(&self.color_texture, &self.depth_texture).unwrap();
+    }
+
+    pub fn clear(&self, color: &Vec4)
+    {
+        self.bind();
+        self.rendertarget.clear_color_and_depth(color);
+    }
+
+    /// Returns the RGB pixels with the top row first.
+    fn pixels(&self) -> Vec<u8>
+    {
+        let mut pixels = vec![0u8; self.width * self.height * 3];
+        self.rendertarget.pixels(self.width, self.height, &mut pixels);
+        let row_length = self.width * 3;
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks(row_length).rev() {
+            flipped.extend_from_slice(row);
+        }
+        flipped
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    /// The largest difference in any color channel for which two pixels are considered equal.
+    pub per_channel: u8,
+    /// The largest fraction of pixels which are allowed to differ by more than `per_channel`.
+    pub max_mismatch_fraction: f32,
+    /// The largest allowed mean perceptual difference (CIE76 delta E) over all pixels.
+    pub max_mean_delta_e: f32
+}
+
+impl Default for Tolerance
+{
+    fn default() -> Self
+    {
+        Tolerance { per_channel: 8, max_mismatch_fraction: 0.005, max_mean_delta_e: 0.5 }
+    }
+}
+
+#[derive(Debug)]
+pub struct Comparison {
+    pub mismatched_pixels: usize,
+    pub mean_delta_e: f32,
+    pub max_delta_e: f32,
+    delta_e: Vec<f32>,
+    mismatched: Vec<bool>
+}
+
+impl Comparison
+{
+    fn new(actual: &[u8], reference: &[u8], per_channel: u8) -> Comparison
+    {
+        let mut delta_e = Vec::with_capacity(actual.len() / 3);
+        let mut mismatched = Vec::with_capacity(actual.len() / 3);
+        for (a, r) in actual.chunks(3).zip(reference.chunks(3)) {
+            mismatched.push(a.iter().zip(r.iter()).any(|(x, y)| (*x as i32 - *y as i32).abs() > per_channel as i32));
+            delta_e.push((to_lab(a) - to_lab(r)).magnitude());
+        }
+        let mismatched_pixels = mismatched.iter().filter(|m| **m).count();
+        let mean_delta_e = delta_e.iter().sum::<f32>() / delta_e.len().max(1) as f32;
+        let max_delta_e = delta_e.iter().cloned().fold(0.0, f32::max);
+        Comparison { mismatched_pixels, mean_delta_e, max_delta_e, delta_e, mismatched }
+    }
+
+    fn passes(&self, tolerance: &Tolerance) -> bool
+    {
+        self.mismatched_pixels as f32 <= tolerance.max_mismatch_fraction * self.mismatched.len() as f32
+            && self.mean_delta_e <= tolerance.max_mean_delta_e
+    }
+
+    /// A darkened gray version of the actual image with the mismatching pixels in red, brighter the larger the perceptual difference.
+    fn diff_image(&self, actual: &[u8]) -> Vec<u8>
+    {
+        let mut image = Vec::with_capacity(actual.len());
+        for (i, pixel) in actual.chunks(3).enumerate() {
+            if self.mismatched[i] {
+                let intensity = (64.0 + 191.0 * (self.delta_e[i] / 20.0).min(1.0)) as u8;
+                image.extend_from_slice(&[intensity, 0, 0]);
+            }
+            else {
+                let gray = ((pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32) / 9) as u8;
+                image.extend_from_slice(&[gray, gray, gray]);
+            }
+        }
+        image
+    }
+}
+
+///
+/// Renders the scene into a `width` x `height` offscreen target, which is bound and cleared to black before `scene` is called,
+/// and compares the result against the reference image with the given name.
+/// If no headless context can be created on this machine, the test fails, or is skipped with a message when `DUST_SKIP_GOLDEN` is set.
+///
+pub fn golden_test<F>(name: &str, width: usize, height: usize, tolerance: Tolerance, scene: F)
+    where F: FnOnce(&Gl, &Offscreen)
+{
+    let context = match headless_context(name, width, height) {
+        Some(context) => context,
+        None => return
+    };
+    let gl = context.gl();
+    let offscreen = Offscreen::new(&gl, width, height);
+    offscreen.clear(&vec4(0.0, 0.0, 0.0, 1.0));
+    scene(&gl, &offscreen);
+    let actual = offscreen.pixels();
+
+    let reference_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.png", name));
+    if std::env::var("DUST_UPDATE_GOLDEN").is_ok() {
+        std::fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+        save(&reference_path, &actual, width, height);
+        return;
+    }
+
+    let reference = image::open(&reference_path)
+        .unwrap_or_else(|e| panic!("Unable to read the reference image {:?} ({}). Run with DUST_UPDATE_GOLDEN=1 to create it.", reference_path, e))
+        .to_rgb();
+    assert_eq!((reference.width() as usize, reference.height() as usize), (width, height),
+               "The reference image {:?} has a different size than the rendered image", reference_path);
+
+    let comparison = Comparison::new(&actual, &reference.into_raw(), tolerance.per_channel);
+    if !comparison.passes(&tolerance) {
+        let output_directory = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        std::fs::create_dir_all(&output_directory).unwrap();
+        let actual_path = output_directory.join(format!("{}.actual.png", name));
+        let diff_path = output_directory.join(format!("{}.diff.png", name));
+        save(&actual_path, &actual, width, height);
+        save(&diff_path, &comparison.diff_image(&actual), width, height);
+        panic!("Golden image '{}' does not match the reference: {} of {} pixels differ (tolerance {:?}), mean delta E {:.3}, max delta E {:.3}.\nActual: {:?}\nDiff: {:?}",
+               name, comparison.mismatched_pixels, width * height, tolerance, comparison.mean_delta_e, comparison.max_delta_e, actual_path, diff_path);
+    }
+}
+
+///
+/// Runs a test which needs a headless context but does not compare an image, which is skipped like the golden tests.
+/// Like the golden tests, each test must be alone in its test binary, since the render state is cached per process and not per context.
+///
+pub fn headless_test<F>(name: &str, test: F)
+    where F: FnOnce(&Gl)
+{
+    if let Some(context) = headless_context(name, 256, 128) {
+        test(&context.gl());
+    }
+}
+
+fn headless_context(name: &str, width: usize, height: usize) -> Option<HeadlessContext>
+{
+    match HeadlessContext::new(width, height) {
+        Ok(context) => Some(context),
+        Err(e) if std::env::var("DUST_SKIP_GOLDEN").is_ok() => {
+            eprintln!("Skipping the test '{}' since a headless context could not be created: {:?}", name, e);
+            None
+        },
+        Err(e) => panic!("Unable to create a headless context for the test '{}' ({:?}). Set DUST_SKIP_GOLDEN=1 to skip the tests which need one.", name, e)
+    }
+}
+
+fn save(path: &std::path::Path, pixels: &[u8], width: usize, height: usize)
+{
+    image::save_buffer(path, pixels, width as u32, height as u32, image::RGB(8)).unwrap();
+}
+
+/// Converts an sRGB pixel to the CIE L*a*b* color space (D65 white point).
+fn to_lab(pixel: &[u8]) -> Vec3
+{
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    let (r, g, b) = (linear(pixel[0]), linear(pixel[1]), linear(pixel[2]));
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.950_47;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.088_83;
+    let f = |t: f32| if t > 0.008_856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    vec3(116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}